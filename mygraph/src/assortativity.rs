@@ -0,0 +1,360 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Attempts per requested swap before rewiring gives up, e.g. on a complete graph
+const MAX_SWAP_ATTEMPTS: usize = 10;
+
+// Pearson correlation of the pairs, NaN if either side is constant
+fn pearson(pairs: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let (mut n, mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        n += 1.0;
+        sx += x;
+        sy += y;
+        sxx += x * x;
+        syy += y * y;
+        sxy += x * y;
+    }
+    let covariance = sxy / n - (sx / n) * (sy / n);
+    let variance_x = sxx / n - (sx / n) * (sx / n);
+    let variance_y = syy / n - (sy / n) * (sy / n);
+    let r = covariance / (variance_x * variance_y).sqrt();
+    if r.is_finite() {
+        r.clamp(-1.0, 1.0)
+    } else {
+        f64::NAN
+    }
+}
+
+// Rich-club coefficient of a simple graph given as sorted neighbour lists
+fn rich_club(adjacency: &[Vec<usize>]) -> Vec<f64> {
+    let degree: Vec<usize> = adjacency.iter().map(Vec::len).collect();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    // Vertices and edges among vertices with degree above k, from the counts at exactly k
+    let mut vertices_at = vec![0usize; max_degree + 1];
+    let mut edges_at = vec![0usize; max_degree + 1];
+    for (v, neighbors) in adjacency.iter().enumerate() {
+        vertices_at[degree[v]] += 1;
+        for &w in neighbors.iter().filter(|&&w| w > v) {
+            // An edge stays in the club until its lower-degree end drops out
+            edges_at[degree[v].min(degree[w])] += 1;
+        }
+    }
+
+    let (mut vertices, mut edges) = (0, 0);
+    let mut phi = vec![f64::NAN; max_degree + 1];
+    for k in (0..=max_degree).rev() {
+        if vertices >= 2 {
+            phi[k] = 2.0 * edges as f64 / (vertices * (vertices - 1)) as f64;
+        }
+        vertices += vertices_at[k];
+        edges += edges_at[k];
+    }
+    phi
+}
+
+impl Graph {
+    // Correlation of a numeric vertex value across the ends of every edge, seen from
+    // both ends; the arcs of a directed graph are seen from the source only
+    pub fn numeric_assortativity(&self, values: &[f64]) -> f64 {
+        assert_eq!(values.len(), self.vertices, "one value per vertex");
+        pearson(
+            self.adj_list
+                .iter()
+                .enumerate()
+                .flat_map(|(v, neighbors)| neighbors.iter().map(move |&w| (values[v], values[w]))),
+        )
+    }
+
+    // Newman's degree assortativity r: the degree correlation across edges
+    pub fn degree_assortativity(&self) -> f64 {
+        let degree: Vec<f64> = self.degree().into_iter().map(|d| d as f64).collect();
+        self.numeric_assortativity(&degree)
+    }
+
+    // Newman's assortativity coefficient for a categorical vertex attribute, 1 if
+    // every edge joins vertices of the same category, NaN if there is only one
+    pub fn categorical_assortativity(&self, categories: &[usize]) -> f64 {
+        assert_eq!(categories.len(), self.vertices, "one category per vertex");
+        let count = categories.iter().max().map_or(0, |&c| c + 1);
+        let mut within = 0.0;
+        let mut sources = vec![0.0; count];
+        let mut targets = vec![0.0; count];
+        let mut total = 0.0;
+        for (v, neighbors) in self.adj_list.iter().enumerate() {
+            for &w in neighbors {
+                if categories[v] == categories[w] {
+                    within += 1.0;
+                }
+                sources[categories[v]] += 1.0;
+                targets[categories[w]] += 1.0;
+                total += 1.0;
+            }
+        }
+
+        let expected: f64 = sources
+            .iter()
+            .zip(&targets)
+            .map(|(a, b)| a * b / (total * total))
+            .sum();
+        let r = (within / total - expected) / (1.0 - expected);
+        if r.is_finite() {
+            r
+        } else {
+            f64::NAN
+        }
+    }
+
+    // Mean degree of the neighbours of every vertex, NaN for isolated vertices
+    pub fn average_neighbor_degree(&self) -> Vec<f64> {
+        let degree = self.degree();
+        self.adj_list
+            .par_iter()
+            .map(|neighbors| {
+                let total: usize = neighbors.iter().map(|&w| degree[w]).sum();
+                total as f64 / neighbors.len() as f64
+            })
+            .collect()
+    }
+
+    // Degree-correlation profile k_nn(k): the mean neighbour degree over the edges
+    // of vertices with degree k, indexed by k, NaN where no edge has such an end.
+    // Increasing for assortative networks, decreasing for disassortative ones.
+    pub fn knn_by_degree(&self) -> Vec<f64> {
+        let degree = self.degree();
+        let max_degree = degree.iter().copied().max().unwrap_or(0);
+        let mut neighbor_total = vec![0usize; max_degree + 1];
+        let mut edge_ends = vec![0usize; max_degree + 1];
+        for (v, neighbors) in self.adj_list.iter().enumerate() {
+            neighbor_total[degree[v]] += neighbors.iter().map(|&w| degree[w]).sum::<usize>();
+            edge_ends[degree[v]] += neighbors.len();
+        }
+        neighbor_total
+            .into_iter()
+            .zip(edge_ends)
+            .map(|(total, ends)| total as f64 / ends as f64)
+            .collect()
+    }
+
+    // Rich-club coefficient phi(k): density among the vertices with degree above k,
+    // indexed by k, NaN where fewer than two vertices are left. Self-loops and
+    // parallel edges are ignored.
+    pub fn rich_club_coefficient(&self) -> Vec<f64> {
+        rich_club(&simple_adjacency(self))
+    }
+
+    // Copy of the simple graph with its edges rewired by double edge swaps
+    // (Maslov & Sneppen): a-b, c-d become a-d, c-b unless that would create a
+    // self-loop or parallel edge. Every vertex keeps its degree. Runs until
+    // `swaps` swaps succeeded or ten times as many were attempted.
+    pub fn degree_preserving_rewire(&self, swaps: usize, seed: u64) -> Graph {
+        let adjacency = simple_adjacency(self);
+        let mut edges: Vec<(usize, usize)> = adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(v, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |&&w| w > v)
+                    .map(move |&w| (v, w))
+            })
+            .collect();
+        let mut present: HashSet<(usize, usize)> = edges.iter().copied().collect();
+        let key = |a: usize, b: usize| (a.min(b), a.max(b));
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut done = 0;
+        let mut attempts = 0;
+        while edges.len() >= 2 && done < swaps && attempts < MAX_SWAP_ATTEMPTS * swaps {
+            attempts += 1;
+            let i = rng.random_range(0..edges.len());
+            let j = rng.random_range(0..edges.len());
+            let (a, b) = edges[i];
+            // Either orientation of the second edge gives a different swap
+            let (c, d) = if rng.random::<bool>() {
+                edges[j]
+            } else {
+                (edges[j].1, edges[j].0)
+            };
+            if i == j || a == d || c == b {
+                continue;
+            }
+            if present.contains(&key(a, d)) || present.contains(&key(c, b)) {
+                continue;
+            }
+            present.remove(&key(a, b));
+            present.remove(&key(c, d));
+            present.insert(key(a, d));
+            present.insert(key(c, b));
+            edges[i] = key(a, d);
+            edges[j] = key(c, b);
+            done += 1;
+        }
+
+        let mut graph = Graph::from_edgelist(edges, self.vertices);
+        graph.labels = self.labels.clone();
+        graph
+    }
+
+    // Rich-club coefficient divided by its mean over `samples` degree-preserving
+    // rewirings with `swaps_per_edge` swaps per edge. Values above 1 mean the
+    // high-degree vertices are more tightly knit than their degrees alone explain.
+    pub fn normalized_rich_club(
+        &self,
+        swaps_per_edge: usize,
+        samples: usize,
+        seed: u64,
+    ) -> Vec<f64> {
+        let phi = self.rich_club_coefficient();
+        let swaps = swaps_per_edge * simple_adjacency(self).iter().map(Vec::len).sum::<usize>() / 2;
+
+        let null_models: Vec<Vec<f64>> = (0..samples as u64)
+            .into_par_iter()
+            .map(|i| {
+                let null = self.degree_preserving_rewire(swaps, seed.wrapping_add(i));
+                rich_club(&simple_adjacency(&null))
+            })
+            .collect();
+
+        phi.iter()
+            .enumerate()
+            .map(|(k, &observed)| {
+                let expected = null_models.iter().map(|null| null[k]).sum::<f64>() / samples as f64;
+                if expected > 0.0 {
+                    observed / expected
+                } else {
+                    f64::NAN
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::structural::simple_adjacency;
+    use crate::testutil::{self, gnp};
+    use rand::Rng;
+
+    // Barbell of two triangles joined by the edge 2-3
+    fn barbell() -> Graph {
+        Graph::from_edgelist(
+            vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)],
+            6,
+        )
+    }
+
+    #[test]
+    fn degree_assortativity_matches_newman() {
+        let mut rng = testutil::rng();
+        for _ in 0..50 {
+            let n = rng.random_range(4..30);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let degree = graph.degree();
+            let edges = graph.edges();
+            let m = edges.len() as f64;
+            let (mut product, mut sum, mut squares) = (0.0, 0.0, 0.0);
+            for &(u, w) in &edges {
+                let (j, k) = (degree[u] as f64, degree[w] as f64);
+                product += j * k / m;
+                sum += (j + k) / (2.0 * m);
+                squares += (j * j + k * k) / (2.0 * m);
+            }
+            // Undefined when every edge end has the same degree, up to rounding
+            let variance = squares - sum * sum;
+            let expected = (product - sum * sum) / variance;
+            let r = graph.degree_assortativity();
+            if variance > 1e-9 {
+                assert!((r - expected).abs() < 1e-9, "{} {}", r, expected);
+            } else {
+                assert!(r.is_nan());
+            }
+        }
+    }
+
+    #[test]
+    fn stars_are_disassortative_and_cycles_undefined() {
+        let star = Graph::from_edgelist(vec![(0, 1), (0, 2), (0, 3)], 4);
+        assert!((star.degree_assortativity() + 1.0).abs() < 1e-12);
+        assert_eq!(star.average_neighbor_degree(), vec![1.0, 3.0, 3.0, 3.0]);
+        let knn = star.knn_by_degree();
+        assert_eq!(knn.len(), 4);
+        assert_eq!((knn[1], knn[3]), (3.0, 1.0));
+        assert!(knn[0].is_nan() && knn[2].is_nan());
+
+        let cycle = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 0)], 4);
+        assert!(cycle.degree_assortativity().is_nan());
+        assert!(Graph::new(1).average_neighbor_degree()[0].is_nan());
+    }
+
+    #[test]
+    fn categorical_assortativity_of_two_triangles() {
+        let graph = barbell();
+        let by_triangle = [0, 0, 0, 1, 1, 1];
+        assert!((graph.categorical_assortativity(&by_triangle) - 5.0 / 7.0).abs() < 1e-12);
+        assert!(graph.categorical_assortativity(&[0; 6]).is_nan());
+        let values = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        assert!((graph.numeric_assortativity(&values) - 5.0 / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rich_club_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..50 {
+            let n = rng.random_range(1..25);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let degree = graph.degree();
+            let phi = graph.rich_club_coefficient();
+            assert_eq!(phi.len(), degree.iter().max().unwrap() + 1);
+            for (k, &value) in phi.iter().enumerate() {
+                let club: Vec<usize> = (0..n).filter(|&v| degree[v] > k).collect();
+                if club.len() < 2 {
+                    assert!(value.is_nan());
+                    continue;
+                }
+                let edges = graph
+                    .edges()
+                    .iter()
+                    .filter(|(u, w)| club.contains(u) && club.contains(w))
+                    .count();
+                let pairs = club.len() * (club.len() - 1) / 2;
+                assert!((value - edges as f64 / pairs as f64).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn rewiring_keeps_degrees_and_simplicity() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(4..30);
+            let graph = gnp(&mut rng, n, 0.3, false);
+            let rewired = graph.degree_preserving_rewire(100, rng.random());
+            assert_eq!(rewired.degree(), graph.degree());
+            assert_eq!(simple_adjacency(&rewired), rewired.adj_list);
+        }
+
+        // No swap is possible in a complete graph, so the null model is the graph
+        let mut edges = Vec::new();
+        for u in 0..5 {
+            for w in u + 1..5 {
+                edges.push((u, w));
+            }
+        }
+        let complete = Graph::from_edgelist(edges, 5);
+        let normalized = complete.normalized_rich_club(10, 3, 1);
+        assert_eq!(normalized.len(), 5);
+        assert!(normalized[..4].iter().all(|&x| (x - 1.0).abs() < 1e-12));
+        assert!(normalized[4].is_nan());
+    }
+}
@@ -0,0 +1,391 @@
+use crate::graph::Graph;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// A filtered copy of a graph. `edges` are all edges of the original graph in the
+// order of `weighted_edges()`, `significance` the score of each under the method;
+// `graph` keeps every vertex and the edges that passed.
+pub struct Backbone {
+    pub graph: Graph,
+    pub edges: Vec<(usize, usize, f64)>,
+    pub significance: Vec<f64>,
+}
+
+// Index into `weighted_edges()` of every entry of the adjacency lists. Both entries
+// of an undirected edge, and both copies of a self-loop, get the same index.
+fn entry_edges(graph: &Graph) -> Vec<Vec<usize>> {
+    let mut ids: Vec<Vec<usize>> = graph
+        .adj_list
+        .iter()
+        .map(|neighbors| vec![usize::MAX; neighbors.len()])
+        .collect();
+    let mut next = 0;
+    if graph.is_directed() {
+        for entries in ids.iter_mut() {
+            for id in entries.iter_mut() {
+                *id = next;
+                next += 1;
+            }
+        }
+        return ids;
+    }
+
+    // Ids handed out at the lower end, waiting for the entry at the higher end;
+    // parallel edges are matched in the order they appear in both lists
+    let mut pending: HashMap<(usize, usize), VecDeque<usize>> = HashMap::new();
+    for (u, neighbors) in graph.adj_list.iter().enumerate() {
+        let mut loops = Vec::new();
+        for (pos, &w) in neighbors.iter().enumerate() {
+            match u.cmp(&w) {
+                Ordering::Less => {
+                    ids[u][pos] = next;
+                    pending.entry((w, u)).or_default().push_back(next);
+                    next += 1;
+                }
+                Ordering::Equal => loops.push(pos),
+                Ordering::Greater => {
+                    ids[u][pos] = pending
+                        .get_mut(&(u, w))
+                        .and_then(VecDeque::pop_front)
+                        .expect("adjacency lists are symmetric");
+                }
+            }
+        }
+        for pair in loops.chunks(2) {
+            for &pos in pair {
+                ids[u][pos] = next;
+            }
+            next += 1;
+        }
+    }
+    ids
+}
+
+// Out- and in-strength and -degree of every vertex; for an undirected graph both
+// are the strength and degree
+fn strengths(graph: &Graph) -> (Vec<f64>, Vec<f64>, Vec<usize>, Vec<usize>) {
+    let out_strength = graph.strength();
+    let out_degree: Vec<usize> = graph.adj_list.iter().map(Vec::len).collect();
+    if !graph.is_directed() {
+        return (
+            out_strength.clone(),
+            out_strength,
+            out_degree.clone(),
+            out_degree,
+        );
+    }
+    let mut in_strength = vec![0.0; graph.vertices];
+    let mut in_degree = vec![0; graph.vertices];
+    for (_, w, weight) in graph.weighted_edges() {
+        in_strength[w] += weight;
+        in_degree[w] += 1;
+    }
+    (out_strength, in_strength, out_degree, in_degree)
+}
+
+// Shortest-path tree candidate ordered for a min-heap on distance
+struct Visit {
+    distance: f64,
+    vertex: usize,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then(other.vertex.cmp(&self.vertex))
+    }
+}
+
+// Edges of the shortest-path tree from `root` with lengths 1 / weight, by Dijkstra.
+// Of several shortest paths the one found first is kept.
+fn shortest_path_tree(graph: &Graph, ids: &[Vec<usize>], root: usize) -> Vec<usize> {
+    let mut distance = vec![f64::INFINITY; graph.vertices];
+    let mut parent_edge = vec![usize::MAX; graph.vertices];
+    let mut done = vec![false; graph.vertices];
+    let mut heap = BinaryHeap::new();
+    distance[root] = 0.0;
+    heap.push(Visit {
+        distance: 0.0,
+        vertex: root,
+    });
+    while let Some(Visit {
+        distance: d,
+        vertex: v,
+    }) = heap.pop()
+    {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        for (pos, (w, weight)) in graph.weighted_neighbors(v).enumerate() {
+            if weight <= 0.0 || done[w] {
+                continue;
+            }
+            let candidate = d + 1.0 / weight;
+            if candidate < distance[w] {
+                distance[w] = candidate;
+                parent_edge[w] = ids[v][pos];
+                heap.push(Visit {
+                    distance: candidate,
+                    vertex: w,
+                });
+            }
+        }
+    }
+    parent_edge.retain(|&e| e != usize::MAX);
+    parent_edge
+}
+
+impl Graph {
+    // Copy of the graph with the edges for which `keep` holds, weights and labels kept
+    fn filter_edges(&self, edges: &[(usize, usize, f64)], keep: impl Fn(usize) -> bool) -> Graph {
+        let mut graph = if self.is_directed() {
+            Graph::directed_with_policy(self.vertices, self.policy)
+        } else {
+            Graph::with_policy(self.vertices, self.policy)
+        };
+        graph.add_weighted_edges(
+            edges
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| keep(i))
+                .map(|(_, &edge)| edge),
+        );
+        graph.labels = self.labels.clone();
+        graph
+    }
+
+    fn backbone(&self, significance: Vec<f64>, keep: impl Fn(f64) -> bool) -> Backbone {
+        let edges = self.weighted_edges();
+        let graph = self.filter_edges(&edges, |i| keep(significance[i]));
+        Backbone {
+            graph,
+            edges,
+            significance,
+        }
+    }
+
+    // Disparity filter (Serrano, Boguñá and Vespignani 2009). The significance of an
+    // edge is the smaller of its two p-values (1 - w / s)^(k - 1) under a uniform
+    // split of each endpoint's strength s over its k edges; for an arc, the tail's
+    // out-edges and the head's in-edges. Keeps the edges with a p-value below `alpha`.
+    pub fn disparity_filter(&self, alpha: f64) -> Backbone {
+        let (out_strength, in_strength, out_degree, in_degree) = strengths(self);
+        let p_value = |weight: f64, strength: f64, degree: usize| {
+            if degree < 2 {
+                return 1.0;
+            }
+            (1.0 - weight / strength).powi(degree as i32 - 1)
+        };
+        let significance = self
+            .weighted_edges()
+            .iter()
+            .map(|&(u, w, weight)| {
+                p_value(weight, out_strength[u], out_degree[u]).min(p_value(
+                    weight,
+                    in_strength[w],
+                    in_degree[w],
+                ))
+            })
+            .collect();
+        self.backbone(significance, |p| p < alpha)
+    }
+
+    // Noise-corrected backbone (Coscia and Neffke 2017). An edge's significance is its
+    // lift over the weight expected from the endpoint strengths, transformed to
+    // (-1, 1), in standard deviations of its binomial noise under a Bayesian prior.
+    // Keeps the edges more than `delta` standard deviations above zero (1.64 ~ p 0.05).
+    pub fn noise_corrected_backbone(&self, delta: f64) -> Backbone {
+        let (out_strength, in_strength, _, _) = strengths(self);
+        let total: f64 = out_strength.iter().sum();
+        let significance = self
+            .weighted_edges()
+            .iter()
+            .map(|&(u, w, n_ij)| {
+                let (n_i, n_j) = (out_strength[u], in_strength[w]);
+                let kappa = total / (n_i * n_j);
+                let score = (kappa * n_ij - 1.0) / (kappa * n_ij + 1.0);
+
+                // Beta prior on the edge probability with the moments of a
+                // hypergeometric draw, updated with the observed weight
+                let prior_mean = n_i * n_j / (total * total);
+                let prior_variance =
+                    n_i * n_j * (total - n_i) * (total - n_j) / (total.powi(4) * (total - 1.0));
+                let alpha_prior =
+                    prior_mean * prior_mean / prior_variance * (1.0 - prior_mean) - prior_mean;
+                let beta_prior = prior_mean / prior_variance * (1.0 - prior_mean * prior_mean)
+                    - (1.0 - prior_mean);
+                let alpha_post = alpha_prior + n_ij;
+                let beta_post = total - n_ij + beta_prior;
+                let expected = alpha_post / (alpha_post + beta_post);
+                let variance_n = expected * (1.0 - expected) * total;
+
+                // Delta method for the variance of the transformed lift
+                let d = 1.0 / (n_i * n_j) - total * (n_i + n_j) / (n_i * n_j).powi(2);
+                let derivative = 2.0 * (kappa + n_ij * d) / (kappa * n_ij + 1.0).powi(2);
+                let deviation = (variance_n * derivative * derivative).sqrt();
+                score / deviation
+            })
+            .collect();
+        self.backbone(significance, |z| z > delta)
+    }
+
+    // High-salience skeleton (Grady, Thiemann and Brockmann 2012): the significance of
+    // an edge is the share of vertices whose shortest-path tree, with lengths
+    // 1 / weight, contains it. Trees are grown in parallel. Keeps the edges with
+    // salience at least `threshold`; salience is typically near 0 or 1.
+    pub fn high_salience_skeleton(&self, threshold: f64) -> Backbone {
+        let ids = entry_edges(self);
+        let edge_count = self.weighted_edges().len();
+        let counts = (0..self.vertices)
+            .into_par_iter()
+            .fold(
+                || vec![0usize; edge_count],
+                |mut counts, root| {
+                    for e in shortest_path_tree(self, &ids, root) {
+                        counts[e] += 1;
+                    }
+                    counts
+                },
+            )
+            .reduce(
+                || vec![0usize; edge_count],
+                |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                    a
+                },
+            );
+        let significance = counts
+            .iter()
+            .map(|&c| c as f64 / self.vertices as f64)
+            .collect();
+        self.backbone(significance, |s| s >= threshold)
+    }
+
+    // Keeps the edges with weight at least `threshold`; the significance is the weight
+    pub fn global_threshold_backbone(&self, threshold: f64) -> Backbone {
+        let significance = self.weighted_edges().iter().map(|e| e.2).collect();
+        self.backbone(significance, |weight| weight >= threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::entry_edges;
+    use crate::graph::{Graph, GraphPolicy};
+    use crate::testutil::{self, random_edges};
+    use rand::Rng;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-12, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn entries_map_to_their_edges() {
+        let mut rng = testutil::rng();
+        for directed in [false, true] {
+            for _ in 0..30 {
+                let n = rng.random_range(1..8);
+                let m = rng.random_range(0..20);
+                let edges: Vec<(usize, usize, f64)> = random_edges(&mut rng, n, m)
+                    .into_iter()
+                    .map(|(u, w)| (u, w, rng.random()))
+                    .collect();
+                let graph = if directed {
+                    Graph::from_weighted_arclist_with_policy(edges, n, GraphPolicy::Multigraph)
+                } else {
+                    Graph::from_weighted_edgelist_with_policy(edges, n, GraphPolicy::Multigraph)
+                };
+                let edges = graph.weighted_edges();
+                let ids = entry_edges(&graph);
+                let mut uses = vec![0; edges.len()];
+                for (u, entries) in ids.iter().enumerate() {
+                    for ((w, weight), &id) in graph.weighted_neighbors(u).zip(entries) {
+                        let (a, b, edge_weight) = edges[id];
+                        assert!((a, b) == (u, w) || (!directed && (a, b) == (w, u)));
+                        assert_eq!(weight, edge_weight);
+                        uses[id] += 1;
+                    }
+                }
+                let expected = if directed { 1 } else { 2 };
+                assert!(uses.iter().all(|&count| count == expected));
+            }
+        }
+    }
+
+    #[test]
+    fn disparity_p_values_on_a_star() {
+        // The centre splits a strength of 10 over 4 edges; leaves have a single edge
+        let star = Graph::from_weighted_edgelist(
+            vec![(0, 1, 1.0), (0, 2, 2.0), (0, 3, 3.0), (0, 4, 4.0)],
+            5,
+        );
+        let backbone = star.disparity_filter(0.3);
+        assert_close(&backbone.significance, &[0.729, 0.512, 0.343, 0.216]);
+        assert_eq!(backbone.graph.vertices, 5);
+        assert_eq!(backbone.graph.weighted_edges(), vec![(0, 4, 4.0)]);
+
+        // Arcs use the out-edges of the tail and the in-edges of the head
+        let digraph = Graph::from_weighted_arclist(vec![(0, 1, 1.0), (0, 2, 3.0), (2, 1, 1.0)], 3);
+        let backbone = digraph.disparity_filter(0.4);
+        assert_close(&backbone.significance, &[0.5, 0.25, 0.5]);
+        assert!(backbone.graph.is_directed());
+        assert_eq!(backbone.graph.weighted_edges(), vec![(0, 2, 3.0)]);
+    }
+
+    #[test]
+    fn high_salience_skeleton_drops_long_detours() {
+        // 0-2 is far longer (1 / 0.01) than the way through 1
+        let graph = Graph::from_weighted_edgelist(
+            vec![(0, 1, 10.0), (1, 2, 10.0), (0, 2, 0.01), (2, 3, 1.0)],
+            4,
+        );
+        let backbone = graph.high_salience_skeleton(0.5);
+        assert_close(&backbone.significance, &[1.0, 0.0, 1.0, 1.0]);
+        assert_eq!(backbone.graph.edge_count(), 3);
+        assert!(!backbone.graph.has_edge(0, 2));
+    }
+
+    #[test]
+    fn noise_corrected_and_global_threshold() {
+        // Heavy outer edges exceed the weight their strengths predict, the light
+        // middle edge falls short
+        let graph = Graph::from_weighted_edgelist(vec![(0, 1, 10.0), (1, 2, 1.0), (2, 3, 10.0)], 4)
+            .with_labels(vec!["a".into(), "b".into(), "c".into(), "d".into()]);
+        let backbone = graph.noise_corrected_backbone(0.0);
+        let z = &backbone.significance;
+        assert!(z[0] > 0.0 && z[2] > 0.0 && z[1] < 0.0);
+        assert!((z[0] - z[2]).abs() < 1e-12);
+        assert_eq!(backbone.graph.edge_count(), 2);
+
+        let backbone = graph.global_threshold_backbone(5.0);
+        assert_close(&backbone.significance, &[10.0, 1.0, 10.0]);
+        assert_eq!(
+            backbone.graph.weighted_edges(),
+            vec![(0, 1, 10.0), (2, 3, 10.0)]
+        );
+        assert_eq!(backbone.graph.label(3), "d");
+    }
+}
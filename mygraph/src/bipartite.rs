@@ -0,0 +1,344 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use crate::subgraph::Subgraph;
+use std::collections::{HashMap, VecDeque};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// A graph whose edges all join the two sides given by `types`, e.g. people (false)
+// and the events they attend (true)
+pub struct BipartiteGraph {
+    pub graph: Graph,
+    pub types: Vec<bool>,
+}
+
+// Weight of a tie between two vertices of one side in a one-mode projection, summed
+// over the neighbours k they share
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Projection {
+    // 1 per shared neighbour
+    Count,
+    // 1 / (d_k - 1), so each vertex spreads a total weight of 1 per neighbour over
+    // its co-neighbours (Newman 2001)
+    Newman,
+    // 1 / d_k, discounting large events hyperbolically but not dropping the
+    // contribution of pairs as Newman's weighting does for d_k = 2
+    Hyperbolic,
+}
+
+impl Graph {
+    // Side of every vertex in a 2-colouring, each component's first vertex on side
+    // false; None if an odd cycle or self-loop makes the graph non-bipartite.
+    // Arcs are followed in both directions.
+    pub fn bipartition(&self) -> Option<Vec<bool>> {
+        let mut side: Vec<Option<bool>> = vec![None; self.vertices];
+        let mut queue = VecDeque::new();
+        for start in 0..self.vertices {
+            if side[start].is_some() {
+                continue;
+            }
+            side[start] = Some(false);
+            queue.push_back(start);
+            while let Some(v) = queue.pop_front() {
+                let other = !side[v].unwrap();
+                for &w in self.successors(v).iter().chain(self.predecessors(v)) {
+                    match side[w] {
+                        None => {
+                            side[w] = Some(other);
+                            queue.push_back(w);
+                        }
+                        Some(s) if s != other => return None,
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        Some(side.into_iter().map(Option::unwrap).collect())
+    }
+
+    pub fn is_bipartite(&self) -> bool {
+        self.bipartition().is_some()
+    }
+}
+
+impl BipartiteGraph {
+    // Panics if an edge joins two vertices of the same side
+    pub fn new(graph: Graph, types: Vec<bool>) -> Self {
+        assert_eq!(types.len(), graph.vertices, "one type per vertex");
+        for (u, w) in graph.edges() {
+            assert_ne!(types[u], types[w], "edge {}-{} within one side", u, w);
+        }
+        BipartiteGraph { graph, types }
+    }
+
+    // Sides from a 2-colouring, None if the graph is not bipartite
+    pub fn from_graph(graph: Graph) -> Option<Self> {
+        let types = graph.bipartition()?;
+        Some(BipartiteGraph { graph, types })
+    }
+
+    // Vertices of one side in index order
+    pub fn side(&self, side: bool) -> Vec<usize> {
+        (0..self.graph.vertices)
+            .filter(|&v| self.types[v] == side)
+            .collect()
+    }
+
+    // One-mode projection onto one side: two of its vertices are tied if they share
+    // a neighbour, with weight per `weighting`. Parallel edges and edge weights of
+    // the bipartite graph are ignored. Vertices map back through `mapping`.
+    pub fn projection(&self, side: bool, weighting: Projection) -> Subgraph {
+        let adj = simple_adjacency(&self.graph);
+        let mapping = self.side(side);
+        let mut new_index = vec![usize::MAX; self.graph.vertices];
+        for (i, &v) in mapping.iter().enumerate() {
+            new_index[v] = i;
+        }
+
+        let edges: Vec<(usize, usize, f64)> = mapping
+            .par_iter()
+            .flat_map_iter(|&v| {
+                let mut ties: HashMap<usize, f64> = HashMap::new();
+                for &k in &adj[v] {
+                    let d = adj[k].len() as f64;
+                    let weight = match weighting {
+                        Projection::Count => 1.0,
+                        Projection::Newman => 1.0 / (d - 1.0),
+                        Projection::Hyperbolic => 1.0 / d,
+                    };
+                    for &u in adj[k].iter().filter(|&&u| u > v) {
+                        *ties.entry(u).or_default() += weight;
+                    }
+                }
+                let mut ties: Vec<(usize, usize, f64)> = ties
+                    .into_iter()
+                    .map(|(u, weight)| (new_index[v], new_index[u], weight))
+                    .collect();
+                ties.sort_by_key(|&(_, u, _)| u);
+                ties
+            })
+            .collect();
+
+        let mut graph = Graph::from_weighted_edgelist(edges, mapping.len());
+        if let Some(labels) = &self.graph.labels {
+            graph.labels = Some(mapping.iter().map(|&v| labels[v].clone()).collect());
+        }
+        Subgraph { graph, mapping }
+    }
+
+    // Degree over the size of the other side (Borgatti and Everett 1997)
+    pub fn degree_centrality(&self) -> Vec<f64> {
+        let adj = simple_adjacency(&self.graph);
+        let top = self.types.iter().filter(|&&t| t).count();
+        let bottom = self.graph.vertices - top;
+        (0..self.graph.vertices)
+            .map(|v| {
+                let other = if self.types[v] { bottom } else { top };
+                adj[v].len() as f64 / other as f64
+            })
+            .collect()
+    }
+
+    // Latapy et al.'s clustering: the mean Jaccard overlap of a vertex's
+    // neighbourhood with those of the vertices two steps away. NaN without any.
+    pub fn clustering(&self) -> Vec<f64> {
+        let adj = simple_adjacency(&self.graph);
+        (0..self.graph.vertices)
+            .into_par_iter()
+            .map(|v| {
+                let mut shared: HashMap<usize, usize> = HashMap::new();
+                for &k in &adj[v] {
+                    for &u in adj[k].iter().filter(|&&u| u != v) {
+                        *shared.entry(u).or_default() += 1;
+                    }
+                }
+                let overlap: f64 = shared
+                    .iter()
+                    .map(|(&u, &common)| {
+                        common as f64 / (adj[v].len() + adj[u].len() - common) as f64
+                    })
+                    .sum();
+                overlap / shared.len() as f64
+            })
+            .collect()
+    }
+
+    // Robins and Alexander's global clustering: four times the 4-cycles over the
+    // paths of length 3. NaN without any such path.
+    pub fn robins_alexander_clustering(&self) -> f64 {
+        let adj = simple_adjacency(&self.graph);
+        // Every 4-cycle is counted once from each of its two vertices on side false
+        let cycles: usize = self
+            .side(false)
+            .par_iter()
+            .map(|&v| {
+                let mut shared: HashMap<usize, usize> = HashMap::new();
+                for &k in &adj[v] {
+                    for &u in adj[k].iter().filter(|&&u| u != v) {
+                        *shared.entry(u).or_default() += 1;
+                    }
+                }
+                shared
+                    .values()
+                    .map(|&c| c * (c.saturating_sub(1)) / 2)
+                    .sum::<usize>()
+            })
+            .sum::<usize>()
+            / 2;
+        let paths: usize = (0..self.graph.vertices)
+            .filter(|&v| !self.types[v])
+            .flat_map(|v| adj[v].iter().map(move |&w| (v, w)))
+            .map(|(v, w)| (adj[v].len() - 1) * (adj[w].len() - 1))
+            .sum();
+        4.0 * cycles as f64 / paths as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BipartiteGraph, Projection};
+    use crate::graph::{Graph, GraphPolicy};
+    use crate::testutil;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use std::collections::HashSet;
+
+    // People 0, 1, 2 (false) attending events 3 (everyone) and 4 (1 and 2)
+    fn attendance() -> BipartiteGraph {
+        let graph = Graph::from_edgelist(vec![(0, 3), (1, 3), (2, 3), (1, 4), (2, 4)], 5);
+        BipartiteGraph::new(graph, vec![false, false, false, true, true])
+    }
+
+    fn random_bipartite(rng: &mut StdRng) -> BipartiteGraph {
+        let (a, b) = (rng.random_range(1..6), rng.random_range(1..6));
+        let edges = (0..rng.random_range(0..3 * (a + b)))
+            .map(|_| (rng.random_range(0..a), a + rng.random_range(0..b)))
+            .collect();
+        let types = (0..a + b).map(|v| v >= a).collect();
+        BipartiteGraph::new(Graph::from_edgelist(edges, a + b), types)
+    }
+
+    fn neighbors(graph: &Graph, v: usize) -> HashSet<usize> {
+        graph.successors(v).iter().copied().collect()
+    }
+
+    #[test]
+    fn bipartitions() {
+        let square = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 0)], 5);
+        assert_eq!(
+            square.bipartition(),
+            Some(vec![false, true, false, true, false])
+        );
+        let triangle = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 0)], 3);
+        assert!(!triangle.is_bipartite());
+        let looped = Graph::from_edgelist_with_policy(vec![(0, 1), (1, 1)], 2, GraphPolicy::Loops);
+        assert!(!looped.is_bipartite());
+        // Arcs into a vertex count as well
+        let arcs = Graph::from_arclist(vec![(1, 0), (2, 0), (2, 1)], 3);
+        assert!(!arcs.is_bipartite());
+        assert!(BipartiteGraph::from_graph(square).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "within one side")]
+    fn edges_must_join_the_sides() {
+        BipartiteGraph::new(Graph::from_edgelist(vec![(0, 1)], 2), vec![true, true]);
+    }
+
+    #[test]
+    fn projections_of_the_attendance() {
+        let bipartite = attendance();
+        let expected = [
+            (Projection::Count, [1.0, 1.0, 2.0]),
+            (Projection::Newman, [0.5, 0.5, 1.5]),
+            (
+                Projection::Hyperbolic,
+                [1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0 + 0.5],
+            ),
+        ];
+        for (weighting, weights) in expected {
+            let people = bipartite.projection(false, weighting);
+            assert_eq!(people.mapping, vec![0, 1, 2]);
+            let edges = people.graph.weighted_edges();
+            let pairs: Vec<(usize, usize)> = edges.iter().map(|&(u, w, _)| (u, w)).collect();
+            assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 2)]);
+            for (&(_, _, weight), expected) in edges.iter().zip(weights) {
+                assert!((weight - expected).abs() < 1e-12);
+            }
+        }
+        let events = bipartite.projection(true, Projection::Newman);
+        assert_eq!(events.mapping, vec![3, 4]);
+        assert_eq!(events.graph.weighted_edges(), vec![(0, 1, 2.0)]);
+    }
+
+    #[test]
+    fn degree_centrality_of_the_attendance() {
+        let centrality = attendance().degree_centrality();
+        let expected = [0.5, 1.0, 1.0, 1.0, 2.0 / 3.0];
+        for (c, e) in centrality.iter().zip(expected) {
+            assert!((c - e).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn clustering_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..50 {
+            let bipartite = random_bipartite(&mut rng);
+            let graph = &bipartite.graph;
+            let n = graph.vertices;
+
+            let clustering = bipartite.clustering();
+            for (v, &c) in clustering.iter().enumerate() {
+                let a = neighbors(graph, v);
+                let overlaps: Vec<f64> = (0..n)
+                    .filter(|&u| u != v)
+                    .map(|u| neighbors(graph, u))
+                    .filter(|b| !a.is_disjoint(b))
+                    .map(|b| a.intersection(&b).count() as f64 / a.union(&b).count() as f64)
+                    .collect();
+                if overlaps.is_empty() {
+                    assert!(c.is_nan());
+                } else {
+                    let mean = overlaps.iter().sum::<f64>() / overlaps.len() as f64;
+                    assert!((c - mean).abs() < 1e-12);
+                }
+            }
+
+            // 4-cycles from pairs on each side, paths of length 3 as ordered walks
+            // through distinct vertices, each counted from both ends
+            let side = |s: bool| bipartite.side(s);
+            let mut cycles = 0;
+            for (i, &a) in side(false).iter().enumerate() {
+                for &b in &side(false)[i + 1..] {
+                    let common = neighbors(graph, a)
+                        .intersection(&neighbors(graph, b))
+                        .count();
+                    cycles += common * common.saturating_sub(1) / 2;
+                }
+            }
+            let mut walks = 0;
+            for a in 0..n {
+                for &b in &neighbors(graph, a) {
+                    for &c in neighbors(graph, b).iter().filter(|&&c| c != a) {
+                        walks += neighbors(graph, c)
+                            .iter()
+                            .filter(|&&d| d != a && d != b)
+                            .count();
+                    }
+                }
+            }
+            let expected = 4.0 * cycles as f64 / (walks / 2) as f64;
+            let actual = bipartite.robins_alexander_clustering();
+            if walks == 0 {
+                assert!(actual.is_nan());
+            } else {
+                assert!((actual - expected).abs() < 1e-12);
+            }
+        }
+        let square = Graph::from_edgelist(vec![(0, 2), (0, 3), (1, 2), (1, 3)], 4);
+        let square = BipartiteGraph::new(square, vec![false, false, true, true]);
+        assert_eq!(square.robins_alexander_clustering(), 1.0);
+    }
+}
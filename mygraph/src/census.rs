@@ -0,0 +1,316 @@
+use crate::graph::Graph;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Triad types in MAN notation: mutual, asymmetric and null dyads, and a letter for
+// Down, Up, Cyclic or Transitive where the counts alone are ambiguous
+pub const TRIAD_TYPES: [&str; 16] = [
+    "003", "012", "102", "021D", "021U", "021C", "111D", "111U", "030T", "030C", "201", "120D",
+    "120U", "120C", "210", "300",
+];
+
+// Triad type (index into TRIAD_TYPES) of every arc pattern among v, u, w, with the
+// arcs v->u, u->v, v->w, w->v, u->w, w->u as bits 0 to 5
+const TRICODES: [usize; 64] = [
+    0, 1, 1, 2, 1, 3, 5, 7, 1, 5, 4, 6, 2, 7, 6, 10, 1, 5, 3, 7, 4, 8, 8, 12, 5, 9, 8, 13, 6, 13,
+    11, 14, 1, 4, 5, 6, 5, 8, 9, 13, 3, 8, 8, 11, 7, 12, 13, 14, 2, 6, 7, 10, 6, 11, 13, 14, 7, 13,
+    12, 14, 10, 14, 14, 15,
+];
+
+// Pairs of vertices by the arcs between them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct DyadCensus {
+    // Arcs in both directions
+    pub mutual: usize,
+    // An arc in one direction only
+    pub asymmetric: usize,
+    // No arc
+    pub null: usize,
+}
+
+// Number of vertex triples of each type, indexed like TRIAD_TYPES
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TriadCensus {
+    pub counts: [usize; 16],
+}
+
+impl TriadCensus {
+    // Count of a triad type by its name, e.g. "030T"
+    pub fn get(&self, name: &str) -> Option<usize> {
+        TRIAD_TYPES
+            .iter()
+            .position(|&t| t == name)
+            .map(|i| self.counts[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        TRIAD_TYPES.iter().copied().zip(self.counts.iter().copied())
+    }
+}
+
+// Serialized as a map from type name to count
+impl Serialize for TriadCensus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(TRIAD_TYPES.len()))?;
+        for (name, count) in self.iter() {
+            map.serialize_entry(name, &count)?;
+        }
+        map.end()
+    }
+}
+
+// Sorted successor lists and sorted lists of vertices joined by an arc in either
+// direction, without self-loops and parallel arcs
+fn simple_arcs(graph: &Graph) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    (0..graph.vertices)
+        .into_par_iter()
+        .map(|v| {
+            let mut successors = graph.successors(v).to_vec();
+            successors.sort_unstable();
+            successors.dedup();
+            successors.retain(|&w| w != v);
+            let mut neighbors = successors.clone();
+            neighbors.extend(graph.predecessors(v).iter().filter(|&&w| w != v));
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            (successors, neighbors)
+        })
+        .unzip()
+}
+
+impl Graph {
+    // Mutual, asymmetric and null dyads. In an undirected graph every edge is mutual.
+    // Self-loops and parallel arcs are ignored.
+    pub fn dyad_census(&self) -> DyadCensus {
+        let (successors, neighbors) = simple_arcs(self);
+        let (mutual, asymmetric) = (0..self.vertices)
+            .into_par_iter()
+            .map(|v| {
+                // Each dyad is counted from its lower vertex
+                neighbors[v]
+                    .iter()
+                    .filter(|&&u| u > v)
+                    .fold((0, 0), |(mutual, asymmetric), &u| {
+                        let forward = successors[v].binary_search(&u).is_ok();
+                        let backward = successors[u].binary_search(&v).is_ok();
+                        if forward && backward {
+                            (mutual + 1, asymmetric)
+                        } else {
+                            (mutual, asymmetric + 1)
+                        }
+                    })
+            })
+            .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+        let pairs = self.vertices * self.vertices.saturating_sub(1) / 2;
+        DyadCensus {
+            mutual,
+            asymmetric,
+            null: pairs - mutual - asymmetric,
+        }
+    }
+
+    // Share of arcs whose reverse arc also exists, NaN if there are no arcs
+    pub fn reciprocity(&self) -> f64 {
+        let dyads = self.dyad_census();
+        let arcs = 2 * dyads.mutual + dyads.asymmetric;
+        if arcs > 0 {
+            2.0 * dyads.mutual as f64 / arcs as f64
+        } else {
+            f64::NAN
+        }
+    }
+
+    // Triad census with the algorithm of Batagelj & Mrvar (2001): every connected
+    // triple is classified once from its lowest-indexed dyad, triples with a single
+    // dyad are counted in bulk, and the empty triples are what is left over.
+    // Self-loops and parallel arcs are ignored.
+    pub fn triad_census(&self) -> TriadCensus {
+        let n = self.vertices;
+        let (successors, neighbors) = simple_arcs(self);
+        let arc = |a: usize, b: usize| successors[a].binary_search(&b).is_ok() as usize;
+        let tricode = |v: usize, u: usize, w: usize| {
+            arc(v, u)
+                | arc(u, v) << 1
+                | arc(v, w) << 2
+                | arc(w, v) << 3
+                | arc(u, w) << 4
+                | arc(w, u) << 5
+        };
+
+        let mut counts = (0..n)
+            .into_par_iter()
+            .map(|v| {
+                let mut counts = [0usize; 16];
+                for &u in neighbors[v].iter().filter(|&&u| u > v) {
+                    // Vertices joined to u or v, other than u and v
+                    let mut joined: Vec<usize> = neighbors[v]
+                        .iter()
+                        .chain(&neighbors[u])
+                        .copied()
+                        .filter(|&w| w != u && w != v)
+                        .collect();
+                    joined.sort_unstable();
+                    joined.dedup();
+
+                    // Triples of the dyad with a vertex joined to neither
+                    let dyad = if arc(v, u) == 1 && arc(u, v) == 1 {
+                        2
+                    } else {
+                        1
+                    };
+                    counts[dyad] += n - joined.len() - 2;
+
+                    for &w in &joined {
+                        // Count the triple only from its first dyad
+                        if u < w || (v < w && w < u && neighbors[v].binary_search(&w).is_err()) {
+                            counts[TRICODES[tricode(v, u, w)]] += 1;
+                        }
+                    }
+                }
+                counts
+            })
+            .reduce(
+                || [0usize; 16],
+                |mut a, b| {
+                    for (x, y) in a.iter_mut().zip(b) {
+                        *x += y;
+                    }
+                    a
+                },
+            );
+
+        let triples = n * n.saturating_sub(1) * n.saturating_sub(2) / 6;
+        counts[0] = triples - counts[1..].iter().sum::<usize>();
+        TriadCensus { counts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DyadCensus, TRIAD_TYPES};
+    use crate::graph::{Graph, GraphPolicy};
+    use crate::testutil::{self, gnp};
+    use rand::Rng;
+
+    // One arc set of each triad type on vertices 0, 1, 2, in the order of TRIAD_TYPES
+    const EXAMPLES: [&[(usize, usize)]; 16] = [
+        &[],
+        &[(0, 1)],
+        &[(0, 1), (1, 0)],
+        &[(1, 0), (1, 2)],
+        &[(0, 1), (2, 1)],
+        &[(0, 1), (1, 2)],
+        &[(0, 1), (1, 0), (2, 1)],
+        &[(0, 1), (1, 0), (1, 2)],
+        &[(0, 1), (2, 1), (0, 2)],
+        &[(1, 0), (2, 1), (0, 2)],
+        &[(0, 1), (1, 0), (1, 2), (2, 1)],
+        &[(1, 0), (1, 2), (0, 2), (2, 0)],
+        &[(0, 1), (2, 1), (0, 2), (2, 0)],
+        &[(0, 1), (1, 2), (0, 2), (2, 0)],
+        &[(0, 1), (1, 2), (2, 1), (0, 2), (2, 0)],
+        &[(0, 1), (1, 0), (1, 2), (2, 1), (0, 2), (2, 0)],
+    ];
+
+    // Triad type of three vertices by trying every relabelling of every example
+    fn classify(graph: &Graph, triple: [usize; 3]) -> usize {
+        let permutations = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+        let arcs = |map: [usize; 3], example: &[(usize, usize)]| {
+            (0..3).all(|a| {
+                (0..3).all(|b| {
+                    a == b
+                        || graph.has_edge(triple[map[a]], triple[map[b]])
+                            == example.contains(&(a, b))
+                })
+            })
+        };
+        (0..16)
+            .find(|&t| permutations.iter().any(|&map| arcs(map, EXAMPLES[t])))
+            .unwrap()
+    }
+
+    #[test]
+    fn triad_census_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..100 {
+            let n = rng.random_range(0..12);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, true);
+            let mut expected = [0; 16];
+            for a in 0..n {
+                for b in a + 1..n {
+                    for c in b + 1..n {
+                        expected[classify(&graph, [a, b, c])] += 1;
+                    }
+                }
+            }
+            assert_eq!(graph.triad_census().counts, expected);
+        }
+    }
+
+    #[test]
+    fn every_example_is_its_own_type() {
+        for (t, arcs) in EXAMPLES.iter().enumerate() {
+            let census = Graph::from_arclist(arcs.to_vec(), 3).triad_census();
+            assert_eq!(census.get(TRIAD_TYPES[t]), Some(1));
+            assert_eq!(census.counts.iter().sum::<usize>(), 1);
+        }
+    }
+
+    #[test]
+    fn census_of_a_small_digraph() {
+        let graph = Graph::from_arclist(vec![(0, 1), (1, 2), (2, 0), (2, 3)], 4);
+        let census = graph.triad_census();
+        for (name, count) in census.iter() {
+            let expected = matches!(name, "030C" | "012" | "021D" | "021C") as usize;
+            assert_eq!(count, expected, "{}", name);
+        }
+        assert_eq!(
+            graph.dyad_census(),
+            DyadCensus {
+                mutual: 0,
+                asymmetric: 4,
+                null: 2
+            }
+        );
+        assert_eq!(graph.reciprocity(), 0.0);
+        assert_eq!(census.get("xyz"), None);
+    }
+
+    #[test]
+    fn dyads_ignore_loops_and_parallel_arcs() {
+        let graph = Graph::from_arclist_with_policy(
+            vec![(0, 1), (0, 1), (1, 0), (1, 2), (2, 2)],
+            3,
+            GraphPolicy::Multigraph,
+        );
+        let dyads = graph.dyad_census();
+        assert_eq!((dyads.mutual, dyads.asymmetric, dyads.null), (1, 1, 1));
+        assert!((graph.reciprocity() - 2.0 / 3.0).abs() < 1e-12);
+
+        // Every undirected edge is mutual
+        let path = Graph::from_edgelist(vec![(0, 1), (1, 2)], 3);
+        assert_eq!(path.dyad_census().mutual, 2);
+        assert_eq!(path.triad_census().get("201"), Some(1));
+        assert!(Graph::new_directed(2).reciprocity().is_nan());
+    }
+
+    #[test]
+    fn census_serializes_as_a_map() {
+        let census = Graph::from_arclist(vec![(0, 1)], 3).triad_census();
+        let json = serde_json::to_value(census).unwrap();
+        assert_eq!(json["012"], 1);
+        assert_eq!(json["003"], 0);
+        assert_eq!(json.as_object().unwrap().len(), 16);
+    }
+}
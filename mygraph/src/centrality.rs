@@ -1,78 +1,14 @@
 extern crate nalgebra as na;
 extern crate nalgebra_sparse as na_sparse;
 use crate::graph::Graph;
+use crate::paths::{bfs_shortest_paths, single_source_shortest_path, ShortestPathTree};
 use na::DVector;
 use na_sparse::csr::CsrMatrix;
-use std::collections::VecDeque;
 
 extern crate rayon;
 use rayon::prelude::*;
 use std::sync::Mutex;
 
-fn single_source_shortest_path(
-    graph: &Graph,
-    s: usize,
-) -> (Vec<usize>, Vec<Vec<usize>>, Vec<usize>, Vec<usize>) {
-    let mut distances = vec![usize::MAX; graph.vertices];
-    let mut shortest_paths = vec![0; graph.vertices];
-    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); graph.vertices];
-
-    let mut queue = VecDeque::new();
-    let mut stack = Vec::new();
-
-    distances[s] = 0;
-    shortest_paths[s] = 1;
-    queue.push_back(s);
-
-    while let Some(v) = queue.pop_front() {
-        stack.push(v);
-        for &w in &graph.adj_list[v] {
-            // Path discovery
-            if distances[w] == usize::MAX {
-                queue.push_back(w);
-                distances[w] = distances[v] + 1;
-            }
-            // Path counting
-            if distances[w] == distances[v] + 1 {
-                shortest_paths[w] += shortest_paths[v];
-                predecessors[w].push(v);
-            }
-        }
-    }
-
-    (stack, predecessors, shortest_paths, distances)
-}
-
-fn distance_matrix(graph: &Graph) -> Vec<Vec<usize>> {
-    let mut matrix = vec![vec![usize::MAX; graph.vertices]; graph.vertices];
-
-    for s in 0..graph.vertices {
-        let (_, _, _, distances) = single_source_shortest_path(graph, s);
-        matrix[s] = distances;
-    }
-
-    matrix
-}
-
-fn bfs_shortest_paths(graph: &Graph, start: usize) -> Vec<usize> {
-    let mut distances = vec![usize::MAX; graph.vertices];
-    let mut queue = VecDeque::new();
-
-    distances[start] = 0;
-    queue.push_back(start);
-
-    while let Some(node) = queue.pop_front() {
-        for &neighbor in &graph.adj_list[node] {
-            if distances[neighbor] == usize::MAX {
-                distances[neighbor] = distances[node] + 1;
-                queue.push_back(neighbor);
-            }
-        }
-    }
-
-    distances
-}
-
 fn power_iteration(matrix: &CsrMatrix<f64>, max_iters: usize, tolerance: f64) -> DVector<f64> {
     let n = matrix.nrows();
     let mut b_k = DVector::from_element(n, 1.0); // Initial guess
@@ -97,10 +33,25 @@ fn power_iteration(matrix: &CsrMatrix<f64>, max_iters: usize, tolerance: f64) ->
 }
 
 impl Graph {
+    // Number of edges at every vertex; in a directed graph arcs in and out
     pub fn degree(&self) -> Vec<usize> {
-        self.adj_list
-            .iter()
-            .map(|neighbors| neighbors.len())
+        (0..self.vertices)
+            .map(|v| match &self.in_list {
+                Some(in_list) => self.adj_list[v].len() + in_list[v].len(),
+                None => self.adj_list[v].len(),
+            })
+            .collect()
+    }
+
+    // Number of arcs out of every vertex; the degree if the graph is undirected
+    pub fn out_degree(&self) -> Vec<usize> {
+        self.adj_list.iter().map(Vec::len).collect()
+    }
+
+    // Number of arcs into every vertex; the degree if the graph is undirected
+    pub fn in_degree(&self) -> Vec<usize> {
+        (0..self.vertices)
+            .map(|v| self.predecessors(v).len())
             .collect()
     }
 
@@ -126,8 +77,12 @@ impl Graph {
         let centrality_global = Mutex::new(vec![0.0; vertices]);
 
         (0..vertices).into_par_iter().for_each(|s| {
-            let (mut stack, predecessors, shortest_paths, _distances) =
-                single_source_shortest_path(self, s);
+            let ShortestPathTree {
+                order: mut stack,
+                predecessors,
+                path_counts: shortest_paths,
+                ..
+            } = single_source_shortest_path(self, s);
 
             let mut dependency = vec![0.0; vertices];
             let mut centrality_local = vec![0.0; vertices];
@@ -148,18 +103,46 @@ impl Graph {
             }
         });
 
-        // Normalization step
+        // Normalization step: an undirected path is counted from both of its ends
         let mut centrality = centrality_global.into_inner().unwrap();
-        for value in centrality.iter_mut() {
-            *value *= 1.0 / 2.0;
+        if !self.is_directed() {
+            for value in centrality.iter_mut() {
+                *value *= 1.0 / 2.0;
+            }
         }
 
         centrality
     }
 
+    // In a directed graph a vertex is central if central vertices have arcs to it
     pub fn eigenvector_centrality(&self) -> DVector<f64> {
-        let adj_mat = &self.to_adjacency_matrix_sparse();
-        let eigenvector = power_iteration(adj_mat, 1000, 1e-10);
-        eigenvector
+        let adj_mat = self.to_adjacency_matrix_sparse();
+        if self.is_directed() {
+            power_iteration(&adj_mat.transpose(), 1000, 1e-10)
+        } else {
+            power_iteration(&adj_mat, 1000, 1e-10)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+
+    #[test]
+    fn directed_degree_counts_both_directions() {
+        let graph = Graph::from_arclist(vec![(0, 1), (1, 2), (2, 0), (2, 3)], 4);
+        assert_eq!(graph.out_degree(), vec![1, 1, 2, 0]);
+        assert_eq!(graph.in_degree(), vec![1, 1, 1, 1]);
+        assert_eq!(graph.degree(), vec![2, 2, 3, 1]);
+    }
+
+    #[test]
+    fn directed_betweenness_is_not_halved() {
+        // Only the path 0 -> 1 -> 2 passes through a vertex
+        let graph = Graph::from_arclist(vec![(0, 1), (1, 2)], 3);
+        assert_eq!(graph.betweenness_centrality(), vec![0.0, 1.0, 0.0]);
+        let path = Graph::from_edgelist(vec![(0, 1), (1, 2)], 3);
+        assert_eq!(path.betweenness_centrality(), vec![0.0, 1.0, 0.0]);
     }
 }
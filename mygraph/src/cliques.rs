@@ -0,0 +1,414 @@
+use crate::graph::Graph;
+use crate::structural::{find_root, simple_adjacency};
+use std::collections::HashMap;
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Vertices in the order of repeatedly removing one of minimum remaining degree
+// (Matula & Beck). Every vertex has at most `degeneracy` neighbours after it.
+pub(crate) fn degeneracy_ordering(adjacency: &[Vec<usize>]) -> Vec<usize> {
+    let n = adjacency.len();
+    let mut degree: Vec<usize> = adjacency.iter().map(Vec::len).collect();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+    let mut buckets = vec![Vec::new(); max_degree + 1];
+    for (v, &d) in degree.iter().enumerate() {
+        buckets[d].push(v);
+    }
+
+    let mut removed = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut d = 0;
+    while order.len() < n {
+        while buckets[d].is_empty() {
+            d += 1;
+        }
+        let v = buckets[d].pop().unwrap();
+        // Buckets are not cleaned up when a degree drops, skip stale entries
+        if removed[v] || degree[v] != d {
+            continue;
+        }
+        removed[v] = true;
+        order.push(v);
+        for &w in &adjacency[v] {
+            if !removed[w] {
+                degree[w] -= 1;
+                buckets[degree[w]].push(w);
+            }
+        }
+        d = d.saturating_sub(1);
+    }
+
+    order
+}
+
+// Intersection of two sorted vertex lists
+fn intersect(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                result.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    result
+}
+
+// Number of k-cliques among `candidates`, which are all out-neighbours of the
+// clique built so far
+fn count_extensions(out_neighbors: &[Vec<usize>], candidates: &[usize], k: usize) -> usize {
+    match k {
+        0 => return 1,
+        1 => return candidates.len(),
+        _ => {}
+    }
+    candidates
+        .iter()
+        .map(|&v| {
+            count_extensions(
+                out_neighbors,
+                &intersect(candidates, &out_neighbors[v]),
+                k - 1,
+            )
+        })
+        .sum()
+}
+
+// Bron–Kerbosch with Tomita pivoting. `candidates` and `excluded` are sorted;
+// every maximal clique extending `clique` is appended to `cliques`.
+fn bron_kerbosch(
+    adjacency: &[Vec<usize>],
+    clique: &mut Vec<usize>,
+    mut candidates: Vec<usize>,
+    mut excluded: Vec<usize>,
+    cliques: &mut Vec<Vec<usize>>,
+) {
+    if candidates.is_empty() {
+        if excluded.is_empty() {
+            let mut found = clique.clone();
+            found.sort_unstable();
+            cliques.push(found);
+        }
+        return;
+    }
+
+    // The pivot with the most neighbours among the candidates leaves the fewest branches
+    let pivot = *candidates
+        .iter()
+        .chain(&excluded)
+        .max_by_key(|&&u| intersect(&candidates, &adjacency[u]).len())
+        .unwrap();
+    let branches: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|v| adjacency[pivot].binary_search(v).is_err())
+        .collect();
+
+    for v in branches {
+        clique.push(v);
+        bron_kerbosch(
+            adjacency,
+            clique,
+            intersect(&candidates, &adjacency[v]),
+            intersect(&excluded, &adjacency[v]),
+            cliques,
+        );
+        clique.pop();
+        if let Ok(i) = candidates.binary_search(&v) {
+            candidates.remove(i);
+        }
+        if let Err(i) = excluded.binary_search(&v) {
+            excluded.insert(i, v);
+        }
+    }
+}
+
+impl Graph {
+    // All maximal cliques, each as a sorted vertex list. Bron–Kerbosch with pivoting
+    // runs from every vertex on its neighbours later in the degeneracy ordering
+    // (Eppstein, Löffler & Strash), one vertex per task. Isolated vertices are
+    // cliques of size one; self-loops and parallel edges are ignored.
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        let adjacency = simple_adjacency(self);
+        let order = degeneracy_ordering(&adjacency);
+        let mut position = vec![0; self.vertices];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+
+        let clique_lists: Vec<Vec<Vec<usize>>> = order
+            .par_iter()
+            .map(|&v| {
+                let (later, earlier): (Vec<usize>, Vec<usize>) = adjacency[v]
+                    .iter()
+                    .partition(|&&w| position[w] > position[v]);
+                let mut cliques = Vec::new();
+                bron_kerbosch(&adjacency, &mut vec![v], later, earlier, &mut cliques);
+                cliques
+            })
+            .collect();
+
+        clique_lists.into_iter().flatten().collect()
+    }
+
+    // Number of cliques with k vertices (Chiba & Nishizeki). Edges point from earlier
+    // to later vertices in the degeneracy ordering, so every clique is found once from
+    // its earliest vertex, and candidate sets never exceed the degeneracy.
+    pub fn count_cliques(&self, k: usize) -> usize {
+        if k == 0 {
+            return 1;
+        }
+        let adjacency = simple_adjacency(self);
+        let order = degeneracy_ordering(&adjacency);
+        let mut position = vec![0; self.vertices];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+        let out_neighbors: Vec<Vec<usize>> = adjacency
+            .par_iter()
+            .enumerate()
+            .map(|(v, neighbors)| {
+                neighbors
+                    .iter()
+                    .copied()
+                    .filter(|&w| position[w] > position[v])
+                    .collect()
+            })
+            .collect();
+
+        (0..self.vertices)
+            .into_par_iter()
+            .map(|v| count_extensions(&out_neighbors, &out_neighbors[v], k - 1))
+            .sum()
+    }
+
+    // Size of the largest clique
+    pub fn clique_number(&self) -> usize {
+        self.maximal_cliques()
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Size of the largest clique containing each vertex
+    pub fn vertex_clique_number(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.vertices];
+        for clique in self.maximal_cliques() {
+            for &v in &clique {
+                sizes[v] = sizes[v].max(clique.len());
+            }
+        }
+        sizes
+    }
+
+    // Clique percolation (Palla et al., 2005): unions of k-cliques that can be
+    // reached from one another through k-cliques sharing k - 1 vertices. Found by
+    // joining maximal cliques of at least k vertices that share at least k - 1.
+    // Communities may overlap; each is a sorted vertex list, ordered by lowest vertex.
+    pub fn k_clique_communities(&self, k: usize) -> Vec<Vec<usize>> {
+        assert!(k >= 2, "k-clique communities need k >= 2");
+        let cliques: Vec<Vec<usize>> = self
+            .maximal_cliques()
+            .into_iter()
+            .filter(|clique| clique.len() >= k)
+            .collect();
+
+        let mut containing = vec![Vec::new(); self.vertices];
+        for (i, clique) in cliques.iter().enumerate() {
+            for &v in clique {
+                containing[v].push(i);
+            }
+        }
+
+        // Pairs of cliques with enough overlap, each found from the lower index
+        let adjacent: Vec<(usize, usize)> = (0..cliques.len())
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let mut shared: HashMap<usize, usize> = HashMap::new();
+                for &v in &cliques[i] {
+                    for &j in &containing[v] {
+                        if j > i {
+                            *shared.entry(j).or_insert(0) += 1;
+                        }
+                    }
+                }
+                shared
+                    .into_iter()
+                    .filter(|&(_, count)| count + 1 >= k)
+                    .map(move |(j, _)| (i, j))
+            })
+            .collect();
+
+        let mut parent: Vec<usize> = (0..cliques.len()).collect();
+        for (i, j) in adjacent {
+            let (a, b) = (find_root(&mut parent, i), find_root(&mut parent, j));
+            if a != b {
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (i, clique) in cliques.iter().enumerate() {
+            let root = find_root(&mut parent, i);
+            members.entry(root).or_default().extend(clique);
+        }
+        let mut communities: Vec<Vec<usize>> = members
+            .into_values()
+            .map(|mut community| {
+                community.sort_unstable();
+                community.dedup();
+                community
+            })
+            .collect();
+        communities.sort();
+        communities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::degeneracy_ordering;
+    use crate::graph::{Graph, GraphPolicy};
+    use crate::structural::simple_adjacency;
+    use crate::testutil::{self, gnp};
+    use rand::Rng;
+
+    // Every vertex subset as a bit mask that is a clique
+    fn clique_masks(graph: &Graph) -> Vec<u32> {
+        let n = graph.vertices;
+        (1..1u32 << n)
+            .filter(|&mask| {
+                (0..n).all(|u| {
+                    mask & (1 << u) == 0
+                        || (u + 1..n).all(|w| mask & (1 << w) == 0 || graph.has_edge(u, w))
+                })
+            })
+            .collect()
+    }
+
+    fn members(mask: u32) -> Vec<usize> {
+        (0..32).filter(|&v| mask & (1 << v) != 0).collect()
+    }
+
+    #[test]
+    fn maximal_cliques_match_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..100 {
+            let n = rng.random_range(1..11);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let cliques = clique_masks(&graph);
+            let mut expected: Vec<Vec<usize>> = cliques
+                .iter()
+                .filter(|&&a| !cliques.iter().any(|&b| b != a && b & a == a))
+                .map(|&mask| members(mask))
+                .collect();
+            expected.sort();
+            let mut found = graph.maximal_cliques();
+            found.sort();
+            assert_eq!(found, expected);
+
+            let largest = cliques
+                .iter()
+                .map(|m| m.count_ones() as usize)
+                .max()
+                .unwrap();
+            assert_eq!(graph.clique_number(), largest);
+            for v in 0..n {
+                let expected = cliques
+                    .iter()
+                    .filter(|&&m| m & (1 << v) != 0)
+                    .map(|m| m.count_ones() as usize)
+                    .max()
+                    .unwrap();
+                assert_eq!(graph.vertex_clique_number()[v], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn clique_counts_match_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..100 {
+            let n = rng.random_range(1..11);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let cliques = clique_masks(&graph);
+            assert_eq!(graph.count_cliques(0), 1);
+            for k in 1..=n {
+                let expected = cliques
+                    .iter()
+                    .filter(|m| m.count_ones() as usize == k)
+                    .count();
+                assert_eq!(graph.count_cliques(k), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn degeneracy_ordering_bounds_later_neighbours() {
+        let mut rng = testutil::rng();
+        for _ in 0..50 {
+            let n = rng.random_range(1..30);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let adjacency = simple_adjacency(&graph);
+            let order = degeneracy_ordering(&adjacency);
+            let mut position = vec![usize::MAX; n];
+            for (i, &v) in order.iter().enumerate() {
+                assert_eq!(position[v], usize::MAX);
+                position[v] = i;
+            }
+            // Each vertex has minimum degree among those not yet removed
+            for (i, &v) in order.iter().enumerate() {
+                let remaining =
+                    |u: usize| adjacency[u].iter().filter(|&&w| position[w] >= i).count();
+                assert!(order[i..].iter().all(|&u| remaining(v) <= remaining(u)));
+            }
+        }
+    }
+
+    #[test]
+    fn loops_and_isolated_vertices() {
+        let graph = Graph::from_edgelist_with_policy(
+            vec![(0, 1), (0, 1), (1, 1)],
+            3,
+            GraphPolicy::Multigraph,
+        );
+        let mut cliques = graph.maximal_cliques();
+        cliques.sort();
+        assert_eq!(cliques, vec![vec![0, 1], vec![2]]);
+        assert_eq!(Graph::new(0).clique_number(), 0);
+    }
+
+    #[test]
+    fn clique_percolation_joins_cliques_sharing_k_minus_one_vertices() {
+        // Triangles 0-1-2 and 1-2-3 share an edge, 3-4-5 only the vertex 3
+        let graph = Graph::from_edgelist(
+            vec![
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (3, 4),
+                (3, 5),
+                (4, 5),
+            ],
+            7,
+        );
+        assert_eq!(
+            graph.k_clique_communities(3),
+            vec![vec![0, 1, 2, 3], vec![3, 4, 5]]
+        );
+        assert_eq!(graph.k_clique_communities(2), vec![vec![0, 1, 2, 3, 4, 5]]);
+        assert!(graph.k_clique_communities(4).is_empty());
+    }
+}
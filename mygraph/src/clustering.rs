@@ -1,18 +1,136 @@
+extern crate nalgebra as na;
 use crate::graph::Graph;
+use crate::spectral::smallest_eigenpairs;
+use na::DMatrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+const KMEANS_RESTARTS: usize = 10;
+const KMEANS_MAX_ITERS: usize = 300;
+
+fn squared_distance(data: &DMatrix<f64>, row: usize, centroids: &DMatrix<f64>, c: usize) -> f64 {
+    data.row(row)
+        .iter()
+        .zip(centroids.row(c).iter())
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum()
+}
+
+// One run of Lloyd's algorithm from a k-means++ seeding, returns labels and inertia
+fn kmeans_run(data: &DMatrix<f64>, k: usize, rng: &mut StdRng) -> (Vec<usize>, f64) {
+    let (n, dim) = data.shape();
+    let mut centroids = DMatrix::zeros(k, dim);
+
+    // k-means++: pick each new centroid with probability proportional to the
+    // squared distance to the closest centroid so far
+    centroids.set_row(0, &data.row(rng.random_range(0..n)));
+    let mut closest: Vec<f64> = (0..n)
+        .map(|row| squared_distance(data, row, &centroids, 0))
+        .collect();
+    for c in 1..k {
+        let total: f64 = closest.iter().sum();
+        let mut pick = rng.random::<f64>() * total;
+        let mut chosen = n - 1;
+        for (row, &d) in closest.iter().enumerate() {
+            if pick < d {
+                chosen = row;
+                break;
+            }
+            pick -= d;
+        }
+        centroids.set_row(c, &data.row(chosen));
+        for (row, d) in closest.iter_mut().enumerate() {
+            *d = d.min(squared_distance(data, row, &centroids, c));
+        }
+    }
+
+    let mut labels = vec![usize::MAX; n];
+    for _ in 0..KMEANS_MAX_ITERS {
+        let assignment: Vec<usize> = (0..n)
+            .into_par_iter()
+            .map(|row| {
+                (0..k)
+                    .map(|c| (c, squared_distance(data, row, &centroids, c)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(c, _)| c)
+                    .unwrap()
+            })
+            .collect();
+        if assignment == labels {
+            break;
+        }
+        labels = assignment;
+
+        let mut sums = DMatrix::zeros(k, dim);
+        let mut counts = vec![0usize; k];
+        for (row, &c) in labels.iter().enumerate() {
+            let mut sum = sums.row_mut(c);
+            sum += data.row(row);
+            counts[c] += 1;
+        }
+        for (c, &count) in counts.iter().enumerate() {
+            // An empty cluster keeps its old centroid
+            if count > 0 {
+                centroids.set_row(c, &(sums.row(c) / count as f64));
+            }
+        }
+    }
+
+    let inertia = labels
+        .iter()
+        .enumerate()
+        .map(|(row, &c)| squared_distance(data, row, &centroids, c))
+        .sum();
+    (labels, inertia)
+}
+
+// Renumber labels 0, 1, 2, ... in order of first appearance
+fn relabel(labels: &[usize]) -> Vec<usize> {
+    let mut mapping = vec![usize::MAX; labels.iter().max().map_or(0, |&m| m + 1)];
+    let mut next = 0;
+    labels
+        .iter()
+        .map(|&label| {
+            if mapping[label] == usize::MAX {
+                mapping[label] = next;
+                next += 1;
+            }
+            mapping[label]
+        })
+        .collect()
+}
+
+// Cluster the rows of `data` into k groups with k-means, keeping the best of
+// several k-means++ restarts. Labels are numbered in order of first appearance.
+pub fn kmeans(data: &DMatrix<f64>, k: usize, seed: u64) -> Vec<usize> {
+    let k = k.min(data.nrows());
+    if k == 0 {
+        return vec![0; data.nrows()];
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (labels, _) = (0..KMEANS_RESTARTS)
+        .map(|_| kmeans_run(data, k, &mut rng))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    relabel(&labels)
+}
 
 impl Graph {
     // Assuming an additional function to build a community to nodes mapping
-    fn community_to_nodes(&self, communities: &Vec<usize>) -> Vec<Vec<usize>> {
-        let mut community_map: Vec<Vec<usize>> =
-            vec![vec![]; *communities.iter().max().unwrap() as usize + 1];
+    fn community_to_nodes(&self, communities: &[usize]) -> Vec<Vec<usize>> {
+        let count = communities.iter().max().map_or(0, |&c| c + 1);
+        let mut community_map: Vec<Vec<usize>> = vec![vec![]; count];
         for (node, &community) in communities.iter().enumerate() {
             community_map[community].push(node);
         }
         community_map
     }
 
-    pub fn calculate_modularity(&self, communities: &Vec<usize>) -> f64 {
-        let m = self.total_degree() as f64 / 2.0;
+    pub fn calculate_modularity(&self, communities: &[usize]) -> f64 {
+        let m = self.degree().iter().sum::<usize>() as f64 / 2.0;
         let community_map = self.community_to_nodes(communities);
 
         let mut total_modularity = 0.0;
@@ -23,18 +141,150 @@ impl Graph {
 
             // Calculate total degree of nodes in the community and internal edges
             for &node in nodes {
-                total_degree += self.degree(node);
+                total_degree += self.adj_list[node].len();
                 internal_edges += self.adj_list[node]
                     .iter()
                     .filter(|&&neighbor| communities[neighbor] == community_id)
                     .count();
             }
 
+            // Internal edges are seen from both ends, hence the 2m
             let expected_edges = total_degree as f64 * total_degree as f64 / (2.0 * m);
-            total_modularity +=
-                internal_edges as f64 / (2.0 * m) - expected_edges / (2.0 * m * 2.0);
+            total_modularity += internal_edges as f64 / (2.0 * m) - expected_edges / (2.0 * m);
         }
 
         total_modularity
     }
+
+    // Number of clusters suggested by the largest gap among the max_k + 1 smallest
+    // eigenvalues of the normalised Laplacian
+    pub fn eigengap(&self, max_k: usize) -> usize {
+        let (eigenvalues, _) = smallest_eigenpairs(&self.normalized_laplacian(), max_k + 1);
+        (1..eigenvalues.len())
+            .max_by(|&a, &b| {
+                let gap_a = eigenvalues[a] - eigenvalues[a - 1];
+                let gap_b = eigenvalues[b] - eigenvalues[b - 1];
+                // Prefer the smaller k on ties
+                gap_a.total_cmp(&gap_b).then(b.cmp(&a))
+            })
+            .unwrap_or(1)
+    }
+
+    // Spectral clustering (Ng, Jordan & Weiss): k-means on the unit-length rows of
+    // the k leading eigenvectors of the normalised Laplacian. Returns a community
+    // id per vertex, as used by `calculate_modularity`.
+    pub fn spectral_clustering(&self, k: usize) -> Vec<usize> {
+        let (_, mut embedding) = smallest_eigenpairs(&self.normalized_laplacian(), k);
+        for mut row in embedding.row_iter_mut() {
+            let norm = row.norm();
+            if norm > 0.0 {
+                row /= norm;
+            }
+        }
+        kmeans(&embedding, k, 42)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kmeans, DMatrix};
+    use crate::graph::Graph;
+    use crate::testutil;
+    use rand::Rng;
+
+    // Two triangles joined by the edge 2-3
+    fn barbell() -> Graph {
+        Graph::from_edgelist(
+            vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)],
+            6,
+        )
+    }
+
+    #[test]
+    fn kmeans_separates_blobs() {
+        let mut rng = testutil::rng();
+        let centers = [(0.0, 0.0), (10.0, 0.0), (0.0, 10.0)];
+        let data = DMatrix::from_fn(60, 2, |row, col| {
+            let center = centers[row % 3];
+            let base = if col == 0 { center.0 } else { center.1 };
+            base + rng.random::<f64>() - 0.5
+        });
+        let labels = kmeans(&data, 3, 1);
+        // Numbered in order of first appearance
+        assert_eq!(&labels[..3], &[0, 1, 2]);
+        for (row, &label) in labels.iter().enumerate() {
+            assert_eq!(label, row % 3);
+        }
+    }
+
+    #[test]
+    fn kmeans_assigns_every_row_to_its_nearest_centroid() {
+        let mut rng = testutil::rng();
+        for _ in 0..20 {
+            let n = rng.random_range(1..40);
+            let k = rng.random_range(1..6);
+            let data = DMatrix::from_fn(n, 3, |_, _| rng.random::<f64>());
+            let labels = kmeans(&data, k, rng.random());
+            let clusters = labels.iter().max().unwrap() + 1;
+            assert!(clusters <= k.min(n));
+
+            let mut centroids = DMatrix::<f64>::zeros(clusters, 3);
+            let mut counts = vec![0.0; clusters];
+            for (row, &c) in labels.iter().enumerate() {
+                let mut centroid = centroids.row_mut(c);
+                centroid += data.row(row);
+                counts[c] += 1.0;
+            }
+            for (c, &count) in counts.iter().enumerate() {
+                let mut centroid = centroids.row_mut(c);
+                centroid /= count;
+            }
+            for (row, &c) in labels.iter().enumerate() {
+                let own = (data.row(row) - centroids.row(c)).norm();
+                for other in 0..clusters {
+                    assert!(own <= (data.row(row) - centroids.row(other)).norm() + 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn kmeans_caps_k_at_the_number_of_rows() {
+        let data = DMatrix::from_row_slice(2, 1, &[0.0, 1.0]);
+        assert_eq!(kmeans(&data, 5, 0), vec![0, 1]);
+        assert_eq!(kmeans(&data, 0, 0), vec![0, 0]);
+    }
+
+    #[test]
+    fn modularity_of_two_triangles() {
+        let graph = barbell();
+        let split = [0, 0, 0, 1, 1, 1];
+        // 2 * (6/14 - (7/14)^2)
+        assert!((graph.calculate_modularity(&split) - 5.0 / 14.0).abs() < 1e-12);
+        assert!(graph.calculate_modularity(&[0; 6]).abs() < 1e-12);
+        assert_eq!(Graph::new(0).calculate_modularity(&[]), 0.0);
+    }
+
+    #[test]
+    fn spectral_clustering_finds_the_triangles() {
+        let graph = barbell();
+        assert_eq!(graph.spectral_clustering(2), vec![0, 0, 0, 1, 1, 1]);
+
+        let mut edges = Vec::new();
+        for c in 0..3 {
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    edges.push((4 * c + i, 4 * c + j));
+                }
+            }
+        }
+        let cliques = Graph::from_edgelist(edges, 12);
+        assert_eq!(cliques.eigengap(6), 3);
+        let communities = cliques.spectral_clustering(3);
+        for v in 0..12 {
+            assert_eq!(communities[v], communities[4 * (v / 4)]);
+        }
+        assert_ne!(communities[0], communities[4]);
+        assert_ne!(communities[4], communities[8]);
+    }
 }
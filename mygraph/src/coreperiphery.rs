@@ -0,0 +1,454 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Coreness of every vertex and how well the graph fits the idealised pattern
+#[derive(Clone, Debug)]
+pub struct CorePeriphery {
+    pub coreness: Vec<f64>,
+    pub fit: f64,
+}
+
+// Neighbour sets of the underlying simple undirected graph; the models below need
+// symmetric ties
+fn undirected_adjacency(graph: &Graph) -> Vec<Vec<usize>> {
+    if graph.is_directed() {
+        simple_adjacency(&Graph::from_edgelist(graph.edges(), graph.vertices))
+    } else {
+        simple_adjacency(graph)
+    }
+}
+
+// Pearson correlation of a 0/1 variable x with a variable y from their sums over
+// `pairs` observations; NaN if either is constant
+fn correlation(pairs: f64, sum_x: f64, sum_y: f64, sum_xy: f64, sum_yy: f64) -> f64 {
+    let covariance = pairs * sum_xy - sum_x * sum_y;
+    let variance_x = pairs * sum_x - sum_x * sum_x;
+    let variance_y = pairs * sum_yy - sum_y * sum_y;
+    // Rounding can leave a tiny covariance with a constant variable
+    if variance_x <= 0.0 || variance_y <= 0.0 {
+        return f64::NAN;
+    }
+    covariance / (variance_x * variance_y).sqrt()
+}
+
+// Correlation of the adjacency with the discrete ideal over core-core (1) and
+// periphery-periphery (0) pairs, from the edges within each block and the core size
+fn discrete_fit(vertices: usize, core: usize, core_edges: usize, periphery_edges: usize) -> f64 {
+    let periphery = vertices - core;
+    let core_pairs = (core * core.saturating_sub(1) / 2) as f64;
+    let periphery_pairs = (periphery * periphery.saturating_sub(1) / 2) as f64;
+    let edges = (core_edges + periphery_edges) as f64;
+    correlation(
+        core_pairs + periphery_pairs,
+        core_pairs,
+        edges,
+        core_edges as f64,
+        edges,
+    )
+}
+
+// Correlation of the adjacency with c_i c_j over all unordered vertex pairs
+fn product_fit(adj: &[Vec<usize>], c: &[f64]) -> f64 {
+    let n = adj.len();
+    let sum: f64 = c.iter().sum();
+    let squares: f64 = c.iter().map(|x| x * x).sum();
+    let fourths: f64 = c.iter().map(|x| x.powi(4)).sum();
+    let pairs = (n * n.saturating_sub(1) / 2) as f64;
+    let sum_y = (sum * sum - squares) / 2.0;
+    let sum_yy = (squares * squares - fourths) / 2.0;
+    let sum_x = (adj.iter().map(Vec::len).sum::<usize>() / 2) as f64;
+    let sum_xy: f64 = (0..n)
+        .map(|v| {
+            c[v] * adj[v]
+                .iter()
+                .filter(|&&w| w > v)
+                .map(|&w| c[w])
+                .sum::<f64>()
+        })
+        .sum();
+    correlation(pairs, sum_x, sum_y, sum_xy, sum_yy)
+}
+
+// Rombach et al.'s transition profile: the value of the vertex at `rank` (0 the most
+// peripheral) for a core of the top share `beta` with a jump of sharpness `alpha`
+fn transition(rank: usize, vertices: usize, alpha: f64, beta: f64) -> f64 {
+    let periphery = (beta * vertices as f64).floor() as usize;
+    let i = (rank + 1) as f64;
+    if rank < periphery {
+        i * (1.0 - alpha) / (2.0 * periphery as f64)
+    } else {
+        (i - periphery as f64) * (1.0 - alpha) / (2.0 * (vertices - periphery) as f64)
+            + (1.0 + alpha) / 2.0
+    }
+}
+
+// Vertex order (most peripheral first) maximising the core quality sum_ij A_ij C_i C_j
+// for one transition profile, and that quality. Starts from the degree order and
+// repeatedly reorders the vertices by the profile mass of their neighbours, which
+// by the rearrangement inequality assigns the largest values where they pay most.
+fn rombach_order(adj: &[Vec<usize>], profile: &[f64]) -> (Vec<f64>, f64) {
+    let n = adj.len();
+    let quality = |values: &[f64]| -> f64 {
+        (0..n)
+            .map(|v| values[v] * adj[v].iter().map(|&w| values[w]).sum::<f64>())
+            .sum()
+    };
+    let assign = |score: &[f64]| -> Vec<f64> {
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| score[a].total_cmp(&score[b]).then(a.cmp(&b)));
+        let mut values = vec![0.0; n];
+        for (rank, &v) in order.iter().enumerate() {
+            values[v] = profile[rank];
+        }
+        values
+    };
+
+    let degree: Vec<f64> = adj.iter().map(|a| a.len() as f64).collect();
+    let mut values = assign(&degree);
+    let mut best = (values.clone(), quality(&values));
+    for _ in 0..100 {
+        let pull: Vec<f64> = adj
+            .iter()
+            .map(|neighbors| neighbors.iter().map(|&w| values[w]).sum())
+            .collect();
+        let next = assign(&pull);
+        if next == values {
+            break;
+        }
+        values = next;
+        let q = quality(&values);
+        if q > best.1 {
+            best = (values.clone(), q);
+        }
+    }
+    best
+}
+
+impl Graph {
+    // Borgatti and Everett's discrete model: a core and a periphery such that the
+    // adjacency correlates best with an ideal of complete core and empty periphery,
+    // ignoring core-periphery ties. Starts from the best core of top-degree vertices
+    // and moves single vertices while that improves the correlation, the fit. Arcs
+    // count as undirected edges here and in the models below.
+    pub fn core_periphery_discrete(&self) -> CorePeriphery {
+        let adj = undirected_adjacency(self);
+        let n = self.vertices;
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| adj[b].len().cmp(&adj[a].len()).then(a.cmp(&b)));
+
+        // Best prefix of the degree order
+        let mut in_core = vec![false; n];
+        let mut core_edges = 0;
+        let mut periphery_edges = adj.iter().map(Vec::len).sum::<usize>() / 2;
+        let mut best = (f64::NEG_INFINITY, 0);
+        for (size, &v) in order.iter().enumerate() {
+            let core_neighbors = adj[v].iter().filter(|&&w| in_core[w]).count();
+            core_edges += core_neighbors;
+            periphery_edges -= adj[v].len() - core_neighbors;
+            in_core[v] = true;
+            let fit = discrete_fit(n, size + 1, core_edges, periphery_edges);
+            if fit > best.0 {
+                best = (fit, size + 1);
+            }
+        }
+        let mut in_core = vec![false; n];
+        for &v in &order[..best.1] {
+            in_core[v] = true;
+        }
+
+        // Single-vertex moves, best first
+        let mut core_size = best.1;
+        let mut core_neighbors: Vec<usize> = (0..n)
+            .map(|v| adj[v].iter().filter(|&&w| in_core[w]).count())
+            .collect();
+        core_edges = (0..n)
+            .filter(|&v| in_core[v])
+            .map(|v| core_neighbors[v])
+            .sum::<usize>()
+            / 2;
+        periphery_edges = (0..n)
+            .filter(|&v| !in_core[v])
+            .map(|v| adj[v].len() - core_neighbors[v])
+            .sum::<usize>()
+            / 2;
+        let mut fit = discrete_fit(n, core_size, core_edges, periphery_edges);
+        loop {
+            let moved = (0..n)
+                .into_par_iter()
+                .map(|v| {
+                    let periphery_neighbors = adj[v].len() - core_neighbors[v];
+                    let moved = if in_core[v] {
+                        discrete_fit(
+                            n,
+                            core_size - 1,
+                            core_edges - core_neighbors[v],
+                            periphery_edges + periphery_neighbors,
+                        )
+                    } else {
+                        discrete_fit(
+                            n,
+                            core_size + 1,
+                            core_edges + core_neighbors[v],
+                            periphery_edges - periphery_neighbors,
+                        )
+                    };
+                    (moved, v)
+                })
+                .filter(|&(moved, _)| !moved.is_nan())
+                .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)));
+            let Some((moved, v)) = moved else { break };
+            if moved <= fit + 1e-12 {
+                break;
+            }
+            let periphery_neighbors = adj[v].len() - core_neighbors[v];
+            let delta: isize = if in_core[v] { -1 } else { 1 };
+            if in_core[v] {
+                core_size -= 1;
+                core_edges -= core_neighbors[v];
+                periphery_edges += periphery_neighbors;
+            } else {
+                core_size += 1;
+                core_edges += core_neighbors[v];
+                periphery_edges -= periphery_neighbors;
+            }
+            in_core[v] = !in_core[v];
+            for &w in &adj[v] {
+                core_neighbors[w] = (core_neighbors[w] as isize + delta) as usize;
+            }
+            fit = moved;
+        }
+
+        CorePeriphery {
+            coreness: in_core.iter().map(|&c| if c { 1.0 } else { 0.0 }).collect(),
+            fit,
+        }
+    }
+
+    // Borgatti and Everett's continuous model: coreness c minimising the squared error
+    // of c_i c_j to the adjacency over all vertex pairs, by Gauss-Seidel updates
+    // c_i = sum_(j~i) c_j / sum_(j!=i) c_j^2. Coreness is scaled to a maximum of 1; the
+    // fit is the correlation of the adjacency with c_i c_j over all pairs.
+    pub fn core_periphery_continuous(&self, iterations: usize) -> CorePeriphery {
+        let adj = undirected_adjacency(self);
+        let n = self.vertices;
+        let mut c: Vec<f64> = adj.iter().map(|a| (a.len() as f64).sqrt()).collect();
+        let mut squares: f64 = c.iter().map(|x| x * x).sum();
+        for _ in 0..iterations {
+            let mut change: f64 = 0.0;
+            for v in 0..n {
+                let others = squares - c[v] * c[v];
+                let updated = if others > 0.0 {
+                    adj[v].iter().map(|&w| c[w]).sum::<f64>() / others
+                } else {
+                    0.0
+                };
+                change = change.max((updated - c[v]).abs());
+                squares += updated * updated - c[v] * c[v];
+                c[v] = updated;
+            }
+            if change < 1e-10 {
+                break;
+            }
+        }
+        let max = c.iter().cloned().fold(0.0, f64::max);
+        if max > 0.0 {
+            c.iter_mut().for_each(|x| *x /= max);
+        }
+
+        CorePeriphery {
+            fit: product_fit(&adj, &c),
+            coreness: c,
+        }
+    }
+
+    // Rombach et al.'s core score over a grid of `steps` + 1 values of alpha in [0, 1]
+    // and `steps` - 1 values of beta in (0, 1): the sum of each vertex's profile value
+    // weighted by the core quality it achieves, scaled to a maximum of 1. The fit is
+    // the correlation of the adjacency with the products of core scores, as for the
+    // continuous model. Grid points run in parallel.
+    pub fn rombach_core_score(&self, steps: usize) -> CorePeriphery {
+        let adj = undirected_adjacency(self);
+        let n = self.vertices;
+        let grid: Vec<(f64, f64)> = (0..=steps)
+            .flat_map(|a| {
+                (1..steps).map(move |b| (a as f64 / steps as f64, b as f64 / steps as f64))
+            })
+            .collect();
+        let fits: Vec<(Vec<f64>, f64)> = grid
+            .par_iter()
+            .map(|&(alpha, beta)| {
+                let profile: Vec<f64> = (0..n)
+                    .map(|rank| transition(rank, n, alpha, beta))
+                    .collect();
+                rombach_order(&adj, &profile)
+            })
+            .collect();
+
+        let mut coreness = vec![0.0; n];
+        for (values, quality) in &fits {
+            for (score, value) in coreness.iter_mut().zip(values) {
+                *score += value * quality;
+            }
+        }
+        let max = coreness.iter().cloned().fold(0.0, f64::max);
+        if max > 0.0 {
+            coreness.iter_mut().for_each(|x| *x /= max);
+        }
+        CorePeriphery {
+            fit: product_fit(&adj, &coreness),
+            coreness,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{product_fit, rombach_order, transition};
+    use crate::graph::Graph;
+    use crate::structural::simple_adjacency;
+    use crate::testutil::{self, random_graph};
+    use rand::Rng;
+
+    // Core K4 on 0..4, and two pendants on every core vertex
+    fn ideal() -> Graph {
+        let mut edges = Vec::new();
+        for u in 0..4 {
+            for v in u + 1..4 {
+                edges.push((u, v));
+            }
+            edges.push((u, 4 + 2 * u));
+            edges.push((u, 5 + 2 * u));
+        }
+        Graph::from_edgelist(edges, 12)
+    }
+
+    // Pearson correlation of the pairs (x, y) straight from the definition
+    fn pearson(pairs: &[(f64, f64)]) -> f64 {
+        let n = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+        let covariance: f64 = pairs.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        let var_x: f64 = pairs.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let var_y: f64 = pairs.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+        covariance / (var_x * var_y).sqrt()
+    }
+
+    // (adjacent, ideal) over unordered pairs, skipping pairs the ideal ignores
+    fn pairs(graph: &Graph, ideal: impl Fn(usize, usize) -> Option<f64>) -> Vec<(f64, f64)> {
+        let n = graph.vertices;
+        (0..n)
+            .flat_map(|u| (u + 1..n).map(move |v| (u, v)))
+            .filter_map(|(u, v)| ideal(u, v).map(|y| (graph.has_edge(u, v) as u8 as f64, y)))
+            .collect()
+    }
+
+    #[test]
+    fn discrete_model_finds_the_ideal_core() {
+        let result = ideal().core_periphery_discrete();
+        assert_eq!(result.coreness[..4], [1.0; 4]);
+        assert_eq!(result.coreness[4..], [0.0; 8]);
+        assert!((result.fit - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn arcs_count_as_edges() {
+        let arcs = Graph::from_arclist(vec![(0, 1), (0, 2), (1, 0)], 3);
+        let edges = Graph::from_edgelist(vec![(0, 1), (0, 2)], 3);
+        assert_eq!(
+            arcs.core_periphery_discrete().coreness,
+            edges.core_periphery_discrete().coreness
+        );
+        assert_eq!(
+            arcs.core_periphery_continuous(100).coreness,
+            edges.core_periphery_continuous(100).coreness
+        );
+        assert_eq!(
+            arcs.rombach_core_score(5).coreness,
+            edges.rombach_core_score(5).coreness
+        );
+    }
+
+    #[test]
+    fn discrete_fit_matches_the_correlation() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(4..14);
+            let m = rng.random_range(n..4 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            let result = graph.core_periphery_discrete();
+            let c = &result.coreness;
+            let expected = pearson(&pairs(&graph, |u, v| (c[u] == c[v]).then_some(c[u])));
+            if expected.is_nan() {
+                assert!(result.fit.is_nan());
+            } else {
+                assert!((result.fit - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn product_fit_matches_the_correlation() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(3..14);
+            let m = rng.random_range(1..4 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            let c: Vec<f64> = (0..n).map(|_| rng.random()).collect();
+            let expected = pearson(&pairs(&graph, |u, v| Some(c[u] * c[v])));
+            let fit = product_fit(&simple_adjacency(&graph), &c);
+            // NaN for complete graphs on both counts
+            assert!((fit - expected).abs() < 1e-9 || fit.is_nan() && expected.is_nan());
+        }
+    }
+
+    #[test]
+    fn continuous_coreness_is_a_fixed_point() {
+        let graph = ideal();
+        let result = graph.core_periphery_continuous(1000);
+        let c = &result.coreness;
+        assert!(c[..4].iter().all(|&x| (x - 1.0).abs() < 1e-9));
+        assert!(c[4..].iter().all(|&x| x < 0.5));
+        assert!(result.fit > 0.6);
+        // c_v sum_(j!=v) c_j^2 / sum_(j~v) c_j is the same for every vertex
+        let squares: f64 = c.iter().map(|x| x * x).sum();
+        let ratio = |v: usize| {
+            let pull: f64 = graph.successors(v).iter().map(|&w| c[w]).sum();
+            c[v] * (squares - c[v] * c[v]) / pull
+        };
+        for v in 1..12 {
+            assert!((ratio(v) - ratio(0)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn rombach_profiles_and_scores() {
+        // A sharp jump halfway: periphery 0, core 1
+        let profile: Vec<f64> = (0..4).map(|rank| transition(rank, 4, 1.0, 0.5)).collect();
+        assert_eq!(profile, vec![0.0, 0.0, 1.0, 1.0]);
+        let smooth: Vec<f64> = (0..10).map(|rank| transition(rank, 10, 0.2, 0.3)).collect();
+        assert!(smooth.windows(2).all(|w| w[0] < w[1]));
+
+        let graph = ideal();
+        let adj = simple_adjacency(&graph);
+        let profile: Vec<f64> = (0..12).map(|rank| transition(rank, 12, 0.5, 0.5)).collect();
+        let (values, quality) = rombach_order(&adj, &profile);
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+        assert_eq!(sorted, profile);
+        let expected: f64 = (0..12)
+            .flat_map(|v| adj[v].iter().map(move |&w| (v, w)))
+            .map(|(v, w)| values[v] * values[w])
+            .sum();
+        assert!((quality - expected).abs() < 1e-9);
+
+        let result = graph.rombach_core_score(10);
+        let core_min = result.coreness[..4].iter().cloned().fold(1.0, f64::min);
+        let periphery_max = result.coreness[4..].iter().cloned().fold(0.0, f64::max);
+        assert!(core_min > periphery_max);
+        assert_eq!(result.coreness.iter().cloned().fold(0.0, f64::max), 1.0);
+        assert!(result.fit > 0.0);
+    }
+}
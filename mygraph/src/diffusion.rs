@@ -0,0 +1,520 @@
+use crate::graph::Graph;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpidemicModel {
+    // Recovered vertices are immune
+    Sir,
+    // Recovered vertices are susceptible again
+    Sis,
+}
+
+// Settings of an epidemic simulation
+#[derive(Clone, Debug)]
+pub struct Epidemic {
+    pub model: EpidemicModel,
+    // Per step probability (discrete time) or rate (continuous time) of infection
+    // along every edge from an infected to a susceptible vertex
+    pub transmission: f64,
+    // Per step probability or rate of recovery of every infected vertex
+    pub recovery: f64,
+    // Vertices infected at time 0; one random vertex per run if empty
+    pub initial: Vec<usize>,
+    // Runs stop at this time at the latest, or when nobody is infected
+    pub max_time: f64,
+    pub runs: usize,
+    pub seed: u64,
+}
+
+impl Default for Epidemic {
+    fn default() -> Self {
+        Epidemic {
+            model: EpidemicModel::Sir,
+            transmission: 0.1,
+            recovery: 0.1,
+            initial: Vec::new(),
+            max_time: 100.0,
+            runs: 100,
+            seed: 42,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CascadeModel {
+    // Every newly activated vertex gets one chance to activate each successor
+    IndependentCascade { probability: f64 },
+    // A vertex activates once the share of its active predecessors reaches a
+    // threshold drawn uniformly from [0, 1] in every run
+    LinearThreshold,
+}
+
+// Number of vertices in each state after every event or step of one run. For
+// cascades, infected are the vertices activated in the latest round and recovered
+// those activated before.
+#[derive(Clone, Debug, Default)]
+pub struct TimeSeries {
+    pub times: Vec<f64>,
+    pub susceptible: Vec<usize>,
+    pub infected: Vec<usize>,
+    pub recovered: Vec<usize>,
+}
+
+impl TimeSeries {
+    fn record(&mut self, time: f64, susceptible: usize, infected: usize, recovered: usize) {
+        self.times.push(time);
+        self.susceptible.push(susceptible);
+        self.infected.push(infected);
+        self.recovered.push(recovered);
+    }
+
+    // Vertices infected at the end: ever infected for SIR and cascades, the final
+    // prevalence for SIS
+    pub fn final_size(&self) -> usize {
+        self.infected.last().copied().unwrap_or(0) + self.recovered.last().copied().unwrap_or(0)
+    }
+}
+
+// Time series of many independent runs
+#[derive(Clone, Debug)]
+pub struct Outcome {
+    pub vertices: usize,
+    pub runs: Vec<TimeSeries>,
+}
+
+impl Outcome {
+    pub fn final_sizes(&self) -> Vec<usize> {
+        self.runs.iter().map(TimeSeries::final_size).collect()
+    }
+
+    // Share of runs ending with each final size, indexed by size
+    pub fn final_size_distribution(&self) -> Vec<f64> {
+        let mut distribution = vec![0.0; self.vertices + 1];
+        for size in self.final_sizes() {
+            distribution[size] += 1.0 / self.runs.len() as f64;
+        }
+        distribution
+    }
+
+    pub fn mean_final_size(&self) -> f64 {
+        self.final_sizes().iter().sum::<usize>() as f64 / self.runs.len() as f64
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+// Vertices infected at the start of a run
+fn initial_infected(graph: &Graph, initial: &[usize], rng: &mut StdRng) -> Vec<usize> {
+    let mut infected = if initial.is_empty() && graph.vertices > 0 {
+        vec![rng.random_range(0..graph.vertices)]
+    } else {
+        initial.to_vec()
+    };
+    infected.sort_unstable();
+    infected.dedup();
+    infected
+}
+
+// Infected vertices with constant-time insertion, removal and uniform sampling
+struct InfectedSet {
+    members: Vec<usize>,
+    position: Vec<usize>,
+}
+
+impl InfectedSet {
+    fn new(vertices: usize) -> Self {
+        InfectedSet {
+            members: Vec::new(),
+            position: vec![usize::MAX; vertices],
+        }
+    }
+
+    fn insert(&mut self, v: usize) {
+        self.position[v] = self.members.len();
+        self.members.push(v);
+    }
+
+    fn remove(&mut self, v: usize) {
+        let i = self.position[v];
+        let last = *self.members.last().unwrap();
+        self.members.swap_remove(i);
+        if last != v {
+            self.position[last] = i;
+        }
+        self.position[v] = usize::MAX;
+    }
+}
+
+// Vertices activated in every round of one cascade from `seeds`, seeds first
+pub(crate) fn cascade_rounds(
+    graph: &Graph,
+    seeds: &[usize],
+    model: CascadeModel,
+    rng: &mut StdRng,
+) -> Vec<Vec<usize>> {
+    let mut active = vec![false; graph.vertices];
+    let mut frontier: Vec<usize> = Vec::new();
+    for &s in seeds {
+        if !active[s] {
+            active[s] = true;
+            frontier.push(s);
+        }
+    }
+
+    let thresholds: Vec<f64> = match model {
+        CascadeModel::LinearThreshold => (0..graph.vertices).map(|_| rng.random::<f64>()).collect(),
+        CascadeModel::IndependentCascade { .. } => Vec::new(),
+    };
+    let mut active_predecessors = vec![0usize; graph.vertices];
+
+    let mut rounds = Vec::new();
+    while !frontier.is_empty() {
+        let mut next = Vec::new();
+        for &v in &frontier {
+            for &w in graph.successors(v) {
+                if active[w] {
+                    continue;
+                }
+                let activated = match model {
+                    CascadeModel::IndependentCascade { probability } => {
+                        rng.random::<f64>() < probability
+                    }
+                    CascadeModel::LinearThreshold => {
+                        active_predecessors[w] += 1;
+                        let weight =
+                            active_predecessors[w] as f64 / graph.predecessors(w).len() as f64;
+                        weight >= thresholds[w]
+                    }
+                };
+                if activated {
+                    active[w] = true;
+                    next.push(w);
+                }
+            }
+        }
+        rounds.push(frontier);
+        frontier = next;
+    }
+    rounds
+}
+
+impl Graph {
+    // Discrete-time SIR or SIS: in every step each infected vertex infects each
+    // susceptible neighbour with probability `transmission`, then recovers with
+    // probability `recovery`. All vertices update at once.
+    pub fn simulate_epidemic(&self, settings: &Epidemic) -> Outcome {
+        let runs = (0..settings.runs as u64)
+            .into_par_iter()
+            .map(|run| {
+                let mut rng = StdRng::seed_from_u64(settings.seed.wrapping_add(run));
+                let mut state = vec![State::Susceptible; self.vertices];
+                let mut infected = initial_infected(self, &settings.initial, &mut rng);
+                for &v in &infected {
+                    state[v] = State::Infected;
+                }
+                let mut recovered = 0;
+                let mut series = TimeSeries::default();
+                let mut time = 0.0;
+                series.record(time, self.vertices - infected.len(), infected.len(), 0);
+
+                while !infected.is_empty() && time < settings.max_time {
+                    let mut newly_infected = Vec::new();
+                    for &v in &infected {
+                        for &w in &self.adj_list[v] {
+                            if state[w] == State::Susceptible
+                                && rng.random::<f64>() < settings.transmission
+                            {
+                                state[w] = State::Infected;
+                                newly_infected.push(w);
+                            }
+                        }
+                    }
+                    // Only those infected before this step can recover in it
+                    infected.retain(|&v| {
+                        if rng.random::<f64>() >= settings.recovery {
+                            return true;
+                        }
+                        state[v] = match settings.model {
+                            EpidemicModel::Sir => {
+                                recovered += 1;
+                                State::Recovered
+                            }
+                            EpidemicModel::Sis => State::Susceptible,
+                        };
+                        false
+                    });
+                    infected.extend(newly_infected);
+                    time += 1.0;
+                    let susceptible = self.vertices - infected.len() - recovered;
+                    series.record(time, susceptible, infected.len(), recovered);
+                }
+                series
+            })
+            .collect();
+
+        Outcome {
+            vertices: self.vertices,
+            runs,
+        }
+    }
+
+    // Continuous-time SIR or SIS with the Gillespie algorithm: infection at rate
+    // `transmission` along every edge from an infected vertex, recovery at rate
+    // `recovery`. Infection events pick an edge from an infected vertex and do
+    // nothing if its other end is not susceptible, which keeps every event
+    // constant time and the process exact. One point per event.
+    pub fn simulate_epidemic_gillespie(&self, settings: &Epidemic) -> Outcome {
+        let max_degree = self.adj_list.iter().map(Vec::len).max().unwrap_or(0);
+        let runs = (0..settings.runs as u64)
+            .into_par_iter()
+            .map(|run| {
+                let mut rng = StdRng::seed_from_u64(settings.seed.wrapping_add(run));
+                let mut state = vec![State::Susceptible; self.vertices];
+                let mut infected = InfectedSet::new(self.vertices);
+                // Sum of the degrees of the infected vertices
+                let mut infected_degree = 0;
+                for v in initial_infected(self, &settings.initial, &mut rng) {
+                    state[v] = State::Infected;
+                    infected.insert(v);
+                    infected_degree += self.adj_list[v].len();
+                }
+                let mut recovered = 0;
+                let mut series = TimeSeries::default();
+                let mut time = 0.0;
+                let count = |infected: &InfectedSet, recovered: usize| {
+                    (
+                        self.vertices - infected.members.len() - recovered,
+                        infected.members.len(),
+                    )
+                };
+                let (s, i) = count(&infected, recovered);
+                series.record(time, s, i, recovered);
+
+                while !infected.members.is_empty() {
+                    let infection_rate = settings.transmission * infected_degree as f64;
+                    let recovery_rate = settings.recovery * infected.members.len() as f64;
+                    let total_rate = infection_rate + recovery_rate;
+                    if total_rate <= 0.0 {
+                        break;
+                    }
+                    time += -(1.0 - rng.random::<f64>()).ln() / total_rate;
+                    if time > settings.max_time {
+                        break;
+                    }
+
+                    if rng.random::<f64>() * total_rate < infection_rate {
+                        // An infected vertex by degree, by rejection, then one of its edges
+                        let v = loop {
+                            let v = infected.members[rng.random_range(0..infected.members.len())];
+                            if rng.random::<f64>() * (max_degree as f64)
+                                < self.adj_list[v].len() as f64
+                            {
+                                break v;
+                            }
+                        };
+                        let w = self.adj_list[v][rng.random_range(0..self.adj_list[v].len())];
+                        if state[w] != State::Susceptible {
+                            continue;
+                        }
+                        state[w] = State::Infected;
+                        infected.insert(w);
+                        infected_degree += self.adj_list[w].len();
+                    } else {
+                        let v = infected.members[rng.random_range(0..infected.members.len())];
+                        infected.remove(v);
+                        infected_degree -= self.adj_list[v].len();
+                        state[v] = match settings.model {
+                            EpidemicModel::Sir => {
+                                recovered += 1;
+                                State::Recovered
+                            }
+                            EpidemicModel::Sis => State::Susceptible,
+                        };
+                    }
+                    let (s, i) = count(&infected, recovered);
+                    series.record(time, s, i, recovered);
+                }
+                series
+            })
+            .collect();
+
+        Outcome {
+            vertices: self.vertices,
+            runs,
+        }
+    }
+
+    // Independent cascade or linear threshold spread from `seeds` (one random vertex
+    // per run if empty), `runs` times. Every run records the vertices activated per round.
+    pub fn simulate_cascade(
+        &self,
+        seeds: &[usize],
+        model: CascadeModel,
+        runs: usize,
+        seed: u64,
+    ) -> Outcome {
+        let runs = (0..runs as u64)
+            .into_par_iter()
+            .map(|run| {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(run));
+                let seeds = initial_infected(self, seeds, &mut rng);
+                let rounds = cascade_rounds(self, &seeds, model, &mut rng);
+                let mut series = TimeSeries::default();
+                let mut activated = 0;
+                for (round, frontier) in rounds.iter().enumerate() {
+                    let susceptible = self.vertices - activated - frontier.len();
+                    series.record(round as f64, susceptible, frontier.len(), activated);
+                    activated += frontier.len();
+                }
+                // The last round activated nobody new
+                series.record(rounds.len() as f64, self.vertices - activated, 0, activated);
+                series
+            })
+            .collect();
+
+        Outcome {
+            vertices: self.vertices,
+            runs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CascadeModel, Epidemic, EpidemicModel, Outcome};
+    use crate::graph::Graph;
+
+    fn path(n: usize) -> Graph {
+        Graph::from_edgelist((1..n).map(|v| (v - 1, v)).collect(), n)
+    }
+
+    // Every point accounts for all vertices and time never runs backwards
+    fn assert_consistent(outcome: &Outcome) {
+        for series in &outcome.runs {
+            for i in 0..series.times.len() {
+                let total = series.susceptible[i] + series.infected[i] + series.recovered[i];
+                assert_eq!(total, outcome.vertices);
+            }
+            assert!(series.times.windows(2).all(|t| t[0] <= t[1]));
+        }
+        let total: f64 = outcome.final_size_distribution().iter().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn certain_transmission_sweeps_a_path() {
+        let settings = Epidemic {
+            transmission: 1.0,
+            recovery: 1.0,
+            initial: vec![0],
+            runs: 3,
+            ..Epidemic::default()
+        };
+        let outcome = path(6).simulate_epidemic(&settings);
+        assert_consistent(&outcome);
+        for series in &outcome.runs {
+            assert_eq!(series.infected, vec![1, 1, 1, 1, 1, 1, 0]);
+            assert_eq!(series.recovered, vec![0, 1, 2, 3, 4, 5, 6]);
+        }
+        assert_eq!(outcome.mean_final_size(), 6.0);
+
+        let settings = Epidemic {
+            transmission: 0.0,
+            initial: vec![2, 4, 2],
+            ..settings
+        };
+        assert_eq!(
+            path(6).simulate_epidemic(&settings).final_sizes(),
+            vec![2; 3]
+        );
+    }
+
+    #[test]
+    fn transmission_over_one_edge() {
+        let edge = Graph::from_edgelist(vec![(0, 1)], 2);
+        let (transmission, recovery) = (0.3, 0.5);
+        let settings = Epidemic {
+            transmission,
+            recovery,
+            initial: vec![0],
+            runs: 20_000,
+            seed: 7,
+            ..Epidemic::default()
+        };
+        // Discrete time: the edge transmits in some step before 0 recovers
+        let outcome = edge.simulate_epidemic(&settings);
+        assert_consistent(&outcome);
+        let expected = transmission / (1.0 - (1.0 - transmission) * (1.0 - recovery));
+        assert!((outcome.final_size_distribution()[2] - expected).abs() < 0.015);
+
+        // Continuous time: infection wins the race against recovery
+        let outcome = edge.simulate_epidemic_gillespie(&settings);
+        assert_consistent(&outcome);
+        let expected = transmission / (transmission + recovery);
+        assert!((outcome.final_size_distribution()[2] - expected).abs() < 0.015);
+    }
+
+    #[test]
+    fn sis_runs_stay_consistent() {
+        let settings = Epidemic {
+            model: EpidemicModel::Sis,
+            transmission: 0.5,
+            recovery: 0.2,
+            max_time: 30.0,
+            runs: 20,
+            ..Epidemic::default()
+        };
+        let graph = path(10);
+        for outcome in [
+            graph.simulate_epidemic(&settings),
+            graph.simulate_epidemic_gillespie(&settings),
+        ] {
+            assert_consistent(&outcome);
+            for series in &outcome.runs {
+                assert!(series.recovered.iter().all(|&r| r == 0));
+                assert!(*series.times.last().unwrap() <= 30.0);
+            }
+        }
+    }
+
+    #[test]
+    fn independent_cascade_on_a_star() {
+        let star = Graph::from_edgelist((1..=10).map(|v| (0, v)).collect(), 11);
+        let model = CascadeModel::IndependentCascade { probability: 0.3 };
+        let outcome = star.simulate_cascade(&[0], model, 20_000, 7);
+        assert_consistent(&outcome);
+        // The centre tries each leaf once, and leaves can only reach the centre
+        assert!((outcome.mean_final_size() - 4.0).abs() < 0.05);
+        for series in &outcome.runs {
+            assert!(series.times.len() <= 3);
+        }
+    }
+
+    #[test]
+    fn linear_threshold_cascades() {
+        // A single predecessor always reaches the threshold
+        let chain = Graph::from_arclist((1..5).map(|v| (v - 1, v)).collect(), 5);
+        let outcome = chain.simulate_cascade(&[0], CascadeModel::LinearThreshold, 10, 7);
+        assert_consistent(&outcome);
+        for series in &outcome.runs {
+            assert_eq!(series.infected, vec![1, 1, 1, 1, 1, 0]);
+        }
+
+        // One of two predecessors active: activation with probability 1/2
+        let fork = Graph::from_arclist(vec![(0, 2), (1, 2)], 3);
+        let outcome = fork.simulate_cascade(&[0], CascadeModel::LinearThreshold, 20_000, 7);
+        let distribution = outcome.final_size_distribution();
+        assert!((distribution[1] - 0.5).abs() < 0.015);
+        assert!((distribution[2] - 0.5).abs() < 0.015);
+        assert_eq!(distribution[3], 0.0);
+    }
+}
@@ -0,0 +1,416 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Independent Markov chains for sampled rank probabilities
+const CHAINS: usize = 8;
+// Sweeps of n swap attempts each before a chain is sampled for the first time
+const BURN_IN_SWEEPS: usize = 1000;
+
+// The neighbourhood-inclusion preorder: u is dominated by v if N(u) ⊆ N[v].
+// Vertices dominating each other are equivalent; any centrality index that
+// respects the preorder ranks them equally.
+pub struct Dominance {
+    // Vertices that dominate each vertex, itself excluded, sorted
+    pub dominated_by: Vec<Vec<usize>>,
+}
+
+impl Dominance {
+    pub fn vertices(&self) -> usize {
+        self.dominated_by.len()
+    }
+
+    // Whether N(u) ⊆ N[v]
+    pub fn dominates(&self, v: usize, u: usize) -> bool {
+        u == v || self.dominated_by[u].binary_search(&v).is_ok()
+    }
+
+    // Whether v dominates u but not the other way round
+    pub fn strictly_dominates(&self, v: usize, u: usize) -> bool {
+        self.dominates(v, u) && !self.dominates(u, v)
+    }
+
+    // Vertices that strictly dominate each vertex
+    fn strict_dominators(&self) -> Vec<Vec<usize>> {
+        (0..self.vertices())
+            .map(|u| {
+                self.dominated_by[u]
+                    .iter()
+                    .copied()
+                    .filter(|&v| !self.dominates(u, v))
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Class id of every vertex, vertices dominating each other share a class.
+    // Classes are numbered in order of their lowest vertex.
+    pub fn equivalence_classes(&self) -> Vec<usize> {
+        let mut classes = vec![usize::MAX; self.vertices()];
+        let mut next = 0;
+        for u in 0..self.vertices() {
+            if classes[u] != usize::MAX {
+                continue;
+            }
+            classes[u] = next;
+            for &v in &self.dominated_by[u] {
+                if self.dominates(u, v) {
+                    classes[v] = next;
+                }
+            }
+            next += 1;
+        }
+        classes
+    }
+
+    // Cover pairs (u, v) of the strict order: v strictly dominates u with nothing
+    // strictly in between. Equivalent vertices have the same covers.
+    pub fn transitive_reduction(&self) -> Vec<(usize, usize)> {
+        let strict = self.strict_dominators();
+        (0..self.vertices())
+            .into_par_iter()
+            .flat_map_iter(|u| {
+                let above = &strict[u];
+                above
+                    .iter()
+                    .copied()
+                    .filter(|&v| !above.iter().any(|&w| self.strictly_dominates(v, w)))
+                    .map(move |v| (u, v))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    // Whether a centrality index respects the preorder: every vertex scores at
+    // least as high as the vertices it dominates
+    pub fn preserves(&self, scores: &[f64]) -> bool {
+        let tolerance = |x: f64| 1e-9 * x.abs().max(1.0);
+        (0..self.vertices()).all(|u| {
+            self.dominated_by[u]
+                .iter()
+                .all(|&v| scores[v] >= scores[u] - tolerance(scores[u]))
+        })
+    }
+
+    // Best and worst possible rank of every vertex over all rankings that respect
+    // the preorder, rank 0 being the top. Equivalent vertices may be ranked either way.
+    pub fn rank_intervals(&self) -> Vec<(usize, usize)> {
+        let n = self.vertices();
+        let strict = self.strict_dominators();
+        let mut below = vec![0; n];
+        for above in &strict {
+            for &v in above {
+                below[v] += 1;
+            }
+        }
+        (0..n)
+            .map(|u| (strict[u].len(), n - 1 - below[u]))
+            .collect()
+    }
+
+    // Probability of every rank (columns, 0 the top) for every vertex (rows) in a
+    // uniformly random linear extension of the strict order. Exact, by counting
+    // extensions over the sets of vertices that can fill the top ranks; this is
+    // exponential in the width of the order, so only for small graphs.
+    pub fn rank_probabilities(&self) -> Vec<Vec<f64>> {
+        let n = self.vertices();
+        assert!(n <= 64, "exact rank probabilities need at most 64 vertices");
+        let above: Vec<u64> = self
+            .strict_dominators()
+            .iter()
+            .map(|vs| vs.iter().fold(0u64, |mask, &v| mask | 1 << v))
+            .collect();
+        let can_follow = |set: u64, u: usize| set & (1 << u) == 0 && above[u] & !set == 0;
+
+        // Number of ways to rank the vertices of each reachable top set, layer by size
+        let mut layers: Vec<HashMap<u64, f64>> = vec![HashMap::from([(0u64, 1.0)])];
+        for _ in 0..n {
+            let mut next: HashMap<u64, f64> = HashMap::new();
+            for (&set, &ways) in layers.last().unwrap() {
+                for u in (0..n).filter(|&u| can_follow(set, u)) {
+                    *next.entry(set | 1 << u).or_insert(0.0) += ways;
+                }
+            }
+            layers.push(next);
+        }
+
+        // Number of ways to rank the rest below each top set
+        let mut rest: HashMap<u64, f64> = HashMap::new();
+        for layer in layers.iter().rev() {
+            for &set in layer.keys() {
+                let ways = if set.count_ones() as usize == n {
+                    1.0
+                } else {
+                    (0..n)
+                        .filter(|&u| can_follow(set, u))
+                        .map(|u| rest[&(set | 1 << u)])
+                        .sum()
+                };
+                rest.insert(set, ways);
+            }
+        }
+
+        let total = rest[&0];
+        let mut probabilities = vec![vec![0.0; n]; n];
+        for (rank, layer) in layers.iter().enumerate().take(n) {
+            for (&set, &ways) in layer {
+                for u in (0..n).filter(|&u| can_follow(set, u)) {
+                    probabilities[u][rank] += ways * rest[&(set | 1 << u)] / total;
+                }
+            }
+        }
+        probabilities
+    }
+
+    // Estimate of `rank_probabilities` from `samples` linear extensions drawn by
+    // Markov chains that swap adjacent incomparable vertices (Bubley & Dyer).
+    // Samples are taken one sweep of n steps apart after a burn-in.
+    pub fn sampled_rank_probabilities(&self, samples: usize, seed: u64) -> Vec<Vec<f64>> {
+        assert!(samples > 0, "at least one sample is needed");
+        let n = self.vertices();
+        let strict = self.strict_dominators();
+
+        // Vertices with fewer strict dominators first is a valid starting extension
+        let mut start: Vec<usize> = (0..n).collect();
+        start.sort_by_key(|&u| strict[u].len());
+
+        let counts = (0..CHAINS)
+            .into_par_iter()
+            .map(|chain| {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(chain as u64));
+                let mut ranking = start.clone();
+                let mut counts = vec![vec![0usize; n]; n];
+                let step = |ranking: &mut [usize], rng: &mut StdRng| {
+                    // A single vertex has nothing to swap with
+                    if n < 2 {
+                        return;
+                    }
+                    for _ in 0..n {
+                        let i = rng.random_range(0..n - 1);
+                        let (a, b) = (ranking[i], ranking[i + 1]);
+                        // Lazy chain: only swap half of the time
+                        if rng.random::<bool>() && strict[b].binary_search(&a).is_err() {
+                            ranking.swap(i, i + 1);
+                        }
+                    }
+                };
+
+                for _ in 0..BURN_IN_SWEEPS {
+                    step(&mut ranking, &mut rng);
+                }
+                for _ in (chain..samples).step_by(CHAINS) {
+                    step(&mut ranking, &mut rng);
+                    for (rank, &u) in ranking.iter().enumerate() {
+                        counts[u][rank] += 1;
+                    }
+                }
+                counts
+            })
+            .reduce(
+                || vec![vec![0usize; n]; n],
+                |mut a, b| {
+                    for (row, other) in a.iter_mut().zip(b) {
+                        for (x, y) in row.iter_mut().zip(other) {
+                            *x += y;
+                        }
+                    }
+                    a
+                },
+            );
+
+        counts
+            .into_iter()
+            .map(|row| row.into_iter().map(|c| c as f64 / samples as f64).collect())
+            .collect()
+    }
+}
+
+impl Graph {
+    // Neighbourhood-inclusion preorder of the vertices. Self-loops and parallel
+    // edges are ignored. Only vertices next to a lowest-degree neighbour of u can
+    // dominate it, which keeps the search local.
+    pub fn neighborhood_inclusion(&self) -> Dominance {
+        let adjacency = simple_adjacency(self);
+        let dominated_by = (0..self.vertices)
+            .into_par_iter()
+            .map(|u| {
+                let neighbors = &adjacency[u];
+                // An isolated vertex is dominated by everyone
+                let Some(&pivot) = neighbors.iter().min_by_key(|&&w| adjacency[w].len()) else {
+                    return (0..self.vertices).filter(|&v| v != u).collect();
+                };
+                let mut candidates: Vec<usize> = adjacency[pivot]
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(pivot))
+                    .filter(|&v| v != u)
+                    .filter(|&v| {
+                        neighbors
+                            .iter()
+                            .all(|&w| w == v || adjacency[v].binary_search(&w).is_ok())
+                    })
+                    .collect();
+                candidates.sort_unstable();
+                candidates
+            })
+            .collect();
+
+        Dominance { dominated_by }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dominance;
+    use crate::graph::Graph;
+    use crate::testutil::{self, gnp};
+    use rand::Rng;
+
+    // Every ordering of 0..n
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![Vec::new()];
+        }
+        let mut all = Vec::new();
+        for shorter in permutations(n - 1) {
+            for i in 0..n {
+                let mut longer = shorter.clone();
+                longer.insert(i, n - 1);
+                all.push(longer);
+            }
+        }
+        all
+    }
+
+    // Rank probabilities over all orderings that respect the strict order
+    fn brute_force_ranks(dominance: &Dominance) -> Vec<Vec<f64>> {
+        let n = dominance.vertices();
+        let extensions: Vec<Vec<usize>> = permutations(n)
+            .into_iter()
+            .filter(|order| {
+                (0..n)
+                    .all(|i| (i + 1..n).all(|j| !dominance.strictly_dominates(order[j], order[i])))
+            })
+            .collect();
+        let mut probabilities = vec![vec![0.0; n]; n];
+        for order in &extensions {
+            for (rank, &v) in order.iter().enumerate() {
+                probabilities[v][rank] += 1.0 / extensions.len() as f64;
+            }
+        }
+        probabilities
+    }
+
+    #[test]
+    fn inclusion_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..100 {
+            let n = rng.random_range(1..15);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let dominance = graph.neighborhood_inclusion();
+            for u in 0..n {
+                for v in (0..n).filter(|&v| v != u) {
+                    let expected = graph
+                        .neighbors(u)
+                        .iter()
+                        .all(|&w| w == v || graph.has_edge(v, w));
+                    assert_eq!(dominance.dominates(v, u), expected);
+                }
+            }
+            assert!(
+                dominance.preserves(&graph.degree().iter().map(|&d| d as f64).collect::<Vec<_>>())
+            );
+
+            // Covers: strict pairs with nothing strictly in between
+            let mut covers = dominance.transitive_reduction();
+            covers.sort_unstable();
+            let mut expected = Vec::new();
+            for u in 0..n {
+                for v in 0..n {
+                    if dominance.strictly_dominates(v, u)
+                        && !(0..n).any(|w| {
+                            dominance.strictly_dominates(w, u) && dominance.strictly_dominates(v, w)
+                        })
+                    {
+                        expected.push((u, v));
+                    }
+                }
+            }
+            assert_eq!(covers, expected);
+        }
+    }
+
+    #[test]
+    fn rank_probabilities_match_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..40 {
+            let n = rng.random_range(1..7);
+            let p = rng.random();
+            let dominance = gnp(&mut rng, n, p, false).neighborhood_inclusion();
+            let exact = dominance.rank_probabilities();
+            let expected = brute_force_ranks(&dominance);
+            for (row, expected_row) in exact.iter().zip(&expected) {
+                for (x, y) in row.iter().zip(expected_row) {
+                    assert!((x - y).abs() < 1e-9);
+                }
+            }
+            // Every vertex's rank lies within its interval
+            for (v, &(best, worst)) in dominance.rank_intervals().iter().enumerate() {
+                for (rank, &probability) in exact[v].iter().enumerate() {
+                    assert_eq!(probability > 0.0, (best..=worst).contains(&rank));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sampled_ranks_approach_exact_ranks() {
+        // Path 0-1-2-3-4 with a pendant 5 at 2
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 4), (2, 5)], 6);
+        let dominance = graph.neighborhood_inclusion();
+        let exact = dominance.rank_probabilities();
+        let sampled = dominance.sampled_rank_probabilities(20000, 3);
+        for (row, sampled_row) in exact.iter().zip(&sampled) {
+            assert!((sampled_row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            for (x, y) in row.iter().zip(sampled_row) {
+                assert!((x - y).abs() < 0.03, "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn star_and_path_preorders() {
+        let star = Graph::from_edgelist(vec![(0, 1), (0, 2), (0, 3)], 4).neighborhood_inclusion();
+        assert_eq!(star.equivalence_classes(), vec![0, 1, 1, 1]);
+        assert!(star.strictly_dominates(0, 1));
+        assert!(!star.preserves(&[0.0, 1.0, 1.0, 1.0]));
+        assert_eq!(star.rank_intervals(), vec![(0, 0), (1, 3), (1, 3), (1, 3)]);
+
+        let path = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3)], 4).neighborhood_inclusion();
+        assert_eq!(path.equivalence_classes(), vec![0, 1, 2, 3]);
+        let mut covers = path.transitive_reduction();
+        covers.sort_unstable();
+        // An end's only neighbour is in the closed neighbourhood of both inner vertices
+        assert_eq!(covers, vec![(0, 1), (0, 2), (3, 1), (3, 2)]);
+        assert_eq!(path.rank_intervals(), vec![(2, 3), (0, 1), (0, 1), (2, 3)]);
+
+        // An isolated vertex is dominated by everyone
+        let isolated = Graph::from_edgelist(vec![(0, 1)], 3).neighborhood_inclusion();
+        assert!(isolated.strictly_dominates(0, 2) && isolated.strictly_dominates(1, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one sample")]
+    fn sampling_needs_samples() {
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2)], 3);
+        graph
+            .neighborhood_inclusion()
+            .sampled_rank_probabilities(0, 1);
+    }
+}
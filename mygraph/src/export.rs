@@ -0,0 +1,263 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::sync::Arc;
+
+use parquet::data_type::ByteArray;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+use crate::graph::Graph;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+    Parquet,
+}
+
+impl OutputFormat {
+    // Guess the format from a file extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit('.').next()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(OutputFormat::Csv),
+            "json" => Some(OutputFormat::Json),
+            "parquet" | "pq" => Some(OutputFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Column {
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+}
+
+impl Column {
+    fn len(&self) -> usize {
+        match self {
+            Column::Int(values) => values.len(),
+            Column::Float(values) => values.len(),
+        }
+    }
+
+    fn json_value(&self, row: usize) -> serde_json::Value {
+        match self {
+            Column::Int(values) => values[row].into(),
+            // NaN and infinities have no JSON representation and become null
+            Column::Float(values) => serde_json::Number::from_f64(values[row])
+                .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        }
+    }
+
+    fn csv_value(&self, row: usize) -> String {
+        match self {
+            Column::Int(values) => values[row].to_string(),
+            Column::Float(values) => values[row].to_string(),
+        }
+    }
+}
+
+impl From<Vec<usize>> for Column {
+    fn from(values: Vec<usize>) -> Self {
+        Column::Int(values.into_iter().map(|v| v as i64).collect())
+    }
+}
+
+impl From<Vec<f64>> for Column {
+    fn from(values: Vec<f64>) -> Self {
+        Column::Float(values)
+    }
+}
+
+// Per-vertex results keyed by vertex label
+pub struct VertexTable {
+    labels: Vec<String>,
+    columns: Vec<(String, Column)>,
+}
+
+impl VertexTable {
+    pub fn new(graph: &Graph) -> Self {
+        let labels = (0..graph.vertices).map(|v| graph.label(v)).collect();
+        VertexTable {
+            labels,
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn column<C: Into<Column>>(mut self, name: &str, values: C) -> Self {
+        let values = values.into();
+        assert_eq!(values.len(), self.labels.len(), "one value per vertex");
+        self.columns.push((name.to_string(), values));
+        self
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::Writer::from_writer(writer);
+        let mut header = vec!["vertex"];
+        header.extend(self.columns.iter().map(|(name, _)| name.as_str()));
+        writer.write_record(&header)?;
+        for (row, label) in self.labels.iter().enumerate() {
+            let mut record = vec![label.clone()];
+            record.extend(self.columns.iter().map(|(_, c)| c.csv_value(row)));
+            writer.write_record(&record)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    // Records orientation: one object per vertex
+    pub fn write_json<W: Write>(&self, mut writer: W) -> Result<(), Box<dyn Error>> {
+        let records: Vec<serde_json::Value> = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(row, label)| {
+                let mut record = serde_json::Map::new();
+                record.insert("vertex".to_string(), label.clone().into());
+                for (name, column) in &self.columns {
+                    record.insert(name.clone(), column.json_value(row));
+                }
+                serde_json::Value::Object(record)
+            })
+            .collect();
+        serde_json::to_writer_pretty(&mut writer, &records)?;
+        writeln!(writer)?;
+        Ok(())
+    }
+
+    pub fn write_parquet(&self, file: File) -> Result<(), Box<dyn Error>> {
+        let mut fields = vec!["REQUIRED BYTE_ARRAY vertex (UTF8);".to_string()];
+        for (name, column) in &self.columns {
+            let physical = match column {
+                Column::Int(_) => "INT64",
+                Column::Float(_) => "DOUBLE",
+            };
+            fields.push(format!("REQUIRED {} {};", physical, name));
+        }
+        let schema = Arc::new(parse_message_type(&format!(
+            "message vertices {{ {} }}",
+            fields.join(" ")
+        ))?);
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(file, schema, props)?;
+
+        let mut row_group = writer.next_row_group()?;
+        let labels: Vec<ByteArray> = self.labels.iter().map(|l| l.as_str().into()).collect();
+        let mut column_writer = row_group.next_column()?.ok_or("missing vertex column")?;
+        column_writer
+            .typed::<parquet::data_type::ByteArrayType>()
+            .write_batch(&labels, None, None)?;
+        column_writer.close()?;
+        for (name, column) in &self.columns {
+            let mut column_writer = row_group
+                .next_column()?
+                .ok_or_else(|| format!("missing column {}", name))?;
+            match column {
+                Column::Int(values) => {
+                    column_writer
+                        .typed::<parquet::data_type::Int64Type>()
+                        .write_batch(values, None, None)?;
+                }
+                Column::Float(values) => {
+                    column_writer
+                        .typed::<parquet::data_type::DoubleType>()
+                        .write_batch(values, None, None)?;
+                }
+            }
+            column_writer.close()?;
+        }
+        row_group.close()?;
+        writer.close()?;
+        Ok(())
+    }
+
+    // Write to `path`, or to stdout when no path is given
+    pub fn write(&self, path: Option<&str>, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+        match (path, format) {
+            (None, OutputFormat::Parquet) => Err("parquet output needs an output file".into()),
+            (None, OutputFormat::Csv) => self.write_csv(std::io::stdout().lock()),
+            (None, OutputFormat::Json) => self.write_json(std::io::stdout().lock()),
+            (Some(path), OutputFormat::Csv) => self.write_csv(File::create(path)?),
+            (Some(path), OutputFormat::Json) => self.write_json(File::create(path)?),
+            (Some(path), OutputFormat::Parquet) => self.write_parquet(File::create(path)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OutputFormat, VertexTable};
+    use crate::graph::Graph;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::RowAccessor;
+    use std::fs::File;
+
+    fn table() -> VertexTable {
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2)], 3).with_labels(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+        ]);
+        VertexTable::new(&graph)
+            .column("degree", graph.degree())
+            .column("score", vec![0.5, f64::NAN, 2.0])
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(OutputFormat::from_path("out.CSV"), Some(OutputFormat::Csv));
+        assert_eq!(
+            OutputFormat::from_path("a.b.json"),
+            Some(OutputFormat::Json)
+        );
+        assert_eq!(OutputFormat::from_path("x.pq"), Some(OutputFormat::Parquet));
+        assert_eq!(OutputFormat::from_path("x.txt"), None);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_vertex() {
+        let mut out = Vec::new();
+        table().write_csv(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "vertex,degree,score\na,1,0.5\nb,2,NaN\nc,1,2\n"
+        );
+    }
+
+    #[test]
+    fn json_writes_nan_as_null() {
+        let mut out = Vec::new();
+        table().write_json(&mut out).unwrap();
+        let records: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            records,
+            serde_json::json!([
+                {"vertex": "a", "degree": 1, "score": 0.5},
+                {"vertex": "b", "degree": 2, "score": null},
+                {"vertex": "c", "degree": 1, "score": 2.0},
+            ])
+        );
+    }
+
+    #[test]
+    fn parquet_round_trip() {
+        let path = std::env::temp_dir().join(format!("mygraph-{}.parquet", std::process::id()));
+        table().write_parquet(File::create(&path).unwrap()).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 3);
+        let rows: Vec<_> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rows[1].get_string(0).unwrap(), "b");
+        assert_eq!(rows[1].get_long(1).unwrap(), 2);
+        assert!(rows[1].get_double(2).unwrap().is_nan());
+        assert_eq!(rows[2].get_double(2).unwrap(), 2.0);
+    }
+}
@@ -0,0 +1,799 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Residual capacities at or below this count as saturated
+const EPSILON: f64 = 1e-12;
+
+// Capacitated network for maximum flows. Arcs come in pairs: arc `a ^ 1` is the
+// reverse of arc `a` and carries the opposite flow.
+#[derive(Clone, Debug)]
+pub struct FlowNetwork {
+    pub vertices: usize,
+    out: Vec<Vec<usize>>,
+    head: Vec<usize>,
+    capacity: Vec<f64>,
+    flow: Vec<f64>,
+}
+
+// An edge cut: its total capacity, the side of every vertex (true with the source,
+// or with the vertex the cut was grown from) and the edges crossing it
+#[derive(Clone, Debug)]
+pub struct MinCut {
+    pub value: f64,
+    pub source_side: Vec<bool>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl FlowNetwork {
+    pub fn new(vertices: usize) -> Self {
+        FlowNetwork {
+            vertices,
+            out: vec![Vec::new(); vertices],
+            head: Vec::new(),
+            capacity: Vec::new(),
+            flow: Vec::new(),
+        }
+    }
+
+    fn push_pair(&mut self, src: usize, dest: usize, forward: f64, backward: f64) -> usize {
+        let arc = self.head.len();
+        self.out[src].push(arc);
+        self.head.push(dest);
+        self.capacity.push(forward);
+        self.flow.push(0.0);
+        self.out[dest].push(arc + 1);
+        self.head.push(src);
+        self.capacity.push(backward);
+        self.flow.push(0.0);
+        arc
+    }
+
+    // Add an arc from `src` to `dest` and return its index
+    pub fn add_arc(&mut self, src: usize, dest: usize, capacity: f64) -> usize {
+        self.push_pair(src, dest, capacity, 0.0)
+    }
+
+    // Add an undirected edge with the same capacity both ways and return its index
+    pub fn add_edge(&mut self, u: usize, w: usize, capacity: f64) -> usize {
+        self.push_pair(u, w, capacity, capacity)
+    }
+
+    // Flow on an arc after `max_flow`, negative if it runs against the arc
+    pub fn flow(&self, arc: usize) -> f64 {
+        self.flow[arc]
+    }
+
+    fn residual(&self, arc: usize) -> f64 {
+        self.capacity[arc] - self.flow[arc]
+    }
+
+    // Distances from `source` in the residual network, usize::MAX if unreachable
+    fn levels(&self, source: usize) -> Vec<usize> {
+        let mut level = vec![usize::MAX; self.vertices];
+        let mut queue = VecDeque::from([source]);
+        level[source] = 0;
+        while let Some(v) = queue.pop_front() {
+            for &arc in &self.out[v] {
+                let w = self.head[arc];
+                if level[w] == usize::MAX && self.residual(arc) > EPSILON {
+                    level[w] = level[v] + 1;
+                    queue.push_back(w);
+                }
+            }
+        }
+        level
+    }
+
+    // Blocking flow along shortest residual paths, iterative to keep long paths off
+    // the call stack
+    fn blocking_flow(&mut self, source: usize, sink: usize, level: &[usize]) -> f64 {
+        let mut next = vec![0; self.vertices];
+        let mut path: Vec<usize> = Vec::new();
+        let mut total = 0.0;
+        let mut v = source;
+        loop {
+            if v == sink {
+                let push = path
+                    .iter()
+                    .map(|&arc| self.residual(arc))
+                    .fold(f64::INFINITY, f64::min);
+                for &arc in &path {
+                    self.flow[arc] += push;
+                    self.flow[arc ^ 1] -= push;
+                }
+                total += push;
+                // Retreat to the tail of the first saturated arc
+                let saturated = path
+                    .iter()
+                    .position(|&arc| self.residual(arc) <= EPSILON)
+                    .unwrap();
+                v = self.head[path[saturated] ^ 1];
+                path.truncate(saturated);
+                continue;
+            }
+
+            let mut advanced = false;
+            while next[v] < self.out[v].len() {
+                let arc = self.out[v][next[v]];
+                let w = self.head[arc];
+                if self.residual(arc) > EPSILON && level[w] == level[v] + 1 {
+                    path.push(arc);
+                    v = w;
+                    advanced = true;
+                    break;
+                }
+                next[v] += 1;
+            }
+            if !advanced {
+                // A dead end: drop the arc into it
+                let Some(arc) = path.pop() else { break };
+                v = self.head[arc ^ 1];
+                next[v] += 1;
+            }
+        }
+        total
+    }
+
+    // Value of a maximum flow from `source` to `sink` by Dinic's algorithm; the
+    // flows on the arcs are kept for `flow` and `source_side`
+    pub fn max_flow(&mut self, source: usize, sink: usize) -> f64 {
+        self.flow.iter_mut().for_each(|f| *f = 0.0);
+        if source == sink {
+            return 0.0;
+        }
+        let mut total = 0.0;
+        loop {
+            let level = self.levels(source);
+            if level[sink] == usize::MAX {
+                return total;
+            }
+            total += self.blocking_flow(source, sink, &level);
+        }
+    }
+
+    // Vertices reachable from `source` in the residual network; after `max_flow` the
+    // source side of a minimum cut
+    pub fn source_side(&self, source: usize) -> Vec<bool> {
+        self.levels(source)
+            .iter()
+            .map(|&l| l != usize::MAX)
+            .collect()
+    }
+}
+
+// Vertex of a contracted graph keyed by its attachment to the growing set, ordered
+// for a max-heap
+struct Attachment {
+    weight: f64,
+    vertex: usize,
+}
+
+impl PartialEq for Attachment {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Attachment {}
+
+impl PartialOrd for Attachment {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Attachment {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.weight
+            .total_cmp(&other.weight)
+            .then(other.vertex.cmp(&self.vertex))
+    }
+}
+
+impl Graph {
+    // Network with an arc per arc, or an edge per edge, of the graph; the capacities
+    // are the edge weights, 1 if the graph is unweighted
+    pub fn flow_network(&self) -> FlowNetwork {
+        let mut network = FlowNetwork::new(self.vertices);
+        for (u, w, weight) in self.weighted_edges() {
+            if u == w {
+                continue;
+            }
+            if self.is_directed() {
+                network.add_arc(u, w, weight);
+            } else {
+                network.add_edge(u, w, weight);
+            }
+        }
+        network
+    }
+
+    // Same as `flow_network` with capacity 1 for every edge, parallel edges adding up
+    fn unit_flow_network(&self) -> FlowNetwork {
+        let mut network = FlowNetwork::new(self.vertices);
+        for (u, w) in self.edges() {
+            if u == w {
+                continue;
+            }
+            if self.is_directed() {
+                network.add_arc(u, w, 1.0);
+            } else {
+                network.add_edge(u, w, 1.0);
+            }
+        }
+        network
+    }
+
+    // Network with every vertex v split into 2v -> 2v + 1 of capacity 1; edges run from
+    // the second half of one vertex to the first half of the other with capacity n
+    fn split_flow_network(&self) -> FlowNetwork {
+        let mut network = FlowNetwork::new(2 * self.vertices);
+        let unbounded = self.vertices as f64;
+        for v in 0..self.vertices {
+            network.add_arc(2 * v, 2 * v + 1, 1.0);
+        }
+        for (u, w) in self.edges() {
+            if u == w {
+                continue;
+            }
+            network.add_arc(2 * u + 1, 2 * w, unbounded);
+            if !self.is_directed() {
+                network.add_arc(2 * w + 1, 2 * u, unbounded);
+            }
+        }
+        network
+    }
+
+    // Value of a maximum flow from `source` to `sink`, with the edge weights as capacities
+    pub fn max_flow(&self, source: usize, sink: usize) -> f64 {
+        self.flow_network().max_flow(source, sink)
+    }
+
+    // Minimum cut separating `source` from `sink`, with the edge weights as capacities.
+    // The edges are those leaving the source side, in the order of `edges()`.
+    pub fn minimum_cut(&self, source: usize, sink: usize) -> MinCut {
+        let mut network = self.flow_network();
+        let value = network.max_flow(source, sink);
+        let source_side = network.source_side(source);
+        MinCut {
+            value,
+            edges: self.cut_edges(&source_side),
+            source_side,
+        }
+    }
+
+    fn cut_edges(&self, side: &[bool]) -> Vec<(usize, usize)> {
+        self.edges()
+            .into_iter()
+            .filter(|&(u, w)| {
+                if self.is_directed() {
+                    side[u] && !side[w]
+                } else {
+                    side[u] != side[w]
+                }
+            })
+            .collect()
+    }
+
+    // Minimum cut of an undirected graph over all vertex pairs by Stoer and Wagner's
+    // algorithm, with the edge weights as capacities. The source side holds the
+    // vertices merged into the last vertex of the best phase.
+    pub fn global_minimum_cut(&self) -> MinCut {
+        assert!(
+            !self.is_directed(),
+            "global minimum cut of a directed graph"
+        );
+        let n = self.vertices;
+        let mut adjacency: Vec<HashMap<usize, f64>> = vec![HashMap::new(); n];
+        for (u, w, weight) in self.weighted_edges() {
+            if u != w {
+                *adjacency[u].entry(w).or_default() += weight;
+                *adjacency[w].entry(u).or_default() += weight;
+            }
+        }
+        let mut members: Vec<Vec<usize>> = (0..n).map(|v| vec![v]).collect();
+        let mut active = vec![true; n];
+        let mut best = (f64::INFINITY, Vec::new());
+
+        for _ in 1..n {
+            // Maximum adjacency order: add the vertex most tightly tied to the set
+            let mut attachment = vec![0.0; n];
+            let mut added = vec![false; n];
+            let mut heap: BinaryHeap<Attachment> = (0..n)
+                .filter(|&v| active[v])
+                .map(|vertex| Attachment {
+                    weight: 0.0,
+                    vertex,
+                })
+                .collect();
+            let (mut previous, mut last) = (usize::MAX, usize::MAX);
+            while let Some(Attachment { weight, vertex: v }) = heap.pop() {
+                if added[v] || weight != attachment[v] {
+                    continue;
+                }
+                added[v] = true;
+                previous = last;
+                last = v;
+                for (&u, &w) in &adjacency[v] {
+                    if !added[u] {
+                        attachment[u] += w;
+                        heap.push(Attachment {
+                            weight: attachment[u],
+                            vertex: u,
+                        });
+                    }
+                }
+            }
+
+            if attachment[last] < best.0 {
+                best = (attachment[last], members[last].clone());
+            }
+
+            // Merge the last vertex into the one added before it
+            let edges = std::mem::take(&mut adjacency[last]);
+            for (u, w) in edges {
+                adjacency[u].remove(&last);
+                if u != previous {
+                    *adjacency[previous].entry(u).or_default() += w;
+                    *adjacency[u].entry(previous).or_default() += w;
+                }
+            }
+            active[last] = false;
+            let moved = std::mem::take(&mut members[last]);
+            members[previous].extend(moved);
+        }
+
+        let mut source_side = vec![false; n];
+        for v in best.1 {
+            source_side[v] = true;
+        }
+        MinCut {
+            value: if n < 2 { 0.0 } else { best.0 },
+            edges: self.cut_edges(&source_side),
+            source_side,
+        }
+    }
+
+    // Most edge-disjoint paths from `source` to `sink`, counting parallel edges
+    pub fn local_edge_connectivity(&self, source: usize, sink: usize) -> usize {
+        self.unit_flow_network().max_flow(source, sink).round() as usize
+    }
+
+    // Most internally vertex-disjoint paths from `source` to `sink`; None if they are
+    // the same vertex or adjacent, when no vertex set separates them
+    pub fn local_vertex_connectivity(&self, source: usize, sink: usize) -> Option<usize> {
+        if source == sink || self.has_edge(source, sink) {
+            return None;
+        }
+        let flow = self.split_flow_network().max_flow(2 * source + 1, 2 * sink);
+        Some(flow.round() as usize)
+    }
+
+    // Fewest edges whose removal disconnects the graph (strongly, if directed): the
+    // smallest local edge connectivity between vertex 0 and any other vertex, both
+    // ways if directed. Flows run in parallel.
+    pub fn edge_connectivity(&self) -> usize {
+        if self.vertices < 2 {
+            return 0;
+        }
+        let network = self.unit_flow_network();
+        (1..self.vertices)
+            .into_par_iter()
+            .map(|v| {
+                let mut network = network.clone();
+                let forward = network.max_flow(0, v);
+                let flow = if self.is_directed() {
+                    forward.min(network.max_flow(v, 0))
+                } else {
+                    forward
+                };
+                flow.round() as usize
+            })
+            .min()
+            .unwrap()
+    }
+
+    // Fewest vertices whose removal disconnects the graph (strongly, if directed), or
+    // n - 1 if no vertex set does. Undirected graphs need only the pairs of
+    // Esfahanian and Hakimi's algorithm: a vertex v of minimum degree with each of
+    // its non-neighbours, and non-adjacent pairs of v's neighbours. Directed graphs
+    // check every ordered pair without an arc. Flows run in parallel.
+    pub fn vertex_connectivity(&self) -> usize {
+        let n = self.vertices;
+        if n < 2 {
+            return 0;
+        }
+        let adj = simple_adjacency(self);
+        let pairs: Vec<(usize, usize)> = if self.is_directed() {
+            (0..n)
+                .flat_map(|s| (0..n).map(move |t| (s, t)))
+                .filter(|&(s, t)| s != t && !self.has_edge(s, t))
+                .collect()
+        } else {
+            let v = (0..n).min_by_key(|&v| (adj[v].len(), v)).unwrap();
+            let mut pairs: Vec<(usize, usize)> = (0..n)
+                .filter(|&w| w != v && adj[v].binary_search(&w).is_err())
+                .map(|w| (v, w))
+                .collect();
+            for (i, &x) in adj[v].iter().enumerate() {
+                for &y in &adj[v][i + 1..] {
+                    if adj[x].binary_search(&y).is_err() {
+                        pairs.push((x, y));
+                    }
+                }
+            }
+            pairs
+        };
+
+        let network = self.split_flow_network();
+        pairs
+            .par_iter()
+            .map(|&(s, t)| {
+                let mut network = network.clone();
+                network.max_flow(2 * s + 1, 2 * t).round() as usize
+            })
+            .min()
+            .unwrap_or(n - 1)
+    }
+
+    // Articulation points and bridges of the underlying undirected graph by Tarjan's
+    // depth-first search, iterative so deep graphs do not overflow the stack.
+    // Parallel edges are never bridges; self-loops are ignored. Vertices ascending,
+    // bridges as (u, v) with u < v in ascending order.
+    pub fn articulation_points_and_bridges(&self) -> (Vec<usize>, Vec<(usize, usize)>) {
+        let n = self.vertices;
+        let neighbors: Vec<Vec<usize>> = (0..n)
+            .map(|v| {
+                let mut list = self.successors(v).to_vec();
+                if self.is_directed() {
+                    list.extend_from_slice(self.predecessors(v));
+                }
+                list.retain(|&w| w != v);
+                list
+            })
+            .collect();
+
+        let mut discovery = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut is_articulation = vec![false; n];
+        let mut bridges = Vec::new();
+        let mut timer = 0;
+        // Vertex, its parent, whether one edge to the parent was skipped, next neighbour
+        let mut stack: Vec<(usize, usize, bool, usize)> = Vec::new();
+        for root in 0..n {
+            if discovery[root] != usize::MAX {
+                continue;
+            }
+            discovery[root] = timer;
+            low[root] = timer;
+            timer += 1;
+            let mut root_children = 0;
+            stack.push((root, usize::MAX, false, 0));
+            while let Some(frame) = stack.last_mut() {
+                let (v, parent) = (frame.0, frame.1);
+                if frame.3 < neighbors[v].len() {
+                    let w = neighbors[v][frame.3];
+                    frame.3 += 1;
+                    // The tree edge back to the parent, once; a parallel edge counts
+                    if w == parent && !frame.2 {
+                        frame.2 = true;
+                        continue;
+                    }
+                    if discovery[w] == usize::MAX {
+                        discovery[w] = timer;
+                        low[w] = timer;
+                        timer += 1;
+                        stack.push((w, v, false, 0));
+                    } else {
+                        low[v] = low[v].min(discovery[w]);
+                    }
+                    continue;
+                }
+                stack.pop();
+                if parent == usize::MAX {
+                    continue;
+                }
+                low[parent] = low[parent].min(low[v]);
+                if low[v] > discovery[parent] {
+                    bridges.push((parent.min(v), parent.max(v)));
+                }
+                if parent == root {
+                    root_children += 1;
+                } else if low[v] >= discovery[parent] {
+                    is_articulation[parent] = true;
+                }
+            }
+            if root_children >= 2 {
+                is_articulation[root] = true;
+            }
+        }
+
+        bridges.sort_unstable();
+        let points = (0..n).filter(|&v| is_articulation[v]).collect();
+        (points, bridges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FlowNetwork;
+    use crate::graph::{Graph, GraphPolicy};
+    use crate::testutil::{self, random_edges};
+    use rand::rngs::StdRng;
+    use rand::Rng;
+
+    // Random multigraph with loops and small integer capacities
+    fn random_graph(rng: &mut StdRng, directed: bool) -> Graph {
+        let n = rng.random_range(2..8);
+        let m = rng.random_range(0..3 * n);
+        let edges: Vec<(usize, usize, f64)> = random_edges(rng, n, m)
+            .into_iter()
+            .map(|(u, w)| (u, w, rng.random_range(1..5) as f64))
+            .collect();
+        if directed {
+            Graph::from_weighted_arclist_with_policy(edges, n, GraphPolicy::Multigraph)
+        } else {
+            Graph::from_weighted_edgelist_with_policy(edges, n, GraphPolicy::Multigraph)
+        }
+    }
+
+    // Capacity of the edges leaving the vertex set `side`, or crossing it if undirected
+    fn cut_value(graph: &Graph, side: u32, unit: bool) -> f64 {
+        let inside = |v: usize| side >> v & 1 == 1;
+        graph
+            .weighted_edges()
+            .into_iter()
+            .filter(|&(u, w, _)| {
+                if graph.is_directed() {
+                    inside(u) && !inside(w)
+                } else {
+                    inside(u) != inside(w)
+                }
+            })
+            .map(|(_, _, weight)| if unit { 1.0 } else { weight })
+            .sum()
+    }
+
+    // Smallest cut over the vertex sets with `source` in and `sink` out
+    fn brute_force_cut(graph: &Graph, source: usize, sink: usize, unit: bool) -> f64 {
+        (0..1u32 << graph.vertices)
+            .filter(|&side| side >> source & 1 == 1 && side >> sink & 1 == 0)
+            .map(|side| cut_value(graph, side, unit))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    // Whether the vertices outside `removed` are (strongly) connected
+    fn connected_without(graph: &Graph, removed: u32) -> bool {
+        let n = graph.vertices;
+        let kept: Vec<usize> = (0..n).filter(|&v| removed >> v & 1 == 0).collect();
+        let reaches_all = |forward: bool| {
+            let mut seen = vec![false; n];
+            let mut stack = vec![kept[0]];
+            seen[kept[0]] = true;
+            while let Some(v) = stack.pop() {
+                let next = if forward {
+                    graph.successors(v)
+                } else {
+                    graph.predecessors(v)
+                };
+                for &w in next {
+                    if !seen[w] && removed >> w & 1 == 0 {
+                        seen[w] = true;
+                        stack.push(w);
+                    }
+                }
+            }
+            kept.iter().all(|&v| seen[v])
+        };
+        kept.len() < 2 || reaches_all(true) && (!graph.is_directed() || reaches_all(false))
+    }
+
+    // Weak components of the edge list without vertex `skip`
+    fn components(n: usize, edges: &[(usize, usize)], skip: Option<usize>) -> usize {
+        let mut parent: Vec<usize> = (0..n).collect();
+        fn find(parent: &mut [usize], v: usize) -> usize {
+            if parent[v] != v {
+                parent[v] = find(parent, parent[v]);
+            }
+            parent[v]
+        }
+        for &(u, w) in edges {
+            if Some(u) != skip && Some(w) != skip {
+                let (a, b) = (find(&mut parent, u), find(&mut parent, w));
+                parent[a] = b;
+            }
+        }
+        (0..n)
+            .filter(|&v| Some(v) != skip && find(&mut parent, v) == v)
+            .count()
+    }
+
+    #[test]
+    fn dinic_matches_brute_force_cuts() {
+        let mut rng = testutil::rng();
+        for directed in [false, true] {
+            for _ in 0..40 {
+                let graph = random_graph(&mut rng, directed);
+                let n = graph.vertices;
+                let (source, sink) = (rng.random_range(0..n), rng.random_range(0..n));
+                if source == sink {
+                    continue;
+                }
+                let expected = brute_force_cut(&graph, source, sink, false);
+                assert_eq!(graph.max_flow(source, sink), expected);
+
+                let cut = graph.minimum_cut(source, sink);
+                assert_eq!(cut.value, expected);
+                assert!(cut.source_side[source] && !cut.source_side[sink]);
+                let side = (0..n)
+                    .filter(|&v| cut.source_side[v])
+                    .fold(0, |side, v| side | 1 << v);
+                assert_eq!(cut_value(&graph, side, false), expected);
+                assert_eq!(cut.edges, graph.cut_edges(&cut.source_side));
+
+                assert_eq!(
+                    graph.local_edge_connectivity(source, sink) as f64,
+                    brute_force_cut(&graph, source, sink, true)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn flows_respect_capacities_and_conservation() {
+        let mut rng = testutil::rng();
+        for _ in 0..40 {
+            let n = rng.random_range(2..10);
+            let mut network = FlowNetwork::new(n);
+            let arcs: Vec<(usize, usize, usize, f64, bool)> = (0..rng.random_range(0..4 * n))
+                .map(|_| {
+                    let (u, w) = (rng.random_range(0..n), rng.random_range(0..n));
+                    let capacity = rng.random::<f64>();
+                    let undirected = rng.random_bool(0.5);
+                    let arc = if undirected {
+                        network.add_edge(u, w, capacity)
+                    } else {
+                        network.add_arc(u, w, capacity)
+                    };
+                    (arc, u, w, capacity, undirected)
+                })
+                .collect();
+            let value = network.max_flow(0, n - 1);
+            let mut excess = vec![0.0; n];
+            for &(arc, u, w, capacity, undirected) in &arcs {
+                let flow = network.flow(arc);
+                assert!(flow <= capacity + 1e-9);
+                assert!(flow >= if undirected { -capacity - 1e-9 } else { -1e-9 });
+                excess[u] -= flow;
+                excess[w] += flow;
+            }
+            for (v, &e) in excess.iter().enumerate() {
+                let expected = match v {
+                    0 => -value,
+                    v if v == n - 1 => value,
+                    _ => 0.0,
+                };
+                assert!((e - expected).abs() < 1e-9);
+            }
+            // The reachable side is a cut of the flow's value
+            let side = network.source_side(0);
+            assert!(!side[n - 1]);
+            let crossing: f64 = arcs
+                .iter()
+                .map(|&(_, u, w, capacity, undirected)| {
+                    if side[u] && !side[w] || undirected && side[w] && !side[u] {
+                        capacity
+                    } else {
+                        0.0
+                    }
+                })
+                .sum();
+            assert!((crossing - value).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn stoer_wagner_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..60 {
+            let graph = random_graph(&mut rng, false);
+            let n = graph.vertices;
+            let expected = (1..(1u32 << n) - 1)
+                .map(|side| cut_value(&graph, side, false))
+                .fold(f64::INFINITY, f64::min);
+            let cut = graph.global_minimum_cut();
+            assert_eq!(cut.value, expected);
+            let count = cut.source_side.iter().filter(|&&s| s).count();
+            assert!(count > 0 && count < n);
+            let side = (0..n)
+                .filter(|&v| cut.source_side[v])
+                .fold(0, |side, v| side | 1 << v);
+            assert_eq!(cut_value(&graph, side, false), expected);
+
+            assert_eq!(
+                graph.edge_connectivity() as f64,
+                (1..(1u32 << n) - 1)
+                    .map(|side| cut_value(&graph, side, true))
+                    .fold(f64::INFINITY, f64::min)
+            );
+        }
+        assert_eq!(Graph::new(1).global_minimum_cut().value, 0.0);
+    }
+
+    #[test]
+    fn vertex_connectivity_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for directed in [false, true] {
+            for _ in 0..40 {
+                let graph = random_graph(&mut rng, directed);
+                let n = graph.vertices;
+                let expected = (0..1u32 << n)
+                    .filter(|&removed| !connected_without(&graph, removed))
+                    .map(|removed| removed.count_ones() as usize)
+                    .min()
+                    .unwrap_or(n - 1);
+                assert_eq!(graph.vertex_connectivity(), expected);
+
+                let (source, sink) = (rng.random_range(0..n), rng.random_range(0..n));
+                let local = graph.local_vertex_connectivity(source, sink);
+                if source == sink || graph.has_edge(source, sink) {
+                    assert_eq!(local, None);
+                    continue;
+                }
+                // Fewest other vertices whose removal leaves no path source -> sink
+                let separated = |removed: u32| {
+                    let mut seen = vec![false; n];
+                    let mut stack = vec![source];
+                    seen[source] = true;
+                    while let Some(v) = stack.pop() {
+                        for &w in graph.successors(v) {
+                            if !seen[w] && removed >> w & 1 == 0 {
+                                seen[w] = true;
+                                stack.push(w);
+                            }
+                        }
+                    }
+                    !seen[sink]
+                };
+                let expected = (0..1u32 << n)
+                    .filter(|&removed| removed >> source & 1 == 0 && removed >> sink & 1 == 0)
+                    .filter(|&removed| separated(removed))
+                    .map(|removed| removed.count_ones() as usize)
+                    .min();
+                assert_eq!(local, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn articulation_points_and_bridges_match_brute_force() {
+        let mut rng = testutil::rng();
+        for directed in [false, true] {
+            for _ in 0..60 {
+                let graph = random_graph(&mut rng, directed);
+                let n = graph.vertices;
+                let edges: Vec<(usize, usize)> =
+                    graph.edges().into_iter().filter(|&(u, w)| u != w).collect();
+                let base = components(n, &edges, None);
+                let points: Vec<usize> = (0..n)
+                    .filter(|&v| components(n, &edges, Some(v)) > base)
+                    .collect();
+                let mut bridges: Vec<(usize, usize)> = (0..edges.len())
+                    .filter(|&i| {
+                        let mut rest = edges.clone();
+                        rest.remove(i);
+                        components(n, &rest, None) > base
+                    })
+                    .map(|i| (edges[i].0.min(edges[i].1), edges[i].0.max(edges[i].1)))
+                    .collect();
+                bridges.sort_unstable();
+                assert_eq!(graph.articulation_points_and_bridges(), (points, bridges));
+            }
+        }
+    }
+}
@@ -2,34 +2,200 @@ extern crate nalgebra as na;
 extern crate nalgebra_sparse as na_sparse;
 use na_sparse::{coo::CooMatrix, csr::CsrMatrix};
 
+extern crate rayon;
+use rayon::prelude::*;
+
+// Which edges a graph accepts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphPolicy {
+    // No self-loops and no parallel edges
+    #[default]
+    Simple,
+    // Self-loops allowed, but no parallel edges
+    Loops,
+    // Self-loops and parallel edges allowed
+    Multigraph,
+}
+
 pub struct Graph {
+    // Neighbours of every vertex; the successors if the graph is directed
     pub adj_list: Vec<Vec<usize>>,
     pub vertices: usize,
+    pub labels: Option<Vec<String>>,
+    pub policy: GraphPolicy,
+    // Predecessors of every vertex, None if the graph is undirected
+    pub in_list: Option<Vec<Vec<usize>>>,
+    // Weight of every entry of `adj_list`, None if the graph is unweighted. Both
+    // entries of an undirected edge carry its weight. Bipartite one-mode projections
+    // store their tie strengths here. The backbone methods read them, and the flow
+    // and minimum cut methods use them as capacities; every other algorithm treats
+    // the graph as unweighted.
+    pub weights: Option<Vec<Vec<f64>>>,
 }
 
 // Constructors
 impl Graph {
-    // Initialize a new graph with a given number of vertices
+    // Initialize a new simple graph with a given number of vertices
     pub fn new(vertices: usize) -> Self {
+        Graph::with_policy(vertices, GraphPolicy::Simple)
+    }
+
+    // Initialize a new graph that accepts edges according to `policy`
+    pub fn with_policy(vertices: usize, policy: GraphPolicy) -> Self {
         let adj_list = vec![Vec::new(); vertices];
-        Graph { adj_list, vertices }
+        Graph {
+            adj_list,
+            vertices,
+            labels: None,
+            policy,
+            in_list: None,
+            weights: None,
+        }
+    }
+
+    // Initialize a new directed graph without self-loops and parallel arcs
+    pub fn new_directed(vertices: usize) -> Self {
+        Graph::directed_with_policy(vertices, GraphPolicy::Simple)
+    }
+
+    // Initialize a new directed graph that accepts arcs according to `policy`.
+    // Edges are arcs from `src` to `des`; `u -> v` and `v -> u` are not parallel.
+    pub fn directed_with_policy(vertices: usize, policy: GraphPolicy) -> Self {
+        let mut graph = Graph::with_policy(vertices, policy);
+        graph.in_list = Some(vec![Vec::new(); vertices]);
+        graph
+    }
+
+    // Attach vertex labels, e.g. the names used in an input file
+    pub fn with_labels(mut self, labels: Vec<String>) -> Self {
+        assert_eq!(labels.len(), self.vertices, "one label per vertex");
+        self.labels = Some(labels);
+        self
+    }
+
+    // Label of a vertex, falling back to its index
+    pub fn label(&self, v: usize) -> String {
+        match &self.labels {
+            Some(labels) => labels[v].clone(),
+            None => v.to_string(),
+        }
+    }
+
+    // Add an edge between two vertices, returns false if the policy rejects it.
+    // A self-loop is stored twice in its vertex's adjacency list, so it adds 2 to the degree.
+    // In a directed graph it is stored once in each of the vertex's two lists.
+    // In a weighted graph the edge gets weight 1.
+    pub fn add_edge(&mut self, src: usize, des: usize) -> bool {
+        self.add_weighted_edge(src, des, 1.0)
     }
 
-    // Add an edge between two vertices
-    pub fn add_edge(&mut self, src: usize, des: usize) {
+    // Add an edge with a weight, returns false if the policy rejects it. An unweighted
+    // graph becomes weighted unless the weight is 1. Like `add_edge`, and unlike
+    // `add_weighted_edges`, a parallel edge is rejected rather than merged into the
+    // existing one; add it with `add_weighted_edges` to sum the weights.
+    pub fn add_weighted_edge(&mut self, src: usize, des: usize, weight: f64) -> bool {
+        if src == des && self.policy == GraphPolicy::Simple {
+            return false;
+        }
+        if self.policy != GraphPolicy::Multigraph && self.adj_list[src].contains(&des) {
+            return false;
+        }
+        if weight != 1.0 {
+            self.make_weighted();
+        }
+        self.push_edge(src, des, weight);
+        true
+    }
+
+    // Store both entries of an edge, and its weight if the graph is weighted
+    fn push_edge(&mut self, src: usize, des: usize, weight: f64) {
         self.adj_list[src].push(des);
-        self.adj_list[des].push(src); // Because it's an undirected graph
+        if let Some(weights) = &mut self.weights {
+            weights[src].push(weight);
+        }
+        match &mut self.in_list {
+            Some(in_list) => in_list[des].push(src),
+            None => {
+                self.adj_list[des].push(src);
+                if let Some(weights) = &mut self.weights {
+                    weights[des].push(weight);
+                }
+            }
+        }
+    }
+
+    // Weight 1 for every edge of an unweighted graph
+    fn make_weighted(&mut self) {
+        if self.weights.is_none() {
+            self.weights = Some(self.adj_list.iter().map(|l| vec![1.0; l.len()]).collect());
+        }
+    }
+
+    // Add many edges at once, dropping the ones the policy rejects.
+    // Leaves every adjacency list sorted unless the graph is a multigraph.
+    pub fn add_edges<I: IntoIterator<Item = (usize, usize)>>(&mut self, edges: I) {
+        for (src, des) in edges {
+            self.push_edge(src, des, 1.0);
+        }
+        self.enforce_policy();
+    }
+
+    // Add many weighted edges at once, making the graph weighted. Unless the graph is
+    // a multigraph, parallel edges are merged into one with the sum of their weights.
+    pub fn add_weighted_edges<I: IntoIterator<Item = (usize, usize, f64)>>(&mut self, edges: I) {
+        self.make_weighted();
+        for (src, des, weight) in edges {
+            self.push_edge(src, des, weight);
+        }
+        self.enforce_policy();
+    }
+
+    // Remove self-loops and parallel edges, turning the graph into a simple graph
+    pub fn simplify(&mut self) {
+        self.policy = GraphPolicy::Simple;
+        self.enforce_policy();
+    }
+
+    // Sort and deduplicate the adjacency lists as the policy requires
+    fn enforce_policy(&mut self) {
+        let policy = self.policy;
+        if policy == GraphPolicy::Multigraph {
+            return;
+        }
+        // An undirected self-loop has two entries in the same list
+        let double_loops = !self.is_directed();
+        match &mut self.weights {
+            Some(weights) => {
+                dedup_weighted_lists(&mut self.adj_list, weights, policy, double_loops)
+            }
+            None => dedup_lists(&mut self.adj_list, policy, double_loops),
+        }
+        if let Some(in_list) = &mut self.in_list {
+            dedup_lists(in_list, policy, false);
+        }
     }
 
     // Graph from an existing adjacency list
     pub fn from_adjlist(adj_list: Vec<Vec<usize>>) -> Self {
+        Graph::from_adjlist_with_policy(adj_list, GraphPolicy::Simple)
+    }
+
+    // Graph from an existing adjacency list. If every entry `v` in the list of `u`
+    // is matched by an entry `u` in the list of `v` the input is taken as symmetric
+    // and each edge is added once, otherwise every entry is an edge of its own.
+    pub fn from_adjlist_with_policy(adj_list: Vec<Vec<usize>>, policy: GraphPolicy) -> Self {
         let vertices = adj_list.len();
-        let mut graph = Graph::new(vertices);
+        let mut graph = Graph::with_policy(vertices, policy);
 
-        for (src, neighbors) in adj_list.into_iter().enumerate() {
-            for dest in neighbors {
-                graph.add_edge(src, dest);
-            }
+        let arcs: Vec<(usize, usize)> = adj_list
+            .into_iter()
+            .enumerate()
+            .flat_map(|(src, neighbors)| neighbors.into_iter().map(move |dest| (src, dest)))
+            .collect();
+        if is_symmetric(&arcs) {
+            graph.add_edges(arcs.into_iter().filter(|&(src, dest)| src <= dest));
+        } else {
+            graph.add_edges(arcs);
         }
 
         graph
@@ -37,15 +203,308 @@ impl Graph {
 
     // Graph from an existing edgelist
     pub fn from_edgelist(edge_list: Vec<(usize, usize)>, vertices: usize) -> Self {
-        let mut graph = Graph::new(vertices);
+        Graph::from_edgelist_with_policy(edge_list, vertices, GraphPolicy::Simple)
+    }
 
-        for (src, dest) in edge_list {
-            graph.add_edge(src, dest);
-        }
+    // Graph from an existing edgelist, keeping the edges `policy` accepts
+    pub fn from_edgelist_with_policy(
+        edge_list: Vec<(usize, usize)>,
+        vertices: usize,
+        policy: GraphPolicy,
+    ) -> Self {
+        let mut graph = Graph::with_policy(vertices, policy);
+        graph.add_edges(edge_list);
+        graph
+    }
+
+    // Weighted graph from a list of (src, dest, weight) edges
+    pub fn from_weighted_edgelist(edge_list: Vec<(usize, usize, f64)>, vertices: usize) -> Self {
+        Graph::from_weighted_edgelist_with_policy(edge_list, vertices, GraphPolicy::Simple)
+    }
+
+    // Weighted graph from an edgelist, keeping the edges `policy` accepts; the
+    // weights of merged parallel edges add up
+    pub fn from_weighted_edgelist_with_policy(
+        edge_list: Vec<(usize, usize, f64)>,
+        vertices: usize,
+        policy: GraphPolicy,
+    ) -> Self {
+        let mut graph = Graph::with_policy(vertices, policy);
+        graph.add_weighted_edges(edge_list);
+        graph
+    }
+
+    // Directed graph from a list of (src, dest) arcs
+    pub fn from_arclist(arc_list: Vec<(usize, usize)>, vertices: usize) -> Self {
+        Graph::from_arclist_with_policy(arc_list, vertices, GraphPolicy::Simple)
+    }
+
+    // Directed graph from a list of (src, dest) arcs, keeping the arcs `policy` accepts
+    pub fn from_arclist_with_policy(
+        arc_list: Vec<(usize, usize)>,
+        vertices: usize,
+        policy: GraphPolicy,
+    ) -> Self {
+        let mut graph = Graph::directed_with_policy(vertices, policy);
+        graph.add_edges(arc_list);
+        graph
+    }
+
+    // Weighted directed graph from a list of (src, dest, weight) arcs
+    pub fn from_weighted_arclist(arc_list: Vec<(usize, usize, f64)>, vertices: usize) -> Self {
+        Graph::from_weighted_arclist_with_policy(arc_list, vertices, GraphPolicy::Simple)
+    }
 
+    // Weighted directed graph from a list of arcs, keeping the arcs `policy` accepts;
+    // the weights of merged parallel arcs add up
+    pub fn from_weighted_arclist_with_policy(
+        arc_list: Vec<(usize, usize, f64)>,
+        vertices: usize,
+        policy: GraphPolicy,
+    ) -> Self {
+        let mut graph = Graph::directed_with_policy(vertices, policy);
+        graph.add_weighted_edges(arc_list);
         graph
     }
+}
+
+// Queries
+impl Graph {
+    pub fn is_directed(&self) -> bool {
+        self.in_list.is_some()
+    }
+
+    pub fn is_weighted(&self) -> bool {
+        self.weights.is_some()
+    }
 
+    pub fn neighbors(&self, v: usize) -> &[usize] {
+        &self.adj_list[v]
+    }
+
+    // Neighbours (successors if directed) with the weights of the edges to them,
+    // 1 if the graph is unweighted
+    pub fn weighted_neighbors(&self, v: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.adj_list[v].iter().enumerate().map(move |(i, &w)| {
+            let weight = self.weights.as_ref().map_or(1.0, |weights| weights[v][i]);
+            (w, weight)
+        })
+    }
+
+    // Sum of the weights of every vertex's edges (arcs out of it if directed).
+    // A self-loop counts twice in an undirected graph, like in the degree.
+    pub fn strength(&self) -> Vec<f64> {
+        (0..self.vertices)
+            .map(|v| self.weighted_neighbors(v).map(|(_, weight)| weight).sum())
+            .collect()
+    }
+
+    // Vertices with an arc from `v`; the neighbours if the graph is undirected
+    pub fn successors(&self, v: usize) -> &[usize] {
+        &self.adj_list[v]
+    }
+
+    // Vertices with an arc to `v`; the neighbours if the graph is undirected
+    pub fn predecessors(&self, v: usize) -> &[usize] {
+        match &self.in_list {
+            Some(in_list) => &in_list[v],
+            None => &self.adj_list[v],
+        }
+    }
+
+    pub fn has_edge(&self, src: usize, des: usize) -> bool {
+        self.adj_list[src].contains(&des)
+    }
+
+    // Every edge once as (u, v) with u <= v, parallel edges repeated.
+    // In a directed graph every arc as (src, dest).
+    pub fn edges(&self) -> Vec<(usize, usize)> {
+        if self.is_directed() {
+            return self
+                .adj_list
+                .iter()
+                .enumerate()
+                .flat_map(|(u, neighbors)| neighbors.iter().map(move |&w| (u, w)))
+                .collect();
+        }
+        let mut edges = Vec::new();
+        for (u, neighbors) in self.adj_list.iter().enumerate() {
+            let mut loops = 0;
+            for &w in neighbors {
+                if u < w {
+                    edges.push((u, w));
+                } else if u == w {
+                    loops += 1;
+                }
+            }
+            // A self-loop is stored twice
+            edges.extend(std::iter::repeat_n((u, u), loops / 2));
+        }
+        edges
+    }
+
+    // Edges in the order of `edges()` with their weights, 1 if the graph is unweighted
+    pub fn weighted_edges(&self) -> Vec<(usize, usize, f64)> {
+        let mut edges = Vec::new();
+        for u in 0..self.vertices {
+            let mut loops = 0;
+            for (w, weight) in self.weighted_neighbors(u) {
+                if self.is_directed() || u < w {
+                    edges.push((u, w, weight));
+                } else if u == w {
+                    // Every second entry of a stored-twice self-loop, collected last
+                    loops += 1;
+                }
+            }
+            if loops > 0 {
+                edges.extend(
+                    self.weighted_neighbors(u)
+                        .filter(|&(w, _)| w == u)
+                        .step_by(2)
+                        .map(|(w, weight)| (u, w, weight)),
+                );
+            }
+        }
+        edges
+    }
+
+    // Number of edges, counting parallel edges and self-loops once each
+    pub fn edge_count(&self) -> usize {
+        let entries = self.adj_list.iter().map(|n| n.len()).sum::<usize>();
+        if self.is_directed() {
+            entries
+        } else {
+            entries / 2
+        }
+    }
+}
+
+// Mutation
+impl Graph {
+    // Add an isolated vertex and return its index
+    pub fn add_vertex(&mut self) -> usize {
+        let v = self.vertices;
+        self.adj_list.push(Vec::new());
+        if let Some(weights) = &mut self.weights {
+            weights.push(Vec::new());
+        }
+        if let Some(in_list) = &mut self.in_list {
+            in_list.push(Vec::new());
+        }
+        if let Some(labels) = &mut self.labels {
+            labels.push(v.to_string());
+        }
+        self.vertices += 1;
+        v
+    }
+
+    // Remove one edge between two vertices, returns false if there is none.
+    // Of a set of parallel edges only one is removed.
+    pub fn remove_edge(&mut self, src: usize, des: usize) -> bool {
+        let Some(pos) = self.adj_list[src].iter().position(|&w| w == des) else {
+            return false;
+        };
+        self.adj_list[src].remove(pos);
+        if let Some(weights) = &mut self.weights {
+            weights[src].remove(pos);
+        }
+        // The second entry of the edge; for a self-loop the second copy in the same list
+        let reverse = match &mut self.in_list {
+            Some(in_list) => &mut in_list[des],
+            None => &mut self.adj_list[des],
+        };
+        let pos = reverse
+            .iter()
+            .position(|&w| w == src)
+            .expect("adjacency lists are symmetric");
+        reverse.remove(pos);
+        if let (None, Some(weights)) = (&self.in_list, &mut self.weights) {
+            weights[des].remove(pos);
+        }
+        true
+    }
+
+    // Remove a vertex and all its edges. The last vertex takes over the index of
+    // the removed one (its label moves along), every other index stays the same.
+    pub fn remove_vertex(&mut self, v: usize) {
+        if let Some(in_list) = &mut self.in_list {
+            // Arcs out of v are listed at their heads, arcs into v at their tails
+            let successors = std::mem::take(&mut self.adj_list[v]);
+            let predecessors = std::mem::take(&mut in_list[v]);
+            if let Some(weights) = &mut self.weights {
+                weights[v].clear();
+            }
+            for &w in &successors {
+                in_list[w].retain(|&x| x != v);
+            }
+            for &w in &predecessors {
+                if let Some(weights) = &mut self.weights {
+                    let mut entries = self.adj_list[w].iter();
+                    weights[w].retain(|_| *entries.next().unwrap() != v);
+                }
+                self.adj_list[w].retain(|&x| x != v);
+            }
+        } else {
+            let neighbors = std::mem::take(&mut self.adj_list[v]);
+            for &w in &neighbors {
+                if w != v {
+                    let pos = self.adj_list[w]
+                        .iter()
+                        .position(|&x| x == v)
+                        .expect("adjacency lists are symmetric");
+                    self.adj_list[w].remove(pos);
+                    if let Some(weights) = &mut self.weights {
+                        weights[w].remove(pos);
+                    }
+                }
+            }
+        }
+
+        let last = self.vertices - 1;
+        self.adj_list.swap_remove(v);
+        if let Some(weights) = &mut self.weights {
+            weights.swap_remove(v);
+        }
+        if let Some(in_list) = &mut self.in_list {
+            in_list.swap_remove(v);
+        }
+        if let Some(labels) = &mut self.labels {
+            labels.swap_remove(v);
+        }
+        self.vertices -= 1;
+        if v == last {
+            return;
+        }
+
+        // Rename `last` to `v` wherever the moved vertex is referenced: in the lists
+        // of its neighbours, or of its predecessors and successors if directed
+        let rename = |lists: &mut [Vec<usize>], mut referencing: Vec<usize>| {
+            referencing.sort_unstable();
+            referencing.dedup();
+            for w in referencing {
+                let w = if w == last { v } else { w };
+                for x in lists[w].iter_mut() {
+                    if *x == last {
+                        *x = v;
+                    }
+                }
+            }
+        };
+        match &mut self.in_list {
+            Some(in_list) => {
+                rename(in_list, self.adj_list[v].clone());
+                rename(&mut self.adj_list, in_list[v].clone());
+            }
+            None => {
+                let neighbors = self.adj_list[v].clone();
+                rename(&mut self.adj_list, neighbors)
+            }
+        }
+    }
+}
+
+// Conversions
+impl Graph {
     // Convert the adjacency list to an adjacency matrix
     pub fn to_adjacency_matrix(&self) -> Vec<Vec<i32>> {
         let mut matrix = vec![vec![0; self.vertices]; self.vertices];
@@ -53,22 +512,349 @@ impl Graph {
         for (node, edges) in self.adj_list.iter().enumerate() {
             for &edge in edges {
                 matrix[node][edge] = 1;
-                matrix[edge][node] = 1; // undirected
+                if !self.is_directed() {
+                    matrix[edge][node] = 1;
+                }
             }
         }
 
         matrix
     }
 
+    // The adjacency lists are symmetric, so every entry fills one cell and
+    // parallel edges add up. A self-loop counts 2 on the diagonal.
+    // For a directed graph row u holds the arcs out of u.
     pub fn to_adjacency_matrix_sparse(&self) -> CsrMatrix<f64> {
         let mut coo = CooMatrix::new(self.vertices, self.vertices);
         for (node, edges) in self.adj_list.iter().enumerate() {
             for &edge in edges {
                 coo.push(node, edge, 1.0);
-                coo.push(edge, node, 1.0);
             }
         }
-        let csr = CsrMatrix::from(&coo);
-        csr
+        CsrMatrix::from(&coo)
+    }
+
+    // Degrees without self-loops, which do not enter the Laplacians
+    fn loopless_degree(&self) -> Vec<f64> {
+        self.adj_list
+            .iter()
+            .enumerate()
+            .map(|(node, edges)| edges.iter().filter(|&&w| w != node).count() as f64)
+            .collect()
+    }
+
+    // Combinatorial Laplacian L = D - A
+    pub fn laplacian(&self) -> CsrMatrix<f64> {
+        let degree = self.loopless_degree();
+        let mut coo = CooMatrix::new(self.vertices, self.vertices);
+        for (node, edges) in self.adj_list.iter().enumerate() {
+            coo.push(node, node, degree[node]);
+            for &edge in edges.iter().filter(|&&w| w != node) {
+                coo.push(node, edge, -1.0);
+            }
+        }
+        CsrMatrix::from(&coo)
+    }
+
+    // Symmetric normalised Laplacian I - D^-1/2 A D^-1/2; rows of isolated vertices are zero
+    pub fn normalized_laplacian(&self) -> CsrMatrix<f64> {
+        let degree = self.loopless_degree();
+        let mut coo = CooMatrix::new(self.vertices, self.vertices);
+        for (node, edges) in self.adj_list.iter().enumerate() {
+            if degree[node] == 0.0 {
+                continue;
+            }
+            coo.push(node, node, 1.0);
+            for &edge in edges.iter().filter(|&&w| w != node) {
+                coo.push(node, edge, -1.0 / (degree[node] * degree[edge]).sqrt());
+            }
+        }
+        CsrMatrix::from(&coo)
+    }
+
+    // Random-walk Laplacian I - D^-1 A; rows of isolated vertices are zero
+    pub fn random_walk_laplacian(&self) -> CsrMatrix<f64> {
+        let degree = self.loopless_degree();
+        let mut coo = CooMatrix::new(self.vertices, self.vertices);
+        for (node, edges) in self.adj_list.iter().enumerate() {
+            if degree[node] == 0.0 {
+                continue;
+            }
+            coo.push(node, node, 1.0);
+            for &edge in edges.iter().filter(|&&w| w != node) {
+                coo.push(node, edge, -1.0 / degree[node]);
+            }
+        }
+        CsrMatrix::from(&coo)
+    }
+}
+
+// Sort and deduplicate every list, dropping or keeping self-loops per the policy
+fn dedup_lists(lists: &mut [Vec<usize>], policy: GraphPolicy, double_loops: bool) {
+    lists
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(node, neighbors)| {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            if let Ok(pos) = neighbors.binary_search(&node) {
+                if policy == GraphPolicy::Simple {
+                    neighbors.remove(pos);
+                } else if double_loops {
+                    neighbors.insert(pos, node);
+                }
+            }
+        });
+}
+
+// Sort every list and its weights, merging parallel entries into one with the sum of
+// their weights, and dropping or keeping self-loops per the policy
+fn dedup_weighted_lists(
+    lists: &mut [Vec<usize>],
+    weights: &mut [Vec<f64>],
+    policy: GraphPolicy,
+    double_loops: bool,
+) {
+    lists
+        .par_iter_mut()
+        .zip(weights.par_iter_mut())
+        .enumerate()
+        .for_each(|(node, (neighbors, weights))| {
+            let mut entries: Vec<(usize, f64)> = neighbors
+                .iter()
+                .copied()
+                .zip(weights.iter().copied())
+                .collect();
+            // Stable, so merged weights add up in insertion order
+            entries.sort_by_key(|&(w, _)| w);
+            neighbors.clear();
+            weights.clear();
+            for (w, weight) in entries {
+                if neighbors.last() == Some(&w) {
+                    *weights.last_mut().unwrap() += weight;
+                } else {
+                    neighbors.push(w);
+                    weights.push(weight);
+                }
+            }
+            if let Ok(pos) = neighbors.binary_search(&node) {
+                if policy == GraphPolicy::Simple {
+                    neighbors.remove(pos);
+                    weights.remove(pos);
+                } else if double_loops {
+                    // Both entries of every self-loop were summed
+                    weights[pos] /= 2.0;
+                    neighbors.insert(pos, node);
+                    weights.insert(pos, weights[pos]);
+                }
+            }
+        });
+}
+
+// Every arc (u, v) has a matching (v, u), counting multiplicities
+fn is_symmetric(arcs: &[(usize, usize)]) -> bool {
+    let mut forward = arcs.to_vec();
+    let mut backward: Vec<(usize, usize)> = arcs.iter().map(|&(u, v)| (v, u)).collect();
+    forward.par_sort_unstable();
+    backward.par_sort_unstable();
+    forward == backward
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graph, GraphPolicy};
+    use crate::testutil;
+    use rand::Rng;
+
+    // Endpoints of every edge, sorted, with the ends of undirected edges ordered
+    fn sorted_edges(graph: &Graph) -> Vec<(usize, usize)> {
+        let mut edges = graph.edges();
+        edges.sort_unstable();
+        edges
+    }
+
+    // Lists and weights agree: every entry has a weight and is matched at its other
+    // end by an entry with the same weight
+    fn assert_consistent(graph: &Graph) {
+        assert_eq!(graph.adj_list.len(), graph.vertices);
+        let weights = graph.weights.as_ref().unwrap();
+        assert_eq!(weights.len(), graph.vertices);
+        for (weights, neighbors) in weights.iter().zip(&graph.adj_list) {
+            assert_eq!(weights.len(), neighbors.len());
+        }
+        let mut forward = Vec::new();
+        let mut backward = Vec::new();
+        for u in 0..graph.vertices {
+            for (w, weight) in graph.weighted_neighbors(u) {
+                forward.push((u, w, weight.to_bits()));
+            }
+            if let Some(in_list) = &graph.in_list {
+                backward.extend(in_list[u].iter().map(|&w| (w, u)));
+            }
+        }
+        if graph.is_directed() {
+            let mut arcs: Vec<(usize, usize)> = forward.iter().map(|&(u, w, _)| (u, w)).collect();
+            arcs.sort_unstable();
+            backward.sort_unstable();
+            assert_eq!(arcs, backward);
+        } else {
+            let mut reversed: Vec<_> = forward.iter().map(|&(u, w, x)| (w, u, x)).collect();
+            forward.sort_unstable();
+            reversed.sort_unstable();
+            assert_eq!(forward, reversed);
+        }
+    }
+
+    #[test]
+    fn random_mutations_keep_lists_consistent() {
+        let mut rng = testutil::rng();
+        for round in 0..100 {
+            let directed = round % 2 == 1;
+            let mut graph = if directed {
+                Graph::directed_with_policy(6, GraphPolicy::Multigraph)
+            } else {
+                Graph::with_policy(6, GraphPolicy::Multigraph)
+            };
+            graph.weights = Some(vec![Vec::new(); 6]);
+            // Reference edge list, undirected edges with their lower end first
+            let mut model: Vec<(usize, usize)> = Vec::new();
+            let order = |u: usize, w: usize| {
+                if directed {
+                    (u, w)
+                } else {
+                    (u.min(w), u.max(w))
+                }
+            };
+            for step in 0..60 {
+                let n = graph.vertices;
+                match rng.random_range(0..10) {
+                    0 => {
+                        graph.add_vertex();
+                    }
+                    1 if n > 1 => {
+                        let v = rng.random_range(0..n);
+                        graph.remove_vertex(v);
+                        let last = n - 1;
+                        let rename = |x: usize| if x == last { v } else { x };
+                        model.retain(|&(a, b)| a != v && b != v);
+                        for edge in model.iter_mut() {
+                            *edge = order(rename(edge.0), rename(edge.1));
+                        }
+                    }
+                    2..=4 if n > 0 => {
+                        let (u, w) = (rng.random_range(0..n), rng.random_range(0..n));
+                        let removed = graph.remove_edge(u, w);
+                        let pos = model.iter().position(|&e| e == order(u, w));
+                        assert_eq!(removed, pos.is_some());
+                        if let Some(pos) = pos {
+                            model.swap_remove(pos);
+                        }
+                    }
+                    _ if n > 0 => {
+                        let (u, w) = (rng.random_range(0..n), rng.random_range(0..n));
+                        assert!(graph.add_weighted_edge(u, w, step as f64 + 0.5));
+                        model.push(order(u, w));
+                    }
+                    _ => {}
+                }
+                assert_consistent(&graph);
+                model.sort_unstable();
+                assert_eq!(sorted_edges(&graph), model);
+            }
+        }
+    }
+
+    #[test]
+    fn removed_vertex_index_goes_to_the_last_vertex() {
+        let mut graph = Graph::from_edgelist(vec![(0, 1), (1, 3), (2, 3)], 4)
+            .with_labels(["a", "b", "c", "d"].iter().map(|s| s.to_string()).collect());
+        graph.remove_vertex(1);
+        assert_eq!(graph.vertices, 3);
+        assert_eq!(graph.label(1), "d");
+        assert_eq!(sorted_edges(&graph), vec![(1, 2)]);
+        assert_eq!(graph.add_vertex(), 3);
+        assert_eq!(graph.label(3), "3");
+        assert!(graph.remove_edge(2, 1));
+        assert!(!graph.remove_edge(1, 2));
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn simple_graph_drops_loops_and_parallel_edges() {
+        let mut graph = Graph::from_edgelist(vec![(0, 1), (1, 0), (1, 1), (0, 1), (2, 1)], 3);
+        assert_eq!(graph.adj_list, vec![vec![1], vec![0, 2], vec![1]]);
+        assert_eq!(graph.edge_count(), 2);
+        assert!(!graph.add_edge(1, 0));
+        assert!(!graph.add_edge(2, 2));
+        assert!(graph.add_edge(0, 2));
+        assert_eq!(graph.edge_count(), 3);
+    }
+
+    #[test]
+    fn loops_policy_keeps_one_loop_per_vertex() {
+        let graph = Graph::from_edgelist_with_policy(
+            vec![(0, 0), (0, 0), (0, 1), (1, 0)],
+            2,
+            GraphPolicy::Loops,
+        );
+        // A self-loop is stored twice
+        assert_eq!(graph.adj_list[0], vec![0, 0, 1]);
+        assert_eq!(graph.edges(), vec![(0, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn multigraph_keeps_everything_until_simplified() {
+        let mut graph = Graph::from_edgelist_with_policy(
+            vec![(0, 0), (0, 0), (0, 1), (1, 0)],
+            2,
+            GraphPolicy::Multigraph,
+        );
+        assert_eq!(graph.edge_count(), 4);
+        assert!(graph.add_edge(0, 1));
+        assert_eq!(graph.edge_count(), 5);
+        graph.simplify();
+        assert_eq!(graph.policy, GraphPolicy::Simple);
+        assert_eq!(graph.adj_list, vec![vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn directed_graph_keeps_opposite_arcs() {
+        let graph = Graph::from_arclist(vec![(0, 1), (1, 0), (0, 1), (2, 2)], 3);
+        assert_eq!(graph.edges(), vec![(0, 1), (1, 0)]);
+        assert_eq!(graph.predecessors(1), &[0]);
+    }
+
+    #[test]
+    fn adjacency_list_symmetry_decides_how_entries_are_read() {
+        // Symmetric: every edge is listed at both ends
+        let symmetric =
+            Graph::from_adjlist_with_policy(vec![vec![1, 1], vec![0, 0]], GraphPolicy::Multigraph);
+        assert_eq!(symmetric.edge_count(), 2);
+        // Otherwise every entry is an edge of its own
+        let one_sided = Graph::from_adjlist(vec![vec![1, 2], vec![], vec![0]]);
+        assert_eq!(one_sided.edges(), vec![(0, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn parallel_weighted_edges_are_summed() {
+        let graph = Graph::from_weighted_edgelist(vec![(0, 1, 1.5), (1, 0, 2.0), (1, 2, 1.0)], 3);
+        assert_eq!(graph.weighted_edges(), vec![(0, 1, 3.5), (1, 2, 1.0)]);
+        assert_eq!(graph.strength(), vec![3.5, 4.5, 1.0]);
+
+        let mut graph = graph;
+        assert!(!graph.add_weighted_edge(0, 1, 1.0));
+        assert_eq!(graph.weighted_edges()[0], (0, 1, 3.5));
+    }
+
+    #[test]
+    fn weighted_self_loops_keep_their_weight() {
+        let graph = Graph::from_weighted_edgelist_with_policy(
+            vec![(0, 0, 2.0), (0, 1, 1.0)],
+            2,
+            GraphPolicy::Loops,
+        );
+        assert_eq!(graph.weighted_edges(), vec![(0, 1, 1.0), (0, 0, 2.0)]);
+        // The loop counts twice, like in the degree
+        assert_eq!(graph.strength(), vec![5.0, 1.0]);
     }
 }
@@ -0,0 +1,419 @@
+use crate::diffusion::{cascade_rounds, CascadeModel};
+use crate::graph::Graph;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Seeds chosen by an influence maximisation method, in the order picked, with the
+// expected number of vertices they activate
+#[derive(Clone, Debug)]
+pub struct SeedSelection {
+    pub seeds: Vec<usize>,
+    pub spread: f64,
+}
+
+// Vertices with the `k` largest scores, ties broken by lower index
+pub fn top_k(scores: &[f64], k: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+    order.truncate(k);
+    order
+}
+
+// Size of run `run` of the cascade from `seeds`. Runs are seeded by their index so
+// estimates for different seed sets share their randomness, which keeps the
+// differences CELF compares far less noisy than the estimates themselves.
+fn cascade_size(graph: &Graph, seeds: &[usize], model: CascadeModel, seed: u64, run: u64) -> usize {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(run));
+    cascade_rounds(graph, seeds, model, &mut rng)
+        .iter()
+        .map(Vec::len)
+        .sum()
+}
+
+// Lazy-greedy candidate: marginal gain as of the time `seeds` had `round` vertices
+struct Candidate {
+    gain: f64,
+    vertex: usize,
+    round: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // Largest gain first, then lowest vertex
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.gain
+            .total_cmp(&other.gain)
+            .then(other.vertex.cmp(&self.vertex))
+    }
+}
+
+// Reverse reachable set of a random root: the vertices that would activate the
+// root in a random live-edge graph of the model
+fn random_rr_set(graph: &Graph, model: CascadeModel, rng: &mut StdRng) -> Vec<usize> {
+    let root = rng.random_range(0..graph.vertices);
+    let mut visited = HashSet::from([root]);
+    let mut set = vec![root];
+    match model {
+        CascadeModel::IndependentCascade { probability } => {
+            let mut i = 0;
+            while i < set.len() {
+                let v = set[i];
+                for &u in graph.predecessors(v) {
+                    if !visited.contains(&u) && rng.random::<f64>() < probability {
+                        visited.insert(u);
+                        set.push(u);
+                    }
+                }
+                i += 1;
+            }
+        }
+        // Every vertex keeps one incoming arc, picked by its weight 1 / in-degree
+        CascadeModel::LinearThreshold => {
+            let mut v = root;
+            loop {
+                let predecessors = graph.predecessors(v);
+                if predecessors.is_empty() {
+                    break;
+                }
+                v = predecessors[rng.random_range(0..predecessors.len())];
+                if !visited.insert(v) {
+                    break;
+                }
+                set.push(v);
+            }
+        }
+    }
+    set
+}
+
+// Appends reverse reachable sets until there are `count`; set `i` is seeded by `i`
+fn extend_rr_sets(
+    graph: &Graph,
+    sets: &mut Vec<Vec<usize>>,
+    count: usize,
+    model: CascadeModel,
+    seed: u64,
+) {
+    if count <= sets.len() {
+        return;
+    }
+    let new: Vec<Vec<usize>> = (sets.len()..count)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+            random_rr_set(graph, model, &mut rng)
+        })
+        .collect();
+    sets.extend(new);
+}
+
+// Greedy maximum coverage of the sets by `k` vertices; returns the vertices and the
+// number of sets they cover
+fn max_coverage(sets: &[Vec<usize>], vertices: usize, k: usize) -> (Vec<usize>, usize) {
+    let mut covering = vec![Vec::new(); vertices];
+    for (i, set) in sets.iter().enumerate() {
+        for &v in set {
+            covering[v].push(i);
+        }
+    }
+    let mut gain: Vec<usize> = covering.iter().map(Vec::len).collect();
+    let mut chosen = vec![false; vertices];
+    let mut covered = vec![false; sets.len()];
+    let mut seeds = Vec::with_capacity(k);
+    let mut total = 0;
+    for _ in 0..k {
+        let v = (0..vertices)
+            .filter(|&v| !chosen[v])
+            .max_by(|&a, &b| gain[a].cmp(&gain[b]).then(b.cmp(&a)))
+            .unwrap();
+        chosen[v] = true;
+        seeds.push(v);
+        total += gain[v];
+        for &i in &covering[v] {
+            if !covered[i] {
+                covered[i] = true;
+                for &u in &sets[i] {
+                    gain[u] -= 1;
+                }
+            }
+        }
+    }
+    (seeds, total)
+}
+
+// ln of the binomial coefficient n choose k
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    (0..k)
+        .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+        .sum()
+}
+
+impl Graph {
+    // Monte Carlo estimate of the expected number of vertices activated from `seeds`
+    pub fn expected_spread(
+        &self,
+        seeds: &[usize],
+        model: CascadeModel,
+        runs: usize,
+        seed: u64,
+    ) -> f64 {
+        assert!(runs > 0, "at least one run is needed");
+        let total: usize = (0..runs as u64)
+            .into_par_iter()
+            .map(|run| cascade_size(self, seeds, model, seed, run))
+            .sum();
+        total as f64 / runs as f64
+    }
+
+    // Greedy influence maximisation with CELF lazy evaluation (Leskovec et al. 2007).
+    // Spreads are estimated from `runs` cascades; the first pass is parallel over
+    // vertices, the lazy re-evaluations over runs.
+    pub fn influence_maximization_celf(
+        &self,
+        k: usize,
+        model: CascadeModel,
+        runs: usize,
+        seed: u64,
+    ) -> SeedSelection {
+        assert!(runs > 0, "at least one run is needed");
+        let k = k.min(self.vertices);
+        let mut heap: BinaryHeap<Candidate> = (0..self.vertices)
+            .into_par_iter()
+            .map(|v| {
+                let total: usize = (0..runs as u64)
+                    .map(|run| cascade_size(self, &[v], model, seed, run))
+                    .sum();
+                Candidate {
+                    gain: total as f64 / runs as f64,
+                    vertex: v,
+                    round: 0,
+                }
+            })
+            .collect::<Vec<_>>()
+            .into();
+
+        let mut seeds = Vec::with_capacity(k);
+        let mut spread = 0.0;
+        while seeds.len() < k {
+            let candidate = heap.pop().unwrap();
+            if candidate.round == seeds.len() {
+                seeds.push(candidate.vertex);
+                spread += candidate.gain;
+                continue;
+            }
+            seeds.push(candidate.vertex);
+            let gain = self.expected_spread(&seeds, model, runs, seed) - spread;
+            seeds.pop();
+            heap.push(Candidate {
+                gain,
+                vertex: candidate.vertex,
+                round: seeds.len(),
+            });
+        }
+
+        SeedSelection { seeds, spread }
+    }
+
+    // Influence maximisation by reverse influence sampling with IMM (Tang, Shi and
+    // Xiao 2015): a (1 - 1/e - epsilon)-approximation with probability 1 - 1/n.
+    // Reverse reachable sets are sampled in parallel; the spread is estimated from
+    // the share of them the seeds cover.
+    pub fn influence_maximization_imm(
+        &self,
+        k: usize,
+        model: CascadeModel,
+        epsilon: f64,
+        seed: u64,
+    ) -> SeedSelection {
+        assert!(epsilon > 0.0, "epsilon must be positive");
+        if k == 0 {
+            return SeedSelection {
+                seeds: Vec::new(),
+                spread: 0.0,
+            };
+        }
+        let n = self.vertices;
+        if k >= n {
+            return SeedSelection {
+                seeds: (0..n).collect(),
+                spread: n as f64,
+            };
+        }
+        let nf = n as f64;
+        let ln_n = nf.ln();
+        let l = 1.0 + 2f64.ln() / ln_n;
+        let ln_choose = ln_binomial(n, k);
+        let mut sets = Vec::new();
+
+        // Lower bound on the optimal spread by halving guesses
+        let epsilon_prime = 2f64.sqrt() * epsilon;
+        let lambda_prime =
+            (2.0 + 2.0 / 3.0 * epsilon_prime) * (ln_choose + l * ln_n + nf.log2().ln()) * nf
+                / (epsilon_prime * epsilon_prime);
+        let mut lower_bound = 1.0;
+        for i in 1..nf.log2() as i32 {
+            let x = nf / 2f64.powi(i);
+            extend_rr_sets(
+                self,
+                &mut sets,
+                (lambda_prime / x).ceil() as usize,
+                model,
+                seed,
+            );
+            let (_, covered) = max_coverage(&sets, n, k);
+            let estimate = nf * covered as f64 / sets.len() as f64;
+            if estimate >= (1.0 + epsilon_prime) * x {
+                lower_bound = estimate / (1.0 + epsilon_prime);
+                break;
+            }
+        }
+
+        let e = std::f64::consts::E;
+        let alpha = (l * ln_n + 2f64.ln()).sqrt();
+        let beta = ((1.0 - 1.0 / e) * (ln_choose + l * ln_n + 2f64.ln())).sqrt();
+        let lambda_star = 2.0 * nf * ((1.0 - 1.0 / e) * alpha + beta).powi(2) / (epsilon * epsilon);
+        // Fresh sets for the selection: reusing those that set the lower bound makes
+        // them dependent on it and voids the guarantee (Chen 2018)
+        sets.clear();
+        let final_seed = StdRng::seed_from_u64(seed).random::<u64>();
+        extend_rr_sets(
+            self,
+            &mut sets,
+            (lambda_star / lower_bound).ceil() as usize,
+            model,
+            final_seed,
+        );
+        let (seeds, covered) = max_coverage(&sets, n, k);
+
+        SeedSelection {
+            seeds,
+            spread: nf * covered as f64 / sets.len() as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ln_binomial, max_coverage, top_k};
+    use crate::diffusion::CascadeModel;
+    use crate::graph::Graph;
+
+    // Arcs from 0 to 1..=8 and from 9 to 10..=12, with 13 and 14 isolated
+    fn two_stars() -> Graph {
+        let arcs = (1..=8).map(|v| (0, v)).chain((10..=12).map(|v| (9, v)));
+        Graph::from_arclist(arcs.collect(), 15)
+    }
+
+    #[test]
+    fn helpers_on_known_values() {
+        assert_eq!(top_k(&[1.0, 3.0, 2.0, 3.0], 3), vec![1, 3, 2]);
+        assert_eq!(top_k(&[1.0], 5), vec![0]);
+        assert!((ln_binomial(10, 3) - 120f64.ln()).abs() < 1e-12);
+        assert_eq!(ln_binomial(7, 0), 0.0);
+
+        let sets = vec![vec![0, 1], vec![1, 2], vec![2], vec![3], vec![1]];
+        assert_eq!(max_coverage(&sets, 4, 1), (vec![1], 3));
+        assert_eq!(max_coverage(&sets, 4, 2), (vec![1, 2], 4));
+        assert_eq!(max_coverage(&sets, 4, 3), (vec![1, 2, 3], 5));
+    }
+
+    #[test]
+    fn expected_spread_of_known_cascades() {
+        let graph = two_stars();
+        let certain = CascadeModel::IndependentCascade { probability: 1.0 };
+        assert_eq!(graph.expected_spread(&[0, 9, 13], certain, 10, 1), 14.0);
+        assert_eq!(graph.expected_spread(&[1], certain, 10, 1), 1.0);
+        assert_eq!(
+            graph.expected_spread(&[9], CascadeModel::LinearThreshold, 10, 1),
+            4.0
+        );
+        // Every leaf is reached independently with probability 1/4
+        let uncertain = CascadeModel::IndependentCascade { probability: 0.25 };
+        let spread = graph.expected_spread(&[0], uncertain, 20_000, 7);
+        assert!((spread - 3.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn celf_and_imm_pick_the_star_centres() {
+        let graph = two_stars();
+        for model in [
+            CascadeModel::IndependentCascade { probability: 1.0 },
+            CascadeModel::LinearThreshold,
+        ] {
+            let celf = graph.influence_maximization_celf(2, model, 20, 7);
+            assert_eq!(celf.seeds, vec![0, 9]);
+            assert_eq!(celf.spread, 13.0);
+
+            let imm = graph.influence_maximization_imm(2, model, 0.2, 7);
+            let mut seeds = imm.seeds.clone();
+            seeds.sort_unstable();
+            assert_eq!(seeds, vec![0, 9]);
+            assert!((imm.spread - 13.0).abs() < 1.0);
+        }
+        let all = graph.influence_maximization_imm(20, CascadeModel::LinearThreshold, 0.2, 7);
+        assert_eq!(all.seeds, (0..15).collect::<Vec<_>>());
+        assert_eq!(all.spread, 15.0);
+    }
+
+    #[test]
+    fn celf_matches_exhaustive_search() {
+        // Random directed graph with uncertain arcs; shared runs make spreads comparable
+        let graph = Graph::from_arclist(
+            vec![
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (6, 7),
+                (7, 8),
+                (6, 8),
+            ],
+            9,
+        );
+        let model = CascadeModel::IndependentCascade { probability: 0.5 };
+        let best = (0..9)
+            .map(|v| graph.expected_spread(&[v], model, 200, 7))
+            .fold(f64::MIN, f64::max);
+        let celf = graph.influence_maximization_celf(1, model, 200, 7);
+        assert_eq!(celf.spread, best);
+        let pair = graph.influence_maximization_celf(2, model, 200, 7);
+        assert_eq!(pair.seeds[0], celf.seeds[0]);
+        let exact = graph.expected_spread(&pair.seeds, model, 200, 7);
+        assert!((pair.spread - exact).abs() < 1e-9);
+    }
+
+    #[test]
+    fn imm_without_seeds_returns_at_once() {
+        let graph = Graph::new(1);
+        let selection = graph.influence_maximization_imm(0, CascadeModel::LinearThreshold, 0.5, 1);
+        assert!(selection.seeds.is_empty());
+        assert_eq!(selection.spread, 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one run")]
+    fn spread_needs_runs() {
+        Graph::new(3).expected_spread(&[0], CascadeModel::LinearThreshold, 0, 1);
+    }
+}
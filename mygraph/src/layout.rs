@@ -0,0 +1,631 @@
+use crate::graph::Graph;
+use crate::paths::DistanceMatrix;
+use crate::structural::simple_adjacency;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Keeps coincident vertices from dividing by zero
+const MIN_DISTANCE: f64 = 1e-9;
+
+fn random_positions(n: usize, scale: f64, seed: u64) -> Vec<(f64, f64)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            (
+                rng.random_range(-scale..=scale),
+                rng.random_range(-scale..=scale),
+            )
+        })
+        .collect()
+}
+
+// Graph distances as f64; unreachable pairs are put one step beyond the diameter
+fn layout_distances(graph: &Graph) -> Vec<f64> {
+    let distances = graph.distance_matrix();
+    let longest = distances
+        .data
+        .iter()
+        .filter(|&&d| d != DistanceMatrix::UNREACHABLE)
+        .max()
+        .copied()
+        .unwrap_or(0);
+    distances
+        .data
+        .iter()
+        .map(|&d| {
+            if d == DistanceMatrix::UNREACHABLE {
+                longest as f64 + 1.0
+            } else {
+                d as f64
+            }
+        })
+        .collect()
+}
+
+// Settings of the ForceAtlas2 layout (Jacomy et al., 2014)
+#[derive(Clone, Debug)]
+pub struct ForceAtlas2 {
+    pub iterations: usize,
+    // Strength of the repulsion between vertices
+    pub scaling_ratio: f64,
+    // Pull of every vertex towards the origin
+    pub gravity: f64,
+    // Gravity grows with the distance to the origin
+    pub strong_gravity: bool,
+    // Logarithmic attraction, tightens clusters
+    pub lin_log: bool,
+    // Accuracy of the Barnes-Hut approximation, lower is more exact
+    pub theta: f64,
+    // How much swinging is tolerated before slowing down
+    pub jitter_tolerance: f64,
+    pub seed: u64,
+}
+
+impl Default for ForceAtlas2 {
+    fn default() -> Self {
+        ForceAtlas2 {
+            iterations: 100,
+            scaling_ratio: 2.0,
+            gravity: 1.0,
+            strong_gravity: false,
+            lin_log: false,
+            theta: 1.2,
+            jitter_tolerance: 1.0,
+            seed: 42,
+        }
+    }
+}
+
+// Cell of a Barnes-Hut quadtree, holding the total mass and centre of mass of
+// the vertices inside it
+struct Cell {
+    center: (f64, f64),
+    half_width: f64,
+    mass: f64,
+    mass_center: (f64, f64),
+    body: Option<usize>,
+    children: Option<[usize; 4]>,
+}
+
+struct QuadTree {
+    cells: Vec<Cell>,
+}
+
+impl QuadTree {
+    fn new(positions: &[(f64, f64)], masses: &[f64]) -> Self {
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in positions {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        let half_width = ((max_x - min_x).max(max_y - min_y) / 2.0).max(MIN_DISTANCE);
+        let root = Cell {
+            center: ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0),
+            half_width,
+            mass: 0.0,
+            mass_center: (0.0, 0.0),
+            body: None,
+            children: None,
+        };
+        let mut tree = QuadTree { cells: vec![root] };
+        for v in 0..positions.len() {
+            tree.insert(0, v, positions, masses);
+        }
+        tree
+    }
+
+    fn quadrant(cell: &Cell, (x, y): (f64, f64)) -> usize {
+        (x >= cell.center.0) as usize + 2 * (y >= cell.center.1) as usize
+    }
+
+    fn split(&mut self, index: usize) -> [usize; 4] {
+        let (cx, cy) = self.cells[index].center;
+        let quarter = self.cells[index].half_width / 2.0;
+        let mut children = [0; 4];
+        for (q, child) in children.iter_mut().enumerate() {
+            let dx = if q & 1 == 1 { quarter } else { -quarter };
+            let dy = if q & 2 == 2 { quarter } else { -quarter };
+            *child = self.cells.len();
+            self.cells.push(Cell {
+                center: (cx + dx, cy + dy),
+                half_width: quarter,
+                mass: 0.0,
+                mass_center: (0.0, 0.0),
+                body: None,
+                children: None,
+            });
+        }
+        self.cells[index].children = Some(children);
+        children
+    }
+
+    fn insert(&mut self, mut index: usize, v: usize, positions: &[(f64, f64)], masses: &[f64]) {
+        let (x, y) = positions[v];
+        let m = masses[v];
+        loop {
+            let cell = &mut self.cells[index];
+            let total = cell.mass + m;
+            cell.mass_center = (
+                (cell.mass_center.0 * cell.mass + x * m) / total,
+                (cell.mass_center.1 * cell.mass + y * m) / total,
+            );
+            cell.mass = total;
+
+            if let Some(children) = cell.children {
+                index = children[Self::quadrant(cell, (x, y))];
+                continue;
+            }
+            match cell.body {
+                None if total == m => {
+                    cell.body = Some(v);
+                    return;
+                }
+                // Coincident vertices: keep them together as one aggregate leaf
+                _ if cell.half_width < MIN_DISTANCE => return,
+                _ => {
+                    let other = cell.body.take();
+                    let children = self.split(index);
+                    if let Some(other) = other {
+                        let slot = Self::quadrant(&self.cells[index], positions[other]);
+                        let child = &mut self.cells[children[slot]];
+                        child.body = Some(other);
+                        child.mass = masses[other];
+                        child.mass_center = positions[other];
+                    }
+                    index = children[Self::quadrant(&self.cells[index], (x, y))];
+                }
+            }
+        }
+    }
+
+    // Repulsive force on vertex v, approximating far away cells by their centre of mass
+    fn repulsion(
+        &self,
+        v: usize,
+        position: (f64, f64),
+        mass: f64,
+        settings: &ForceAtlas2,
+    ) -> (f64, f64) {
+        let mut force = (0.0, 0.0);
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            if cell.mass == 0.0 || cell.body == Some(v) {
+                continue;
+            }
+            let dx = position.0 - cell.mass_center.0;
+            let dy = position.1 - cell.mass_center.1;
+            let distance = (dx * dx + dy * dy).sqrt();
+            match cell.children {
+                Some(children) if 2.0 * cell.half_width >= settings.theta * distance => {
+                    stack.extend(children);
+                }
+                _ => {
+                    if distance < MIN_DISTANCE {
+                        continue;
+                    }
+                    // F = k_r * m_v * m_cell / d, along the unit vector
+                    let factor = settings.scaling_ratio * mass * cell.mass / (distance * distance);
+                    force.0 += dx * factor;
+                    force.1 += dy * factor;
+                }
+            }
+        }
+        force
+    }
+}
+
+impl Graph {
+    // Fruchterman-Reingold spring embedder: all pairs repel with k^2 / d, edges
+    // attract with d^2 / k, and the step size cools linearly to zero
+    pub fn layout_fruchterman_reingold(&self, iterations: usize, seed: u64) -> Vec<(f64, f64)> {
+        let n = self.vertices;
+        let adj = simple_adjacency(self);
+        let mut positions = random_positions(n, 0.5, seed);
+        let k = (1.0 / n.max(1) as f64).sqrt();
+        let mut temperature = 0.1;
+        let cooling = temperature / (iterations + 1) as f64;
+
+        for _ in 0..iterations {
+            let current = &positions;
+            let displacement: Vec<(f64, f64)> = (0..n)
+                .into_par_iter()
+                .map(|i| {
+                    let (xi, yi) = current[i];
+                    let mut disp = (0.0, 0.0);
+                    for (j, &(xj, yj)) in current.iter().enumerate() {
+                        if i == j {
+                            continue;
+                        }
+                        let (dx, dy) = (xi - xj, yi - yj);
+                        let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+                        let factor = k * k / (distance * distance);
+                        disp.0 += dx * factor;
+                        disp.1 += dy * factor;
+                    }
+                    for &j in &adj[i] {
+                        let (dx, dy) = (xi - current[j].0, yi - current[j].1);
+                        let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+                        let factor = distance / k;
+                        disp.0 -= dx * factor;
+                        disp.1 -= dy * factor;
+                    }
+                    disp
+                })
+                .collect();
+
+            for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+                let length = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+                let step = length.min(temperature) / length;
+                position.0 += dx * step;
+                position.1 += dy * step;
+            }
+            temperature -= cooling;
+        }
+
+        positions
+    }
+
+    // Kamada-Kawai layout: springs between all pairs with rest length equal to the
+    // graph distance. Moves one vertex at a time with Newton-Raphson steps, always
+    // the one with the largest energy gradient, for at most `iterations` moves.
+    pub fn layout_kamada_kawai(&self, iterations: usize) -> Vec<(f64, f64)> {
+        let n = self.vertices;
+        if n < 2 {
+            return vec![(0.0, 0.0); n];
+        }
+        let distances = layout_distances(self);
+        let d = |i: usize, j: usize| distances[i * n + j];
+
+        // Start from a circle whose circumference fits the longest distance
+        let longest = distances.iter().copied().fold(1.0, f64::max);
+        let mut positions: Vec<(f64, f64)> = (0..n)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+                (longest / 2.0 * angle.cos(), longest / 2.0 * angle.sin())
+            })
+            .collect();
+
+        // Gradient contribution of vertex j to the energy derivative of vertex i
+        let contribution = |positions: &[(f64, f64)], i: usize, j: usize| {
+            let (dx, dy) = (
+                positions[i].0 - positions[j].0,
+                positions[i].1 - positions[j].1,
+            );
+            let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+            let (l, k) = (d(i, j), 1.0 / (d(i, j) * d(i, j)));
+            (k * (dx - l * dx / distance), k * (dy - l * dy / distance))
+        };
+        let gradient_of = |positions: &[(f64, f64)], i: usize| {
+            (0..n).filter(|&j| j != i).fold((0.0, 0.0), |acc, j| {
+                let c = contribution(positions, i, j);
+                (acc.0 + c.0, acc.1 + c.1)
+            })
+        };
+
+        let mut gradients: Vec<(f64, f64)> = (0..n)
+            .into_par_iter()
+            .map(|i| gradient_of(&positions, i))
+            .collect();
+        let epsilon = 1e-4;
+
+        for _ in 0..iterations {
+            let (m, delta) = gradients
+                .iter()
+                .enumerate()
+                .map(|(i, g)| (i, (g.0 * g.0 + g.1 * g.1).sqrt()))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            if delta < epsilon {
+                break;
+            }
+
+            let before: Vec<(f64, f64)> = (0..n).map(|i| contribution(&positions, i, m)).collect();
+            for _ in 0..50 {
+                let (mut exx, mut eyy, mut exy) = (0.0, 0.0, 0.0);
+                for j in (0..n).filter(|&j| j != m) {
+                    let (dx, dy) = (
+                        positions[m].0 - positions[j].0,
+                        positions[m].1 - positions[j].1,
+                    );
+                    let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+                    let (l, k) = (d(m, j), 1.0 / (d(m, j) * d(m, j)));
+                    let cube = distance * distance * distance;
+                    exx += k * (1.0 - l * dy * dy / cube);
+                    eyy += k * (1.0 - l * dx * dx / cube);
+                    exy += k * l * dx * dy / cube;
+                }
+                let (ex, ey) = gradient_of(&positions, m);
+                let determinant = exx * eyy - exy * exy;
+                if determinant.abs() < MIN_DISTANCE {
+                    break;
+                }
+                positions[m].0 += (-ex * eyy + ey * exy) / determinant;
+                positions[m].1 += (-ey * exx + ex * exy) / determinant;
+                if (ex * ex + ey * ey).sqrt() < epsilon {
+                    break;
+                }
+            }
+
+            // Only the terms involving m changed
+            gradients[m] = gradient_of(&positions, m);
+            for (i, old) in before.into_iter().enumerate() {
+                if i != m {
+                    let new = contribution(&positions, i, m);
+                    gradients[i].0 += new.0 - old.0;
+                    gradients[i].1 += new.1 - old.1;
+                }
+            }
+        }
+
+        positions
+    }
+
+    // Stress majorisation: minimises sum_ij d_ij^-2 (|x_i - x_j| - d_ij)^2 with the
+    // localised SMACOF update, all vertices at once in parallel, until the relative
+    // decrease of the stress falls below 1e-5
+    pub fn layout_stress(&self, iterations: usize, seed: u64) -> Vec<(f64, f64)> {
+        let n = self.vertices;
+        if n < 2 {
+            return vec![(0.0, 0.0); n];
+        }
+        let distances = layout_distances(self);
+        let mut positions = random_positions(n, (n as f64).sqrt(), seed);
+
+        let stress = |positions: &[(f64, f64)]| -> f64 {
+            (0..n)
+                .into_par_iter()
+                .map(|i| {
+                    (i + 1..n)
+                        .map(|j| {
+                            let (dx, dy) = (
+                                positions[i].0 - positions[j].0,
+                                positions[i].1 - positions[j].1,
+                            );
+                            let d = distances[i * n + j];
+                            let error = (dx * dx + dy * dy).sqrt() - d;
+                            error * error / (d * d)
+                        })
+                        .sum::<f64>()
+                })
+                .sum()
+        };
+
+        let mut previous = stress(&positions);
+        for _ in 0..iterations {
+            let current = &positions;
+            positions = (0..n)
+                .into_par_iter()
+                .map(|i| {
+                    let (mut x, mut y, mut total_weight) = (0.0, 0.0, 0.0);
+                    for j in (0..n).filter(|&j| j != i) {
+                        let d = distances[i * n + j];
+                        let weight = 1.0 / (d * d);
+                        let (dx, dy) = (current[i].0 - current[j].0, current[i].1 - current[j].1);
+                        let distance = (dx * dx + dy * dy).sqrt().max(MIN_DISTANCE);
+                        x += weight * (current[j].0 + d * dx / distance);
+                        y += weight * (current[j].1 + d * dy / distance);
+                        total_weight += weight;
+                    }
+                    (x / total_weight, y / total_weight)
+                })
+                .collect();
+
+            let current_stress = stress(&positions);
+            if (previous - current_stress).abs() < 1e-5 * previous {
+                break;
+            }
+            previous = current_stress;
+        }
+
+        positions
+    }
+
+    // ForceAtlas2 with Barnes-Hut approximated repulsion, for large graphs
+    pub fn layout_forceatlas2(&self, settings: &ForceAtlas2) -> Vec<(f64, f64)> {
+        let n = self.vertices;
+        let adj = simple_adjacency(self);
+        let masses: Vec<f64> = adj
+            .iter()
+            .map(|neighbors| neighbors.len() as f64 + 1.0)
+            .collect();
+        let mut positions = random_positions(n, (n as f64).sqrt(), settings.seed);
+        let mut previous_forces = vec![(0.0, 0.0); n];
+        let mut speed = 1.0;
+        let mut speed_efficiency = 1.0;
+
+        for _ in 0..settings.iterations {
+            let tree = QuadTree::new(&positions, &masses);
+            let forces: Vec<(f64, f64)> = (0..n)
+                .into_par_iter()
+                .map(|v| {
+                    let (x, y) = positions[v];
+                    let mut force = tree.repulsion(v, (x, y), masses[v], settings);
+
+                    let distance = (x * x + y * y).sqrt();
+                    if distance > MIN_DISTANCE {
+                        let factor = if settings.strong_gravity {
+                            settings.gravity * masses[v] * settings.scaling_ratio
+                        } else {
+                            settings.gravity * masses[v] / distance
+                        };
+                        force.0 -= x * factor;
+                        force.1 -= y * factor;
+                    }
+
+                    for &w in &adj[v] {
+                        let (dx, dy) = (x - positions[w].0, y - positions[w].1);
+                        let distance = (dx * dx + dy * dy).sqrt();
+                        if distance < MIN_DISTANCE {
+                            continue;
+                        }
+                        let factor = if settings.lin_log {
+                            (1.0 + distance).ln() / distance
+                        } else {
+                            1.0
+                        };
+                        force.0 -= dx * factor;
+                        force.1 -= dy * factor;
+                    }
+                    force
+                })
+                .collect();
+
+            // Adaptive global speed from how much vertices swing versus move steadily
+            let (mut total_swing, mut total_traction) = (0.0, 0.0);
+            let swings: Vec<f64> = (0..n)
+                .map(|v| {
+                    let (f, p) = (forces[v], previous_forces[v]);
+                    let swing = ((f.0 - p.0).powi(2) + (f.1 - p.1).powi(2)).sqrt();
+                    let traction = ((f.0 + p.0).powi(2) + (f.1 + p.1).powi(2)).sqrt() / 2.0;
+                    total_swing += masses[v] * swing;
+                    total_traction += masses[v] * traction;
+                    swing
+                })
+                .collect();
+
+            let estimated_jitter = 0.05 * (n as f64).sqrt();
+            let min_jitter = estimated_jitter.sqrt();
+            let mut jitter = settings.jitter_tolerance
+                * min_jitter.max((estimated_jitter * total_traction / (n * n) as f64).min(10.0));
+            if total_traction > 0.0 && total_swing / total_traction > 2.0 {
+                if speed_efficiency > 0.05 {
+                    speed_efficiency *= 0.5;
+                }
+                jitter = jitter.max(settings.jitter_tolerance);
+            }
+            let target_speed = if total_swing > 0.0 {
+                jitter * speed_efficiency * total_traction / total_swing
+            } else {
+                speed
+            };
+            if total_swing > jitter * total_traction {
+                if speed_efficiency > 0.05 {
+                    speed_efficiency *= 0.7;
+                }
+            } else if speed < 1000.0 {
+                speed_efficiency *= 1.3;
+            }
+            speed += (target_speed - speed).min(0.5 * speed);
+
+            for v in 0..n {
+                let factor = speed / (1.0 + (speed * swings[v]).sqrt());
+                positions[v].0 += forces[v].0 * factor;
+                positions[v].1 += forces[v].1 * factor;
+            }
+            previous_forces = forces;
+        }
+
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ForceAtlas2, QuadTree};
+    use crate::graph::Graph;
+    use crate::testutil;
+    use rand::Rng;
+
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    // Two triangles joined by the edge 2-3
+    fn barbell() -> Graph {
+        Graph::from_edgelist(
+            vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)],
+            6,
+        )
+    }
+
+    #[test]
+    fn exact_quadtree_matches_pairwise_repulsion() {
+        let mut rng = testutil::rng();
+        let settings = ForceAtlas2 {
+            theta: 0.0,
+            ..ForceAtlas2::default()
+        };
+        for _ in 0..20 {
+            let n = rng.random_range(1..50);
+            let positions: Vec<(f64, f64)> = (0..n)
+                .map(|_| (rng.random::<f64>() * 10.0, rng.random::<f64>() * 10.0))
+                .collect();
+            let masses: Vec<f64> = (0..n).map(|_| rng.random_range(1..5) as f64).collect();
+            let tree = QuadTree::new(&positions, &masses);
+            assert!((tree.cells[0].mass - masses.iter().sum::<f64>()).abs() < 1e-9);
+
+            for v in 0..n {
+                let mut expected = (0.0, 0.0);
+                for w in (0..n).filter(|&w| w != v) {
+                    let (dx, dy) = (
+                        positions[v].0 - positions[w].0,
+                        positions[v].1 - positions[w].1,
+                    );
+                    let factor =
+                        settings.scaling_ratio * masses[v] * masses[w] / (dx * dx + dy * dy);
+                    expected.0 += dx * factor;
+                    expected.1 += dy * factor;
+                }
+                let force = tree.repulsion(v, positions[v], masses[v], &settings);
+                assert!(distance(force, expected) < 1e-6 * (1.0 + distance(expected, (0.0, 0.0))));
+            }
+        }
+    }
+
+    #[test]
+    fn layouts_are_finite_and_reproducible() {
+        let graph = barbell();
+        let settings = ForceAtlas2::default();
+        let layouts = [
+            graph.layout_fruchterman_reingold(50, 7),
+            graph.layout_kamada_kawai(100),
+            graph.layout_stress(100, 7),
+            graph.layout_forceatlas2(&settings),
+        ];
+        for layout in &layouts {
+            assert_eq!(layout.len(), 6);
+            assert!(layout.iter().all(|p| p.0.is_finite() && p.1.is_finite()));
+        }
+        assert_eq!(layouts[0], graph.layout_fruchterman_reingold(50, 7));
+        assert_eq!(layouts[3], graph.layout_forceatlas2(&settings));
+        for n in 0..2 {
+            assert_eq!(Graph::new(n).layout_kamada_kawai(10), vec![(0.0, 0.0); n]);
+            assert_eq!(Graph::new(n).layout_stress(10, 0), vec![(0.0, 0.0); n]);
+        }
+    }
+
+    #[test]
+    fn distance_layouts_of_a_path_are_straight() {
+        let n = 6;
+        let path = Graph::from_edgelist((0..n - 1).map(|i| (i, i + 1)).collect(), n);
+        for layout in [path.layout_stress(500, 3), path.layout_kamada_kawai(1000)] {
+            for i in 0..n {
+                for j in i + 1..n {
+                    let d = distance(layout[i], layout[j]);
+                    assert!((d - (j - i) as f64).abs() < 1e-2, "{} {} {}", i, j, d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn force_layouts_keep_neighbours_close() {
+        let graph = barbell();
+        let fr = graph.layout_fruchterman_reingold(200, 1);
+        assert!(distance(fr[0], fr[1]) < distance(fr[0], fr[5]));
+
+        let fa2 = graph.layout_forceatlas2(&ForceAtlas2 {
+            iterations: 300,
+            ..ForceAtlas2::default()
+        });
+        assert!(distance(fa2[0], fa2[1]) < distance(fa2[0], fa2[5]));
+        assert!(distance(fa2[4], fa2[5]) < distance(fa2[1], fa2[4]));
+    }
+}
@@ -0,0 +1,30 @@
+pub mod assortativity;
+pub mod backbone;
+pub mod bipartite;
+pub mod census;
+pub mod centrality;
+pub mod cliques;
+pub mod clustering;
+pub mod coreperiphery;
+pub mod diffusion;
+pub mod dominance;
+pub mod export;
+pub mod flow;
+pub mod graph;
+pub mod influence;
+pub mod layout;
+pub mod motifs;
+pub mod paths;
+pub mod render;
+pub mod roles;
+pub mod similarity;
+pub mod spectral;
+pub mod structural;
+pub mod subgraph;
+pub mod summary;
+#[cfg(test)]
+mod testutil;
+pub mod utils;
+pub mod walks;
+
+pub use graph::Graph;
@@ -1,25 +1,906 @@
-mod centrality;
-mod graph;
-mod structural;
-mod utils;
-use std::env;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use mygraph::bipartite::{BipartiteGraph, Projection};
+use mygraph::diffusion::{CascadeModel, Epidemic, EpidemicModel, Outcome};
+use mygraph::export::{OutputFormat, VertexTable};
+use mygraph::graph::GraphPolicy;
+use mygraph::influence::{top_k, SeedSelection};
+use mygraph::layout::ForceAtlas2;
+use mygraph::render::{Dot, NodeColor, Svg};
+use mygraph::similarity::Similarity;
+use mygraph::utils::{self, InputFormat, ReadOptions};
+use mygraph::walks::{train_skipgram, Node2Vec, SkipGram};
+use mygraph::Graph;
+use std::error::Error;
 
-fn main() {
+#[derive(Parser)]
+#[command(
+    name = "mygraph",
+    about = "Network analysis on edge and adjacency lists"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Degree of every vertex
+    Degree(VertexArgs),
+    /// Closeness centrality of every vertex
+    Closeness(VertexArgs),
+    /// Betweenness centrality of every vertex
+    Betweenness(VertexArgs),
+    /// Eigenvector centrality of every vertex
+    Eigenvector(VertexArgs),
+    /// Largest distance from every vertex to any vertex it can reach
+    Eccentricity(VertexArgs),
+    /// Number of triangles every vertex is part of
+    Triangles(VertexArgs),
+    /// Spectral clustering into communities
+    Spectral(SpectralArgs),
+    /// Size, density, effective size and constraint of every ego network
+    Ego(VertexArgs),
+    /// Discrete, continuous and Rombach coreness of every vertex
+    CorePeriphery(VertexArgs),
+    /// Summary report: size, degrees, components and clustering
+    #[command(alias = "summary")]
+    Info(InfoArgs),
+    /// Dyad and triad census and reciprocity of a directed graph
+    Census(InfoArgs),
+    /// Edge and vertex connectivity, minimum cuts, articulation points and bridges
+    Connectivity(ConnectivityArgs),
+    /// Evaluate link prediction by hiding a random fraction of the edges
+    Predict(PredictArgs),
+    /// Simulate epidemics or cascades and report the final-size distribution
+    Simulate(SimulateArgs),
+    /// Pick influential seeds with CELF and IMM and compare them to centrality rankings
+    Influence(InfluenceArgs),
+    /// Write node2vec random walks, and optionally a skip-gram embedding
+    Walks(WalksArgs),
+    /// Project a two-mode edge list (first column one side, second the other) onto one side
+    Project(ProjectArgs),
+    /// Score every edge with a backbone method and mark the edges kept
+    Backbone(BackboneArgs),
+    /// Draw the graph as SVG, or export it with positions as Graphviz DOT
+    Draw(DrawArgs),
+    /// Time every algorithm on the graph
+    Bench(InputArgs),
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Comma separated edge list
+    Csv,
+    /// Whitespace separated edge list
+    Edgelist,
+    /// Whitespace separated adjacency list
+    Adjlist,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Policy {
+    /// Drop self-loops and parallel edges
+    Simple,
+    /// Keep self-loops, drop parallel edges
+    Loops,
+    /// Keep self-loops and parallel edges
+    Multi,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Output {
+    Csv,
+    Json,
+    Parquet,
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// Graph file to read
+    input: String,
+    /// Format of the graph file
+    #[arg(short, long, value_enum, default_value_t = Format::Csv)]
+    format: Format,
+    /// Skip the first line of the graph file
+    #[arg(long)]
+    header: bool,
+    /// Treat vertex ids as labels instead of 0-based indices
+    #[arg(long)]
+    labelled: bool,
+    /// Which self-loops and parallel edges to keep
+    #[arg(short, long, value_enum, default_value_t = Policy::Simple)]
+    policy: Policy,
+    /// Read edges as arcs from the first vertex to the second
+    #[arg(short, long)]
+    directed: bool,
+    /// Read the third column of an edge list as the edge weight
+    #[arg(short, long)]
+    weighted: bool,
+}
+
+#[derive(Args)]
+struct VertexArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// File to write the results to, stdout if omitted
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Format of the results, guessed from the output file if omitted
+    #[arg(short = 't', long = "to", value_enum)]
+    to: Option<Output>,
+}
+
+#[derive(Args)]
+struct SpectralArgs {
+    #[command(flatten)]
+    vertex: VertexArgs,
+    /// Number of communities, chosen by the eigengap heuristic if omitted
+    #[arg(short)]
+    k: Option<usize>,
+    /// Largest number of communities the eigengap heuristic considers
+    #[arg(long, default_value_t = 10)]
+    max_k: usize,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Layout {
+    /// Fruchterman-Reingold
+    Fr,
+    /// Kamada-Kawai
+    Kk,
+    /// Stress majorisation
+    Stress,
+    /// ForceAtlas2 with Barnes-Hut repulsion
+    Fa2,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Measure {
+    Degree,
+    Closeness,
+    Betweenness,
+    Eigenvector,
+    /// Spectral communities, number chosen by the eigengap heuristic
+    Community,
+}
+
+#[derive(Args)]
+struct DrawArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// SVG or DOT file to write, by extension
+    #[arg(short, long)]
+    output: String,
+    #[arg(short, long, value_enum, default_value_t = Layout::Fa2)]
+    layout: Layout,
+    /// Vertex measure mapped to the vertex size
+    #[arg(long, value_enum)]
+    size: Option<Measure>,
+    /// Vertex measure mapped to the vertex colour
+    #[arg(long, value_enum)]
+    color: Option<Measure>,
+    /// Write vertex labels
+    #[arg(long)]
+    labels: bool,
+}
+
+impl Measure {
+    fn compute(self, graph: &Graph) -> Vec<f64> {
+        match self {
+            Measure::Degree => graph.degree().into_iter().map(|d| d as f64).collect(),
+            Measure::Closeness => graph.closeness_centrality(),
+            Measure::Betweenness => graph.betweenness_centrality(),
+            Measure::Eigenvector => graph.eigenvector_centrality().iter().copied().collect(),
+            Measure::Community => graph
+                .spectral_clustering(graph.eigengap(10))
+                .into_iter()
+                .map(|c| c as f64)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Args)]
+struct ConnectivityArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Also report the maximum flow and minimum cut from this vertex (a label with
+    /// --labelled, otherwise a 0-based index)...
+    #[arg(long, requires = "target")]
+    source: Option<String>,
+    /// ...to this one
+    #[arg(long, requires = "source")]
+    target: Option<String>,
+}
+
+#[derive(Args)]
+struct PredictArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Share of the edges to hide
+    #[arg(long, default_value_t = 0.1)]
+    fraction: f64,
+    /// Number of top-scoring pairs for precision@k
+    #[arg(short, default_value_t = 100)]
+    k: usize,
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SpreadModel {
+    Sir,
+    Sis,
+    /// Independent cascade
+    Ic,
+    /// Linear threshold
+    Lt,
+}
+
+#[derive(Args)]
+struct SimulateArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum, default_value = "sir")]
+    model: SpreadModel,
+    /// Infection probability per step, rate in continuous time, or cascade probability
+    #[arg(long, default_value_t = 0.1)]
+    beta: f64,
+    /// Recovery probability per step, or rate in continuous time
+    #[arg(long, default_value_t = 0.1)]
+    gamma: f64,
+    /// Simulate epidemics in continuous time with the Gillespie algorithm
+    #[arg(long)]
+    continuous: bool,
+    /// Initially infected or active vertices (indices, or labels with --labelled);
+    /// one random vertex if empty
+    #[arg(long, value_delimiter = ',')]
+    seeds: Vec<String>,
+    #[arg(long, default_value_t = 1000)]
+    runs: usize,
+    /// Time at which epidemic runs stop
+    #[arg(long, default_value_t = 100.0)]
+    max_time: f64,
+    /// Write the time series of every run to this CSV file
+    #[arg(short, long)]
+    output: Option<String>,
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Cascade {
+    /// Independent cascade
+    Ic,
+    /// Linear threshold
+    Lt,
+}
+
+#[derive(Args)]
+struct InfluenceArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Number of seeds
+    #[arg(short, default_value_t = 10)]
+    k: usize,
+    #[arg(long, value_enum, default_value = "ic")]
+    model: Cascade,
+    /// Activation probability of the independent cascade
+    #[arg(long, default_value_t = 0.1)]
+    probability: f64,
+    /// Cascades per spread estimate
+    #[arg(long, default_value_t = 1000)]
+    runs: usize,
+    /// Approximation error of IMM
+    #[arg(long, default_value_t = 0.2)]
+    epsilon: f64,
+    /// Skip CELF, which simulates cascades for every vertex
+    #[arg(long)]
+    no_celf: bool,
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+}
+
+#[derive(Args)]
+struct WalksArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// File to write the walks to, one per line
+    #[arg(short, long)]
+    output: String,
+    /// Vertices per walk
+    #[arg(long, default_value_t = 80)]
+    length: usize,
+    /// Walks started from every vertex
+    #[arg(long, default_value_t = 10)]
+    walks: usize,
+    /// Return parameter
+    #[arg(short = 'P', default_value_t = 1.0)]
+    p: f64,
+    /// In-out parameter
+    #[arg(short = 'Q', default_value_t = 1.0)]
+    q: f64,
+    /// Also train a skip-gram embedding on the walks and write it here
+    #[arg(long)]
+    embedding: Option<String>,
+    /// Dimensions of the embedding
+    #[arg(long, default_value_t = 128)]
+    dimensions: usize,
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BackboneMethod {
+    /// Disparity filter; keeps p-values below the threshold (default 0.05)
+    Disparity,
+    /// Noise-corrected backbone; keeps edges that many deviations above zero (default 1.64)
+    Noise,
+    /// High-salience skeleton; keeps salience at or above the threshold (default 0.5)
+    Salience,
+    /// Global weight threshold (default 1)
+    Threshold,
+}
+
+#[derive(Args)]
+struct BackboneArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum, default_value = "disparity")]
+    method: BackboneMethod,
+    /// Significance threshold of the method
+    #[arg(long)]
+    threshold: Option<f64>,
+    /// CSV file for the scored edges instead of standard output
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Weighting {
+    /// Number of shared neighbours
+    Count,
+    /// Newman's collaboration weighting, 1 / (degree - 1) per shared neighbour
+    Newman,
+    /// 1 / degree per shared neighbour
+    Hyperbolic,
+}
+
+#[derive(Args)]
+struct ProjectArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Project onto the vertices of the second column instead of the first
+    #[arg(long)]
+    second: bool,
+    #[arg(long, value_enum, default_value = "count")]
+    weighting: Weighting,
+    /// CSV file for the projected edges instead of standard output
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Args)]
+struct InfoArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+impl InputArgs {
+    fn read(&self) -> Result<Graph, Box<dyn Error>> {
+        utils::read_graph(&self.input, &self.options())
+    }
+
+    // For commands whose algorithms only handle undirected graphs
+    fn read_undirected(&self, command: &str) -> Result<Graph, Box<dyn Error>> {
+        if self.directed {
+            return Err(format!("{} needs an undirected graph, drop --directed", command).into());
+        }
+        self.read()
+    }
+
+    fn read_bipartite(&self) -> Result<BipartiteGraph, Box<dyn Error>> {
+        if self.directed {
+            return Err("project needs an undirected graph, drop --directed".into());
+        }
+        utils::read_bipartite(&self.input, &self.options())
+    }
+
+    // Vertex named on the command line, by label if the file is labelled
+    fn vertex(&self, graph: &Graph, id: &str) -> Result<usize, Box<dyn Error>> {
+        let vertex = if self.labelled {
+            (0..graph.vertices).find(|&v| graph.label(v) == id)
+        } else {
+            id.parse().ok().filter(|&v| v < graph.vertices)
+        };
+        vertex.ok_or_else(|| format!("no vertex {:?} in {}", id, self.input).into())
+    }
+
+    fn options(&self) -> ReadOptions {
+        let format = match self.format {
+            Format::Csv => InputFormat::Csv,
+            Format::Edgelist => InputFormat::Edgelist,
+            Format::Adjlist => InputFormat::Adjlist,
+        };
+        let policy = match self.policy {
+            Policy::Simple => GraphPolicy::Simple,
+            Policy::Loops => GraphPolicy::Loops,
+            Policy::Multi => GraphPolicy::Multigraph,
+        };
+        ReadOptions {
+            format,
+            header: self.header,
+            labelled: self.labelled,
+            policy,
+            directed: self.directed,
+            weighted: self.weighted,
+        }
+    }
+}
+
+impl VertexArgs {
+    fn output_format(&self) -> OutputFormat {
+        match self.to {
+            Some(Output::Csv) => OutputFormat::Csv,
+            Some(Output::Json) => OutputFormat::Json,
+            Some(Output::Parquet) => OutputFormat::Parquet,
+            None => self
+                .output
+                .as_deref()
+                .and_then(OutputFormat::from_path)
+                .unwrap_or(OutputFormat::Csv),
+        }
+    }
+
+    fn write(&self, table: VertexTable) -> Result<(), Box<dyn Error>> {
+        table.write(self.output.as_deref(), self.output_format())
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
     env_logger::init();
-    let args: Vec<String> = env::args().collect();
-    // dbg!(args);
-    let path = &args[1];
-    // let mut graph = Graph::new(5);
-
-    // graph.add_edge(0, 1);
-    // graph.add_edge(0, 4);
-    // graph.add_edge(1, 2);
-    // graph.add_edge(1, 3);
-    // graph.add_edge(1, 4);
-    // graph.add_edge(2, 3);
-    // graph.add_edge(3, 4);
-
-    let graph = utils::read_edgelist(path).expect("error");
+    let cli = Cli::parse();
+
+    match &cli.command {
+        Command::Degree(args) => {
+            let graph = args.input.read()?;
+            args.write(VertexTable::new(&graph).column("degree", graph.degree()))
+        }
+        Command::Closeness(args) => {
+            let graph = args.input.read()?;
+            let closeness = graph.closeness_centrality();
+            args.write(VertexTable::new(&graph).column("closeness", closeness))
+        }
+        Command::Betweenness(args) => {
+            let graph = args.input.read()?;
+            let betweenness = graph.betweenness_centrality();
+            args.write(VertexTable::new(&graph).column("betweenness", betweenness))
+        }
+        Command::Eigenvector(args) => {
+            let graph = args.input.read()?;
+            let eigenvector: Vec<f64> = graph.eigenvector_centrality().iter().copied().collect();
+            args.write(VertexTable::new(&graph).column("eigenvector", eigenvector))
+        }
+        Command::Eccentricity(args) => {
+            let graph = args.input.read()?;
+            let eccentricity = graph.eccentricity();
+            args.write(VertexTable::new(&graph).column("eccentricity", eccentricity))
+        }
+        Command::Triangles(args) => {
+            let graph = args.input.read_undirected("triangles")?;
+            let triangles = graph.local_triangles();
+            args.write(VertexTable::new(&graph).column("triangles", triangles))
+        }
+        Command::Spectral(args) => {
+            let graph = args.vertex.input.read_undirected("spectral")?;
+            let k = args.k.unwrap_or_else(|| graph.eigengap(args.max_k));
+            let communities = graph.spectral_clustering(k);
+            eprintln!(
+                "{} communities, modularity {:.6}",
+                k,
+                graph.calculate_modularity(&communities)
+            );
+            args.vertex
+                .write(VertexTable::new(&graph).column("community", communities))
+        }
+        Command::Ego(args) => {
+            let graph = args.input.read_undirected("ego")?;
+            let metrics = graph.ego_metrics();
+            let table = VertexTable::new(&graph)
+                .column("size", metrics.iter().map(|m| m.size).collect::<Vec<_>>())
+                .column(
+                    "density",
+                    metrics.iter().map(|m| m.density).collect::<Vec<_>>(),
+                )
+                .column(
+                    "effective_size",
+                    metrics.iter().map(|m| m.effective_size).collect::<Vec<_>>(),
+                )
+                .column(
+                    "constraint",
+                    metrics.iter().map(|m| m.constraint).collect::<Vec<_>>(),
+                );
+            args.write(table)
+        }
+        Command::CorePeriphery(args) => {
+            let graph = args.input.read_undirected("core-periphery")?;
+            let discrete = graph.core_periphery_discrete();
+            let continuous = graph.core_periphery_continuous(1000);
+            let rombach = graph.rombach_core_score(20);
+            eprintln!(
+                "fit: discrete {:.6}, continuous {:.6}, rombach {:.6}",
+                discrete.fit, continuous.fit, rombach.fit
+            );
+            let table = VertexTable::new(&graph)
+                .column("discrete", discrete.coreness)
+                .column("continuous", continuous.coreness)
+                .column("rombach", rombach.coreness);
+            args.write(table)
+        }
+        Command::Info(args) => {
+            let summary = args.input.read()?.summary();
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                print!("{}", summary);
+            }
+            Ok(())
+        }
+        Command::Census(args) => {
+            let graph = args.input.read()?;
+            let (dyads, triads) = (graph.dyad_census(), graph.triad_census());
+            let reciprocity = graph.reciprocity();
+            if args.json {
+                let census = serde_json::json!({
+                    "dyads": dyads,
+                    "reciprocity": reciprocity,
+                    "triads": triads,
+                });
+                println!("{}", serde_json::to_string_pretty(&census)?);
+            } else {
+                println!("mutual:      {}", dyads.mutual);
+                println!("asymmetric:  {}", dyads.asymmetric);
+                println!("null:        {}", dyads.null);
+                println!("reciprocity: {:.6}", reciprocity);
+                println!("triads:");
+                for (name, count) in triads.iter() {
+                    println!("  {:>6} {:>12}", name, count);
+                }
+            }
+            Ok(())
+        }
+        Command::Connectivity(args) => {
+            let graph = args.input.read()?;
+            let pair = match (&args.source, &args.target) {
+                (Some(source), Some(target)) => Some((
+                    args.input.vertex(&graph, source)?,
+                    args.input.vertex(&graph, target)?,
+                )),
+                _ => None,
+            };
+            let edge_list = |edges: &[(usize, usize)]| {
+                edges
+                    .iter()
+                    .map(|&(u, w)| format!("{}-{}", graph.label(u), graph.label(w)))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            println!("edge connectivity:   {}", graph.edge_connectivity());
+            println!("vertex connectivity: {}", graph.vertex_connectivity());
+            if !graph.is_directed() {
+                let cut = graph.global_minimum_cut();
+                let side = cut.source_side.iter().filter(|&&s| s).count();
+                println!(
+                    "global minimum cut:  {} ({} | {} vertices) {}",
+                    cut.value,
+                    side,
+                    graph.vertices - side,
+                    edge_list(&cut.edges)
+                );
+            }
+            let (points, bridges) = graph.articulation_points_and_bridges();
+            let labels: Vec<String> = points.iter().map(|&v| graph.label(v)).collect();
+            println!("articulation points: {} {}", points.len(), labels.join(" "));
+            println!(
+                "bridges:             {} {}",
+                bridges.len(),
+                edge_list(&bridges)
+            );
+            if let Some((source, target)) = pair {
+                let cut = graph.minimum_cut(source, target);
+                println!("maximum flow:        {}", cut.value);
+                println!("minimum cut:         {}", edge_list(&cut.edges));
+            }
+            Ok(())
+        }
+        Command::Predict(args) => {
+            let graph = args.input.read_undirected("predict")?;
+            println!(
+                "{:<24} {:>8} {:>12}",
+                "index",
+                "AUC",
+                format!("precision@{}", args.k)
+            );
+            for measure in Similarity::ALL {
+                let result =
+                    graph.evaluate_link_prediction(measure, args.fraction, args.k, args.seed);
+                println!(
+                    "{:<24} {:>8.4} {:>12.4}",
+                    format!("{:?}", measure),
+                    result.auc,
+                    result.precision
+                );
+            }
+            Ok(())
+        }
+        Command::Simulate(args) => {
+            let graph = args.input.read()?;
+            let seeds = args
+                .seeds
+                .iter()
+                .map(|id| args.input.vertex(&graph, id))
+                .collect::<Result<Vec<_>, _>>()?;
+            let outcome = simulate(&graph, args, &seeds);
+            println!("{:>8} {:>8} {:>8}", "size", "runs", "share");
+            for (size, share) in outcome.final_size_distribution().iter().enumerate() {
+                if *share > 0.0 {
+                    let runs = (share * outcome.runs.len() as f64).round();
+                    println!("{:>8} {:>8} {:>8.4}", size, runs, share);
+                }
+            }
+            println!("mean final size: {:.2}", outcome.mean_final_size());
+            if let Some(path) = &args.output {
+                let mut writer = csv::Writer::from_path(path)?;
+                writer.write_record(["run", "time", "susceptible", "infected", "recovered"])?;
+                for (run, series) in outcome.runs.iter().enumerate() {
+                    for i in 0..series.times.len() {
+                        writer.write_record(&[
+                            run.to_string(),
+                            series.times[i].to_string(),
+                            series.susceptible[i].to_string(),
+                            series.infected[i].to_string(),
+                            series.recovered[i].to_string(),
+                        ])?;
+                    }
+                }
+                writer.flush()?;
+            }
+            Ok(())
+        }
+        Command::Influence(args) => {
+            let graph = args.input.read()?;
+            influence(&graph, args)
+        }
+        Command::Walks(args) => {
+            for (flag, value) in [("-P", args.p), ("-Q", args.q)] {
+                if !(value > 0.0 && value.is_finite()) {
+                    return Err(
+                        format!("{} must be positive and finite, not {}", flag, value).into(),
+                    );
+                }
+            }
+            let graph = args.input.read()?;
+            let settings = Node2Vec {
+                walk_length: args.length,
+                walks_per_vertex: args.walks,
+                p: args.p,
+                q: args.q,
+                seed: args.seed,
+            };
+            let walks = graph.node2vec_walks(&settings);
+            let file = std::fs::File::create(&args.output)?;
+            graph.write_walks(&walks, std::io::BufWriter::new(file))?;
+            if let Some(path) = &args.embedding {
+                let training = SkipGram {
+                    dimensions: args.dimensions,
+                    seed: args.seed,
+                    ..SkipGram::default()
+                };
+                let embedding = train_skipgram(&walks, graph.vertices, &training);
+                let file = std::fs::File::create(path)?;
+                graph.write_embedding(&embedding, std::io::BufWriter::new(file))?;
+            }
+            Ok(())
+        }
+        Command::Project(args) => {
+            let bipartite = args.input.read_bipartite()?;
+            let weighting = match args.weighting {
+                Weighting::Count => Projection::Count,
+                Weighting::Newman => Projection::Newman,
+                Weighting::Hyperbolic => Projection::Hyperbolic,
+            };
+            let projection = bipartite.projection(args.second, weighting).graph;
+            let writer: Box<dyn std::io::Write> = match &args.output {
+                Some(path) => Box::new(std::fs::File::create(path)?),
+                None => Box::new(std::io::stdout()),
+            };
+            let mut writer = csv::Writer::from_writer(writer);
+            writer.write_record(["src", "dest", "weight"])?;
+            for (u, w, weight) in projection.weighted_edges() {
+                writer.write_record(&[
+                    projection.label(u),
+                    projection.label(w),
+                    weight.to_string(),
+                ])?;
+            }
+            writer.flush()?;
+            Ok(())
+        }
+        Command::Backbone(args) => {
+            let graph = args.input.read()?;
+            let backbone = match args.method {
+                BackboneMethod::Disparity => graph.disparity_filter(args.threshold.unwrap_or(0.05)),
+                BackboneMethod::Noise => {
+                    graph.noise_corrected_backbone(args.threshold.unwrap_or(1.64))
+                }
+                BackboneMethod::Salience => {
+                    graph.high_salience_skeleton(args.threshold.unwrap_or(0.5))
+                }
+                BackboneMethod::Threshold => {
+                    graph.global_threshold_backbone(args.threshold.unwrap_or(1.0))
+                }
+            };
+            let writer: Box<dyn std::io::Write> = match &args.output {
+                Some(path) => Box::new(std::fs::File::create(path)?),
+                None => Box::new(std::io::stdout()),
+            };
+            let mut writer = csv::Writer::from_writer(writer);
+            writer.write_record(["src", "dest", "weight", "significance", "kept"])?;
+            for (&(u, w, weight), significance) in backbone.edges.iter().zip(&backbone.significance)
+            {
+                let kept = backbone.graph.has_edge(u, w);
+                writer.write_record(&[
+                    graph.label(u),
+                    graph.label(w),
+                    weight.to_string(),
+                    significance.to_string(),
+                    kept.to_string(),
+                ])?;
+            }
+            writer.flush()?;
+            Ok(())
+        }
+        Command::Draw(args) => {
+            let graph = args.input.read()?;
+            draw(&graph, args)
+        }
+        Command::Bench(args) => {
+            let graph = args.read()?;
+            bench(&graph);
+            Ok(())
+        }
+    }
+}
+
+fn simulate(graph: &Graph, args: &SimulateArgs, seeds: &[usize]) -> Outcome {
+    let model = match args.model {
+        SpreadModel::Sir => EpidemicModel::Sir,
+        SpreadModel::Sis => EpidemicModel::Sis,
+        SpreadModel::Ic | SpreadModel::Lt => {
+            let model = match args.model {
+                SpreadModel::Ic => CascadeModel::IndependentCascade {
+                    probability: args.beta,
+                },
+                _ => CascadeModel::LinearThreshold,
+            };
+            return graph.simulate_cascade(seeds, model, args.runs, args.seed);
+        }
+    };
+    let settings = Epidemic {
+        model,
+        transmission: args.beta,
+        recovery: args.gamma,
+        initial: seeds.to_vec(),
+        max_time: args.max_time,
+        runs: args.runs,
+        seed: args.seed,
+    };
+    if args.continuous {
+        graph.simulate_epidemic_gillespie(&settings)
+    } else {
+        graph.simulate_epidemic(&settings)
+    }
+}
+
+fn influence(graph: &Graph, args: &InfluenceArgs) -> Result<(), Box<dyn Error>> {
+    if args.runs == 0 {
+        return Err("--runs must be at least 1".into());
+    }
+    if args.epsilon.is_nan() || args.epsilon <= 0.0 {
+        return Err(format!("--epsilon must be positive, not {}", args.epsilon).into());
+    }
+    let model = match args.model {
+        Cascade::Ic => CascadeModel::IndependentCascade {
+            probability: args.probability,
+        },
+        Cascade::Lt => CascadeModel::LinearThreshold,
+    };
+    let degree: Vec<f64> = graph.degree().iter().map(|&d| d as f64).collect();
+    let eigenvector: Vec<f64> = graph.eigenvector_centrality().iter().copied().collect();
+    let mut selections: Vec<(&str, Vec<usize>)> = Vec::new();
+    if !args.no_celf {
+        let SeedSelection { seeds, .. } =
+            graph.influence_maximization_celf(args.k, model, args.runs, args.seed);
+        selections.push(("celf", seeds));
+    }
+    let SeedSelection { seeds: imm, .. } =
+        graph.influence_maximization_imm(args.k, model, args.epsilon, args.seed);
+    selections.push(("imm", imm.clone()));
+    selections.push(("degree", top_k(&degree, args.k)));
+    selections.push((
+        "betweenness",
+        top_k(&graph.betweenness_centrality(), args.k),
+    ));
+    selections.push(("eigenvector", top_k(&eigenvector, args.k)));
+
+    // Every set is scored on the same cascades, independent of those CELF saw
+    let evaluation_seed = args.seed.wrapping_add(1);
+    println!("{:<12} {:>10} {:>8}  seeds", "method", "spread", "in imm");
+    for (method, seeds) in &selections {
+        let spread = graph.expected_spread(seeds, model, args.runs, evaluation_seed);
+        let shared = seeds.iter().filter(|v| imm.contains(v)).count();
+        let labels: Vec<String> = seeds.iter().map(|&v| graph.label(v)).collect();
+        println!(
+            "{:<12} {:>10.2} {:>8}  {}",
+            method,
+            spread,
+            shared,
+            labels.join(",")
+        );
+    }
+    Ok(())
+}
+
+fn draw(graph: &Graph, args: &DrawArgs) -> Result<(), Box<dyn Error>> {
+    let layout = match args.layout {
+        Layout::Fr => graph.layout_fruchterman_reingold(500, 42),
+        Layout::Kk => graph.layout_kamada_kawai(100 * graph.vertices),
+        Layout::Stress => graph.layout_stress(500, 42),
+        Layout::Fa2 => graph.layout_forceatlas2(&ForceAtlas2::default()),
+    };
+    let sizes = args.size.map(|measure| measure.compute(graph));
+    let colors = args.color.map(|measure| (measure, measure.compute(graph)));
+    let file = std::fs::File::create(&args.output)?;
+
+    if args.output.to_ascii_lowercase().ends_with(".dot") {
+        let mut dot = Dot::new().graph_attr("layout", "neato").positions(&layout);
+        // Measures become vertex attributes named after them
+        for (measure, values) in args
+            .size
+            .iter()
+            .zip(&sizes)
+            .chain(colors.iter().map(|(m, v)| (m, v)))
+        {
+            let name = measure.to_possible_value().unwrap();
+            dot = dot.vertex_attr(name.get_name(), values);
+        }
+        graph.write_dot(&dot, std::io::BufWriter::new(file))?;
+        return Ok(());
+    }
+
+    let categories: Vec<usize>;
+    let node_colors = match &colors {
+        Some((Measure::Community, values)) => {
+            categories = values.iter().map(|&c| c as usize).collect();
+            NodeColor::Categories(&categories)
+        }
+        Some((_, values)) => NodeColor::Gradient(values),
+        None => Svg::default().colors,
+    };
+    let svg = Svg {
+        sizes: sizes.as_deref(),
+        colors: node_colors,
+        labels: args.labels,
+        ..Svg::default()
+    };
+    graph.write_svg(&layout, &svg, std::io::BufWriter::new(file))?;
+    Ok(())
+}
+
+fn bench(graph: &Graph) {
     println!("to sparse Matrix");
     time(|| graph.to_adjacency_matrix_sparse());
     // centrality
@@ -33,6 +914,10 @@ fn main() {
     time(|| graph.eigenvector_centrality());
     println!("Triangles");
     time(|| graph.count_triangles());
+    println!("Maximal cliques");
+    time(|| graph.maximal_cliques());
+    println!("4-node graphlets");
+    time(|| graph.graphlet_census());
 }
 
 fn time<T, F: FnOnce() -> T>(f: F) -> T {
@@ -0,0 +1,286 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use std::collections::HashMap;
+use std::ops::{Add, Div};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Counts of the six connected graphs on four vertices, as induced subgraphs
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GraphletCounts {
+    // a - b - c - d
+    pub paths: usize,
+    // One vertex joined to the other three
+    pub stars: usize,
+    // a - b - c - d - a
+    pub cycles: usize,
+    // A triangle with a pendant vertex, also known as a paw
+    pub tailed_triangles: usize,
+    // Four vertices with all edges but one
+    pub diamonds: usize,
+    // Four vertices with all six edges
+    pub cliques: usize,
+}
+
+impl Add for GraphletCounts {
+    type Output = GraphletCounts;
+
+    fn add(self, other: GraphletCounts) -> GraphletCounts {
+        GraphletCounts {
+            paths: self.paths + other.paths,
+            stars: self.stars + other.stars,
+            cycles: self.cycles + other.cycles,
+            tailed_triangles: self.tailed_triangles + other.tailed_triangles,
+            diamonds: self.diamonds + other.diamonds,
+            cliques: self.cliques + other.cliques,
+        }
+    }
+}
+
+impl Div<usize> for GraphletCounts {
+    type Output = GraphletCounts;
+
+    fn div(self, d: usize) -> GraphletCounts {
+        GraphletCounts {
+            paths: self.paths / d,
+            stars: self.stars / d,
+            cycles: self.cycles / d,
+            tailed_triangles: self.tailed_triangles / d,
+            diamonds: self.diamonds / d,
+            cliques: self.cliques / d,
+        }
+    }
+}
+
+impl GraphletCounts {
+    // Induced counts from counts of (not necessarily induced) subgraphs, by removing
+    // the copies of each graphlet that sit inside denser ones: a diamond contains one
+    // cycle, two stars, four tailed triangles and six paths, and so on. The same
+    // holds per vertex, since all subgraphs on a vertex set contain the same vertices.
+    fn from_subgraph_counts(subgraphs: GraphletCounts) -> GraphletCounts {
+        let cliques = subgraphs.cliques;
+        let diamonds = subgraphs.diamonds - 6 * cliques;
+        let cycles = subgraphs.cycles - diamonds - 3 * cliques;
+        let tailed_triangles = subgraphs.tailed_triangles - 4 * diamonds - 12 * cliques;
+        let stars = subgraphs.stars - tailed_triangles - 2 * diamonds - 4 * cliques;
+        let paths =
+            subgraphs.paths - 2 * tailed_triangles - 4 * cycles - 6 * diamonds - 12 * cliques;
+        GraphletCounts {
+            paths,
+            stars,
+            cycles,
+            tailed_triangles,
+            diamonds,
+            cliques,
+        }
+    }
+}
+
+fn choose2(n: usize) -> usize {
+    n * n.saturating_sub(1) / 2
+}
+
+fn choose3(n: usize) -> usize {
+    n * n.saturating_sub(1) * n.saturating_sub(2) / 6
+}
+
+fn intersection_size(a: &[usize], b: &[usize]) -> usize {
+    let (mut i, mut j, mut count) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    count
+}
+
+impl Graph {
+    // Number of induced copies of each 4-vertex graphlet that contain each vertex.
+    // Subgraph counts come from degrees, common neighbours of edges and the triangles
+    // at each vertex, so no 4-vertex set is enumerated except those of 4-cliques.
+    // Self-loops and parallel edges are ignored.
+    pub fn vertex_graphlet_census(&self) -> Vec<GraphletCounts> {
+        let adjacency = simple_adjacency(self);
+        let degree = |v: usize| adjacency[v].len();
+
+        // Common neighbours of every edge, aligned with the adjacency lists
+        let common: Vec<Vec<usize>> = adjacency
+            .par_iter()
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .map(|&u| intersection_size(neighbors, &adjacency[u]))
+                    .collect()
+            })
+            .collect();
+        let edge_common = |a: usize, b: usize| {
+            let i = adjacency[a].binary_search(&b).expect("edge");
+            common[a][i]
+        };
+        let triangles: Vec<usize> = common.iter().map(|c| c.iter().sum::<usize>() / 2).collect();
+        // Walks v - b - c - d that do not return to b, summed over the neighbours c of b
+        let onward: Vec<usize> = adjacency
+            .iter()
+            .map(|neighbors| neighbors.iter().map(|&c| degree(c) - 1).sum())
+            .collect();
+
+        (0..self.vertices)
+            .into_par_iter()
+            .map(|v| {
+                let neighbors = &adjacency[v];
+                let d = neighbors.len();
+                let t = triangles[v];
+                let mut subgraphs = GraphletCounts {
+                    stars: choose3(d),
+                    tailed_triangles: t * d.saturating_sub(2),
+                    ..GraphletCounts::default()
+                };
+
+                // Paths from v, and paths through v where c continues to a fourth vertex
+                let mut path_ends = 0;
+                for (i, &u) in neighbors.iter().enumerate() {
+                    let t_vu = common[v][i];
+                    subgraphs.stars += choose2(degree(u) - 1);
+                    path_ends += onward[u] - (d - 1);
+                    subgraphs.paths += (d - 1) * (degree(u) - 1) - t_vu;
+                    // v as the pendant of a triangle at u, or as a corner next to u
+                    subgraphs.tailed_triangles += triangles[u] - t_vu;
+                    subgraphs.tailed_triangles += t_vu * degree(u).saturating_sub(2);
+                    subgraphs.diamonds += choose2(t_vu);
+                }
+                subgraphs.paths += path_ends - 2 * t;
+
+                // 4-cycles through v and the vertex w opposite to it
+                let mut two_hop: HashMap<usize, usize> = HashMap::new();
+                for &u in neighbors {
+                    for &w in &adjacency[u] {
+                        if w != v {
+                            *two_hop.entry(w).or_insert(0) += 1;
+                        }
+                    }
+                }
+                subgraphs.cycles = two_hop.values().map(|&c| choose2(c)).sum();
+
+                // Triangles v, a, b: v as a tip of a diamond on the edge a - b, and
+                // 4-cliques v, a, b, c counted once with a < b < c
+                for &a in neighbors {
+                    let shared: Vec<usize> = neighbors
+                        .iter()
+                        .copied()
+                        .filter(|&b| b > a && adjacency[a].binary_search(&b).is_ok())
+                        .collect();
+                    for (i, &b) in shared.iter().enumerate() {
+                        subgraphs.diamonds += edge_common(a, b) - 1;
+                        subgraphs.cliques += shared[i + 1..]
+                            .iter()
+                            .filter(|&&c| adjacency[b].binary_search(&c).is_ok())
+                            .count();
+                    }
+                }
+
+                GraphletCounts::from_subgraph_counts(subgraphs)
+            })
+            .collect()
+    }
+
+    // Number of induced copies of each 4-vertex graphlet in the graph
+    pub fn graphlet_census(&self) -> GraphletCounts {
+        // Every graphlet is counted once from each of its four vertices
+        self.vertex_graphlet_census()
+            .into_iter()
+            .fold(GraphletCounts::default(), |total, counts| total + counts)
+            / 4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GraphletCounts;
+    use crate::graph::{Graph, GraphPolicy};
+    use crate::testutil::{self, gnp};
+    use rand::Rng;
+
+    // Induced graphlets of every 4-vertex set that contain each vertex
+    fn brute_force(graph: &Graph) -> Vec<GraphletCounts> {
+        let n = graph.vertices;
+        let mut counts = vec![GraphletCounts::default(); n];
+        for a in 0..n {
+            for b in a + 1..n {
+                for c in b + 1..n {
+                    for d in c + 1..n {
+                        let set = [a, b, c, d];
+                        let mut degrees = [0; 4];
+                        for i in 0..4 {
+                            for j in 0..4 {
+                                if i != j && graph.has_edge(set[i], set[j]) {
+                                    degrees[i] += 1;
+                                }
+                            }
+                        }
+                        degrees.sort_unstable();
+                        let mut graphlet = GraphletCounts::default();
+                        match degrees {
+                            [1, 1, 2, 2] => graphlet.paths = 1,
+                            [1, 1, 1, 3] => graphlet.stars = 1,
+                            [2, 2, 2, 2] => graphlet.cycles = 1,
+                            [1, 2, 2, 3] => graphlet.tailed_triangles = 1,
+                            [2, 2, 3, 3] => graphlet.diamonds = 1,
+                            [3, 3, 3, 3] => graphlet.cliques = 1,
+                            _ => continue,
+                        }
+                        for v in set {
+                            counts[v] = counts[v] + graphlet;
+                        }
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn graphlet_census_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..100 {
+            let n = rng.random_range(1..14);
+            let p = rng.random();
+            let graph = gnp(&mut rng, n, p, false);
+            let expected = brute_force(&graph);
+            assert_eq!(graph.vertex_graphlet_census(), expected);
+            let total = expected
+                .into_iter()
+                .fold(GraphletCounts::default(), |total, counts| total + counts)
+                / 4;
+            assert_eq!(graph.graphlet_census(), total);
+        }
+    }
+
+    #[test]
+    fn graphlets_of_small_graphs() {
+        let cycle = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 0)], 4);
+        let expected = GraphletCounts {
+            cycles: 1,
+            ..GraphletCounts::default()
+        };
+        assert_eq!(cycle.graphlet_census(), expected);
+
+        // Self-loops and parallel edges are ignored
+        let star = Graph::from_edgelist_with_policy(
+            vec![(0, 1), (0, 2), (0, 3), (0, 3), (1, 1)],
+            4,
+            GraphPolicy::Multigraph,
+        );
+        let expected = GraphletCounts {
+            stars: 1,
+            ..GraphletCounts::default()
+        };
+        assert_eq!(star.graphlet_census(), expected);
+    }
+}
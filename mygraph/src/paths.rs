@@ -0,0 +1,506 @@
+use crate::graph::Graph;
+use std::collections::VecDeque;
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Result of a breadth-first search that keeps track of all shortest paths from `source`
+pub struct ShortestPathTree {
+    pub source: usize,
+    // Reached vertices in order of non-decreasing distance
+    pub order: Vec<usize>,
+    // Vertices directly before each vertex on some shortest path from the source
+    pub predecessors: Vec<Vec<usize>>,
+    // Number of shortest paths from the source to each vertex
+    pub path_counts: Vec<usize>,
+    // Distance from the source, usize::MAX if unreachable
+    pub distances: Vec<usize>,
+}
+
+pub fn single_source_shortest_path(graph: &Graph, s: usize) -> ShortestPathTree {
+    let mut distances = vec![usize::MAX; graph.vertices];
+    let mut shortest_paths = vec![0; graph.vertices];
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); graph.vertices];
+
+    let mut queue = VecDeque::new();
+    let mut stack = Vec::new();
+
+    distances[s] = 0;
+    shortest_paths[s] = 1;
+    queue.push_back(s);
+
+    while let Some(v) = queue.pop_front() {
+        stack.push(v);
+        for &w in &graph.adj_list[v] {
+            // Path discovery
+            if distances[w] == usize::MAX {
+                queue.push_back(w);
+                distances[w] = distances[v] + 1;
+            }
+            // Path counting
+            if distances[w] == distances[v] + 1 {
+                shortest_paths[w] += shortest_paths[v];
+                predecessors[w].push(v);
+            }
+        }
+    }
+
+    ShortestPathTree {
+        source: s,
+        order: stack,
+        predecessors,
+        path_counts: shortest_paths,
+        distances,
+    }
+}
+
+// Distances from `start` to every vertex, usize::MAX if unreachable
+pub fn bfs_shortest_paths(graph: &Graph, start: usize) -> Vec<usize> {
+    let mut distances = vec![usize::MAX; graph.vertices];
+    let mut queue = VecDeque::new();
+
+    distances[start] = 0;
+    queue.push_back(start);
+
+    while let Some(node) = queue.pop_front() {
+        for &neighbor in &graph.adj_list[node] {
+            if distances[neighbor] == usize::MAX {
+                distances[neighbor] = distances[node] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+impl ShortestPathTree {
+    // Every shortest path from the source to `target`, each starting at the source.
+    // The number of paths can grow exponentially with the distance.
+    pub fn paths_to(&self, target: usize) -> Vec<Vec<usize>> {
+        if self.distances[target] == usize::MAX {
+            return Vec::new();
+        }
+
+        let mut paths = Vec::new();
+        // Depth-first walk back along the predecessor lists
+        let mut stack = vec![vec![target]];
+        while let Some(path) = stack.pop() {
+            let last = *path.last().unwrap();
+            if last == self.source {
+                let mut path = path;
+                path.reverse();
+                paths.push(path);
+                continue;
+            }
+            let mut seen = Vec::new();
+            for &p in &self.predecessors[last] {
+                // Parallel edges list the same predecessor more than once
+                if seen.contains(&p) {
+                    continue;
+                }
+                seen.push(p);
+                let mut extended = path.clone();
+                extended.push(p);
+                stack.push(extended);
+            }
+        }
+
+        paths
+    }
+}
+
+// All-pairs hop distances stored row-major as u16
+pub struct DistanceMatrix {
+    pub vertices: usize,
+    pub data: Vec<u16>,
+}
+
+impl DistanceMatrix {
+    // Marks pairs of vertices without a path between them
+    pub const UNREACHABLE: u16 = u16::MAX;
+
+    pub fn row(&self, src: usize) -> &[u16] {
+        &self.data[src * self.vertices..(src + 1) * self.vertices]
+    }
+
+    // Distance between two vertices, None if unreachable
+    pub fn get(&self, src: usize, dest: usize) -> Option<u16> {
+        let d = self.data[src * self.vertices + dest];
+        (d != Self::UNREACHABLE).then_some(d)
+    }
+}
+
+impl Graph {
+    // Breadth-first search from `src` with path counts and predecessor lists
+    pub fn shortest_path_tree(&self, src: usize) -> ShortestPathTree {
+        single_source_shortest_path(self, src)
+    }
+
+    // One shortest path from `src` to `dest` as a vertex sequence, None if unreachable
+    pub fn shortest_path(&self, src: usize, dest: usize) -> Option<Vec<usize>> {
+        let mut parent = vec![usize::MAX; self.vertices];
+        let mut queue = VecDeque::new();
+
+        parent[src] = src;
+        queue.push_back(src);
+
+        'search: while let Some(node) = queue.pop_front() {
+            if node == dest {
+                break;
+            }
+            for &neighbor in &self.adj_list[node] {
+                if parent[neighbor] == usize::MAX {
+                    parent[neighbor] = node;
+                    if neighbor == dest {
+                        break 'search;
+                    }
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        if parent[dest] == usize::MAX {
+            return None;
+        }
+        let mut path = vec![dest];
+        let mut node = dest;
+        while node != src {
+            node = parent[node];
+            path.push(node);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    // Every shortest path from `src` to `dest`
+    pub fn all_shortest_paths(&self, src: usize, dest: usize) -> Vec<Vec<usize>> {
+        single_source_shortest_path(self, src).paths_to(dest)
+    }
+
+    // Hop distances between all pairs of vertices, one breadth-first search per row in parallel
+    pub fn distance_matrix(&self) -> DistanceMatrix {
+        let n = self.vertices;
+        let mut data = vec![DistanceMatrix::UNREACHABLE; n * n];
+
+        data.par_chunks_mut(n.max(1))
+            .enumerate()
+            .for_each(|(s, row)| {
+                let distances = bfs_shortest_paths(self, s);
+                for (cell, d) in row.iter_mut().zip(distances) {
+                    if d != usize::MAX {
+                        *cell = u16::try_from(d)
+                            .ok()
+                            .filter(|&d| d != DistanceMatrix::UNREACHABLE)
+                            .expect("distance does not fit into u16");
+                    }
+                }
+            });
+
+        DistanceMatrix { vertices: n, data }
+    }
+}
+
+// Largest finite distance from `start`, the farthest vertex and all distances
+fn eccentricity_from(graph: &Graph, start: usize) -> (usize, usize, Vec<usize>) {
+    let distances = bfs_shortest_paths(graph, start);
+    let (farthest, eccentricity) = distances
+        .iter()
+        .enumerate()
+        .filter(|&(_, &d)| d != usize::MAX)
+        .max_by_key(|&(_, &d)| d)
+        .map(|(v, &d)| (v, d))
+        .unwrap_or((start, 0));
+    (eccentricity, farthest, distances)
+}
+
+// Exact diameter of the component containing `start` with the iFUB algorithm
+// (Crescenzi et al., 2013). Starts from the midpoint of a double sweep and only
+// needs the eccentricities of the outermost BFS levels until the bounds meet.
+fn ifub_component(graph: &Graph, start: usize) -> usize {
+    let (_, a, _) = eccentricity_from(graph, start);
+    let (sweep, b, from_a) = eccentricity_from(graph, a);
+
+    // Walk half way back from b towards a to find a central vertex
+    let mut u = b;
+    for _ in 0..sweep / 2 {
+        u = *graph.adj_list[u]
+            .iter()
+            .find(|&&w| from_a[w] + 1 == from_a[u])
+            .expect("predecessor on a shortest path");
+    }
+
+    let distances = bfs_shortest_paths(graph, u);
+    let ecc_u = distances
+        .iter()
+        .copied()
+        .filter(|&d| d != usize::MAX)
+        .max()
+        .unwrap_or(0);
+    let mut fringes = vec![Vec::new(); ecc_u + 1];
+    for (v, &d) in distances.iter().enumerate() {
+        if d != usize::MAX {
+            fringes[d].push(v);
+        }
+    }
+
+    let mut lower = ecc_u.max(sweep);
+    let mut i = ecc_u;
+    // Every vertex closer than i to u has eccentricity at most 2 * (i - 1)
+    while 2 * i > lower {
+        let fringe_max = fringes[i]
+            .par_iter()
+            .map(|&v| eccentricity_from(graph, v).0)
+            .max()
+            .unwrap_or(0);
+        lower = lower.max(fringe_max);
+        if lower > 2 * (i - 1) {
+            break;
+        }
+        i -= 1;
+    }
+
+    lower
+}
+
+impl Graph {
+    // Largest distance from every vertex to any vertex it can reach
+    pub fn eccentricity(&self) -> Vec<usize> {
+        (0..self.vertices)
+            .into_par_iter()
+            .map(|node| eccentricity_from(self, node).0)
+            .collect()
+    }
+
+    // Largest eccentricity; for a disconnected graph the largest over all components
+    pub fn diameter(&self) -> usize {
+        self.eccentricity().into_iter().max().unwrap_or(0)
+    }
+
+    // Smallest eccentricity
+    pub fn radius(&self) -> usize {
+        self.eccentricity().into_iter().min().unwrap_or(0)
+    }
+
+    // Vertices whose eccentricity equals the radius
+    pub fn center(&self) -> Vec<usize> {
+        let eccentricity = self.eccentricity();
+        let radius = eccentricity.iter().copied().min().unwrap_or(0);
+        (0..self.vertices)
+            .filter(|&v| eccentricity[v] == radius)
+            .collect()
+    }
+
+    // Vertices whose eccentricity equals the diameter
+    pub fn periphery(&self) -> Vec<usize> {
+        let eccentricity = self.eccentricity();
+        let diameter = eccentricity.iter().copied().max().unwrap_or(0);
+        (0..self.vertices)
+            .filter(|&v| eccentricity[v] == diameter)
+            .collect()
+    }
+
+    // Mean distance over all ordered pairs of distinct vertices joined by a path,
+    // NaN if there are none
+    pub fn average_path_length(&self) -> f64 {
+        let (total, pairs) = (0..self.vertices)
+            .into_par_iter()
+            .map(|node| {
+                bfs_shortest_paths(self, node)
+                    .into_iter()
+                    .filter(|&d| d != usize::MAX && d > 0)
+                    .fold((0, 0), |(total, pairs), d| (total + d, pairs + 1))
+            })
+            .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+        if pairs > 0 {
+            total as f64 / pairs as f64
+        } else {
+            f64::NAN
+        }
+    }
+
+    // Lower bound on the diameter of the component of `start` from two breadth-first
+    // searches: the eccentricity of the vertex farthest from `start`
+    pub fn diameter_double_sweep(&self, start: usize) -> usize {
+        let (_, farthest, _) = eccentricity_from(self, start);
+        eccentricity_from(self, farthest).0
+    }
+
+    // Exact diameter with iFUB, usually far fewer breadth-first searches than `diameter`.
+    // iFUB's bounds need symmetric distances, so a directed graph gets `diameter`.
+    pub fn diameter_ifub(&self) -> usize {
+        if self.is_directed() {
+            return self.diameter();
+        }
+        let membership = self.connected_components();
+        let components = membership.iter().map(|&c| c + 1).max().unwrap_or(0);
+
+        // Start each component from its highest-degree vertex
+        let mut starts = vec![usize::MAX; components];
+        for (v, &c) in membership.iter().enumerate() {
+            if starts[c] == usize::MAX || self.adj_list[v].len() > self.adj_list[starts[c]].len() {
+                starts[c] = v;
+            }
+        }
+
+        starts
+            .into_iter()
+            .map(|start| ifub_component(self, start))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::testutil::{self, random_graph};
+    use rand::Rng;
+
+    // All-pairs distances by Floyd-Warshall, usize::MAX if unreachable
+    fn floyd_warshall(graph: &Graph) -> Vec<Vec<usize>> {
+        let n = graph.vertices;
+        let mut d = vec![vec![usize::MAX; n]; n];
+        for (u, row) in d.iter_mut().enumerate() {
+            row[u] = 0;
+            for &w in graph.successors(u) {
+                row[w] = row[w].min(1);
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    if d[i][k] != usize::MAX && d[k][j] != usize::MAX {
+                        d[i][j] = d[i][j].min(d[i][k] + d[k][j]);
+                    }
+                }
+            }
+        }
+        d
+    }
+
+    // Every simple path from `path`'s last vertex to `dest`
+    fn simple_paths(graph: &Graph, path: &mut Vec<usize>, dest: usize, out: &mut Vec<Vec<usize>>) {
+        let last = *path.last().unwrap();
+        if last == dest {
+            out.push(path.clone());
+            return;
+        }
+        for &w in graph.successors(last) {
+            if !path.contains(&w) {
+                path.push(w);
+                simple_paths(graph, path, dest, out);
+                path.pop();
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_paths_match_brute_force() {
+        let mut rng = testutil::rng();
+        for round in 0..200 {
+            let n = rng.random_range(1..8);
+            let m = rng.random_range(0..2 * n);
+            let graph = random_graph(&mut rng, n, m, round % 2 == 1);
+            let expected = floyd_warshall(&graph);
+            let matrix = graph.distance_matrix();
+            for (s, row) in expected.iter().enumerate() {
+                let tree = graph.shortest_path_tree(s);
+                for (t, &distance) in row.iter().enumerate() {
+                    assert_eq!(tree.distances[t], distance);
+                    assert_eq!(
+                        matrix.get(s, t).map(usize::from),
+                        (distance != usize::MAX).then_some(distance)
+                    );
+
+                    let mut all = Vec::new();
+                    simple_paths(&graph, &mut vec![s], t, &mut all);
+                    let mut shortest: Vec<Vec<usize>> = all
+                        .into_iter()
+                        .filter(|p| p.len() == distance.wrapping_add(1))
+                        .collect();
+                    shortest.sort();
+                    let mut found = graph.all_shortest_paths(s, t);
+                    found.sort();
+                    assert_eq!(found, shortest);
+                    assert_eq!(tree.path_counts[t], shortest.len());
+
+                    match graph.shortest_path(s, t) {
+                        Some(path) => assert!(shortest.contains(&path)),
+                        None => assert!(shortest.is_empty()),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn square_has_two_shortest_paths_across() {
+        let square = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 0)], 4);
+        assert_eq!(
+            square.all_shortest_paths(0, 2),
+            vec![vec![0, 3, 2], vec![0, 1, 2]]
+        );
+        assert_eq!(square.shortest_path(0, 0), Some(vec![0]));
+        let tree = square.shortest_path_tree(0);
+        assert_eq!(tree.order[0], 0);
+        assert_eq!(tree.order[3], 2);
+    }
+
+    #[test]
+    fn eccentricity_measures_match_brute_force() {
+        let mut rng = testutil::rng();
+        for round in 0..200 {
+            let n = rng.random_range(1..8);
+            let m = rng.random_range(0..2 * n);
+            let graph = random_graph(&mut rng, n, m, round % 2 == 1);
+            let expected: Vec<usize> = floyd_warshall(&graph)
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .copied()
+                        .filter(|&d| d != usize::MAX)
+                        .max()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(graph.eccentricity(), expected);
+            let diameter = *expected.iter().max().unwrap();
+            assert_eq!(graph.diameter(), diameter);
+            assert_eq!(graph.diameter_ifub(), diameter);
+            assert_eq!(graph.radius(), *expected.iter().min().unwrap());
+            assert!(graph.diameter_double_sweep(0) <= diameter);
+        }
+    }
+
+    #[test]
+    fn ifub_matches_diameter_on_larger_graphs() {
+        let mut rng = testutil::rng();
+        for _ in 0..50 {
+            let n = rng.random_range(20..80);
+            let m = rng.random_range(n..3 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            assert_eq!(graph.diameter_ifub(), graph.diameter());
+        }
+    }
+
+    #[test]
+    fn path_graph_distances() {
+        let path = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 4)], 5);
+        assert_eq!(path.eccentricity(), vec![4, 3, 2, 3, 4]);
+        assert_eq!(path.center(), vec![2]);
+        assert_eq!(path.periphery(), vec![0, 4]);
+        assert_eq!(path.diameter_double_sweep(2), 4);
+        // 2 * (4 * 1 + 3 * 2 + 2 * 3 + 1 * 4) / 20
+        assert!((path.average_path_length() - 2.0).abs() < 1e-12);
+        assert!(Graph::new(3).average_path_length().is_nan());
+        assert_eq!(Graph::new(0).diameter_ifub(), 0);
+    }
+
+    #[test]
+    fn ifub_on_directed_graph_matches_diameter() {
+        let graph = Graph::from_arclist(vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)], 5);
+        assert_eq!(graph.diameter(), 4);
+        assert_eq!(graph.diameter_ifub(), 4);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::graph::Graph;
+use std::io::{self, Write};
+
+// Tableau 10, cycled for categories
+const CATEGORY_PALETTE: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+// Anchors of the viridis colour map, interpolated for continuous values
+const GRADIENT: [(f64, f64, f64); 5] = [
+    (68.0, 1.0, 84.0),
+    (59.0, 82.0, 139.0),
+    (33.0, 145.0, 140.0),
+    (94.0, 201.0, 98.0),
+    (253.0, 231.0, 37.0),
+];
+
+// How vertices are coloured in an SVG rendering
+#[derive(Clone, Copy, Debug)]
+pub enum NodeColor<'a> {
+    // The same colour for every vertex
+    Fixed(&'a str),
+    // A per-vertex value mapped onto a colour gradient, e.g. a centrality
+    Gradient(&'a [f64]),
+    // A per-vertex category, e.g. community membership
+    Categories(&'a [usize]),
+}
+
+// Settings of an SVG rendering
+#[derive(Clone, Debug)]
+pub struct Svg<'a> {
+    pub width: f64,
+    pub height: f64,
+    pub margin: f64,
+    // Radius of the vertex with the smallest and the largest size value
+    pub min_radius: f64,
+    pub max_radius: f64,
+    // Per-vertex values mapped onto the radius, all vertices get min_radius if None
+    pub sizes: Option<&'a [f64]>,
+    pub colors: NodeColor<'a>,
+    pub edge_color: &'a str,
+    pub edge_width: f64,
+    // Write vertex labels next to the vertices
+    pub labels: bool,
+}
+
+impl Default for Svg<'_> {
+    fn default() -> Self {
+        Svg {
+            width: 800.0,
+            height: 800.0,
+            margin: 20.0,
+            min_radius: 3.0,
+            max_radius: 12.0,
+            sizes: None,
+            colors: NodeColor::Fixed(CATEGORY_PALETTE[0]),
+            edge_color: "#999999",
+            edge_width: 0.8,
+            labels: false,
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Values rescaled to [0, 1]; all zero if they are constant. NaN counts as the minimum.
+fn normalize(values: &[f64]) -> Vec<f64> {
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|&v| {
+            if max > min && v.is_finite() {
+                (v - min) / (max - min)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+fn gradient_color(t: f64) -> String {
+    let scaled = t.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f64;
+    let i = (scaled.floor() as usize).min(GRADIENT.len() - 2);
+    let f = scaled - i as f64;
+    let (a, b) = (GRADIENT[i], GRADIENT[i + 1]);
+    let mix = |x: f64, y: f64| (x + (y - x) * f).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(a.0, b.0),
+        mix(a.1, b.1),
+        mix(a.2, b.2)
+    )
+}
+
+// Settings of a Graphviz DOT export
+#[derive(Clone, Debug, Default)]
+pub struct Dot {
+    graph: Vec<(String, String)>,
+    node_defaults: Vec<(String, String)>,
+    edge_defaults: Vec<(String, String)>,
+    vertex_attributes: Vec<(String, Vec<String>)>,
+}
+
+impl Dot {
+    pub fn new() -> Self {
+        Dot::default()
+    }
+
+    // Attribute of the whole graph, e.g. ("layout", "neato")
+    pub fn graph_attr(mut self, name: &str, value: &str) -> Self {
+        self.graph.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // Default attribute of all vertices, e.g. ("shape", "circle")
+    pub fn node_attr(mut self, name: &str, value: &str) -> Self {
+        self.node_defaults
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // Default attribute of all edges, e.g. ("color", "gray")
+    pub fn edge_attr(mut self, name: &str, value: &str) -> Self {
+        self.edge_defaults
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // Per-vertex attribute, one value per vertex
+    pub fn vertex_attr<T: ToString>(mut self, name: &str, values: &[T]) -> Self {
+        let values = values.iter().map(|v| v.to_string()).collect();
+        self.vertex_attributes.push((name.to_string(), values));
+        self
+    }
+
+    // Vertex positions from a layout, pinned with '!' so that neato keeps them
+    pub fn positions(self, layout: &[(f64, f64)]) -> Self {
+        let positions: Vec<String> = layout
+            .iter()
+            .map(|(x, y)| format!("{},{}!", x, y))
+            .collect();
+        self.vertex_attr("pos", &positions)
+    }
+}
+
+fn dot_id(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn dot_attributes(attributes: &[(String, String)]) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!("{}={}", dot_id(name), dot_id(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Graph {
+    // Draw the graph at the given vertex positions, scaled to fit the canvas
+    pub fn write_svg<W: Write>(
+        &self,
+        layout: &[(f64, f64)],
+        svg: &Svg,
+        mut writer: W,
+    ) -> io::Result<()> {
+        assert_eq!(layout.len(), self.vertices, "one position per vertex");
+
+        let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+        let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for &(x, y) in layout {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+        let pad = svg.margin + svg.max_radius;
+        let scale = ((svg.width - 2.0 * pad) / (max_x - min_x))
+            .min((svg.height - 2.0 * pad) / (max_y - min_y));
+        let scale = if scale.is_finite() { scale } else { 1.0 };
+        let point = |v: usize| {
+            (
+                pad + (layout[v].0 - min_x) * scale,
+                pad + (layout[v].1 - min_y) * scale,
+            )
+        };
+
+        let radii: Vec<f64> = match svg.sizes {
+            Some(sizes) => normalize(sizes)
+                .into_iter()
+                .map(|t| svg.min_radius + t * (svg.max_radius - svg.min_radius))
+                .collect(),
+            None => vec![svg.min_radius; self.vertices],
+        };
+        let colors: Vec<String> = match svg.colors {
+            NodeColor::Fixed(color) => vec![color.to_string(); self.vertices],
+            NodeColor::Gradient(values) => {
+                normalize(values).into_iter().map(gradient_color).collect()
+            }
+            NodeColor::Categories(categories) => categories
+                .iter()
+                .map(|&c| CATEGORY_PALETTE[c % CATEGORY_PALETTE.len()].to_string())
+                .collect(),
+        };
+
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            svg.width, svg.height, svg.width, svg.height
+        )?;
+        writeln!(
+            writer,
+            r#"<g stroke="{}" stroke-width="{}" stroke-opacity="0.6">"#,
+            escape(svg.edge_color),
+            svg.edge_width
+        )?;
+        for (u, v) in self.edges() {
+            if u == v {
+                continue;
+            }
+            let ((x1, y1), (x2, y2)) = (point(u), point(v));
+            writeln!(
+                writer,
+                r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}"/>"#,
+                x1, y1, x2, y2
+            )?;
+        }
+        writeln!(writer, "</g>")?;
+
+        writeln!(writer, r##"<g stroke="#ffffff" stroke-width="0.5">"##)?;
+        for v in 0..self.vertices {
+            let (x, y) = point(v);
+            writeln!(
+                writer,
+                r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"><title>{}</title></circle>"#,
+                x,
+                y,
+                radii[v],
+                escape(&colors[v]),
+                escape(&self.label(v))
+            )?;
+        }
+        writeln!(writer, "</g>")?;
+
+        if svg.labels {
+            writeln!(writer, r#"<g font-family="sans-serif" font-size="10">"#)?;
+            for (v, radius) in radii.iter().enumerate() {
+                let (x, y) = point(v);
+                writeln!(
+                    writer,
+                    r#"<text x="{:.2}" y="{:.2}">{}</text>"#,
+                    x + radius + 1.0,
+                    y + 3.0,
+                    escape(&self.label(v))
+                )?;
+            }
+            writeln!(writer, "</g>")?;
+        }
+
+        writeln!(writer, "</svg>")
+    }
+
+    // Export the graph in Graphviz DOT format, vertices named by their labels.
+    // A directed graph becomes a digraph.
+    pub fn write_dot<W: Write>(&self, dot: &Dot, mut writer: W) -> io::Result<()> {
+        let (kind, connector) = if self.is_directed() {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        writeln!(writer, "{} {{", kind)?;
+        if !dot.graph.is_empty() {
+            writeln!(writer, "  graph [{}];", dot_attributes(&dot.graph))?;
+        }
+        if !dot.node_defaults.is_empty() {
+            writeln!(writer, "  node [{}];", dot_attributes(&dot.node_defaults))?;
+        }
+        if !dot.edge_defaults.is_empty() {
+            writeln!(writer, "  edge [{}];", dot_attributes(&dot.edge_defaults))?;
+        }
+
+        for v in 0..self.vertices {
+            let attributes: Vec<(String, String)> = dot
+                .vertex_attributes
+                .iter()
+                .map(|(name, values)| (name.clone(), values[v].clone()))
+                .collect();
+            if attributes.is_empty() {
+                writeln!(writer, "  {};", dot_id(&self.label(v)))?;
+            } else {
+                writeln!(
+                    writer,
+                    "  {} [{}];",
+                    dot_id(&self.label(v)),
+                    dot_attributes(&attributes)
+                )?;
+            }
+        }
+        for (u, v) in self.edges() {
+            writeln!(
+                writer,
+                "  {} {} {};",
+                dot_id(&self.label(u)),
+                connector,
+                dot_id(&self.label(v))
+            )?;
+        }
+
+        writeln!(writer, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gradient_color, normalize, Dot, NodeColor, Svg};
+    use crate::graph::Graph;
+
+    fn svg(graph: &Graph, layout: &[(f64, f64)], settings: &Svg) -> String {
+        let mut out = Vec::new();
+        graph.write_svg(layout, settings, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn normalize_and_gradient_ends() {
+        assert_eq!(
+            normalize(&[2.0, 4.0, f64::NAN, 3.0]),
+            vec![0.0, 1.0, 0.0, 0.5]
+        );
+        assert_eq!(normalize(&[5.0, 5.0]), vec![0.0, 0.0]);
+        assert_eq!(gradient_color(0.0), "#440154");
+        assert_eq!(gradient_color(1.0), "#fde725");
+        assert_eq!(gradient_color(2.0), "#fde725");
+    }
+
+    #[test]
+    fn svg_scales_the_layout_into_the_canvas() {
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2)], 3).with_labels(vec![
+            "a<b".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+        ]);
+        let layout = [(0.0, 0.0), (1.0, 1.0), (2.0, 0.0)];
+        let settings = Svg {
+            width: 100.0,
+            height: 100.0,
+            margin: 10.0,
+            min_radius: 2.0,
+            max_radius: 5.0,
+            sizes: Some(&[0.0, 1.0, 2.0]),
+            colors: NodeColor::Categories(&[0, 1, 10]),
+            labels: true,
+            ..Svg::default()
+        };
+        let out = svg(&graph, &layout, &settings);
+        assert!(out.starts_with("<svg "));
+        assert!(out.trim_end().ends_with("</svg>"));
+        assert_eq!(out.matches("<line ").count(), 2);
+        assert_eq!(out.matches("<circle ").count(), 3);
+        // 15 of padding, then 35 units per layout unit
+        assert!(out.contains(r#"<line x1="15.00" y1="15.00" x2="50.00" y2="50.00"/>"#));
+        assert!(out.contains(r##"cx="85.00" cy="15.00" r="5.00" fill="#4e79a7""##));
+        assert!(out.contains(r#"r="3.50""#));
+        assert!(out.contains("<title>a&lt;b</title>"));
+        assert!(out.contains(">a&lt;b</text>"));
+    }
+
+    #[test]
+    fn svg_of_a_single_vertex() {
+        let out = svg(&Graph::new(1), &[(3.0, 3.0)], &Svg::default());
+        assert!(out.contains(r#"<circle cx="32.00" cy="32.00" r="3.00""#));
+    }
+
+    #[test]
+    fn dot_attributes_are_quoted() {
+        let graph = Graph::from_edgelist(vec![(0, 1)], 2)
+            .with_labels(vec!["say \"hi\"".to_string(), "b".to_string()]);
+        let dot = Dot::new()
+            .graph_attr("layout", "neato")
+            .node_attr("shape", "circle")
+            .edge_attr("color", "gray")
+            .vertex_attr("group", &[1, 2])
+            .positions(&[(0.0, 1.5), (2.0, 0.0)]);
+        let mut out = Vec::new();
+        graph.write_dot(&dot, &mut out).unwrap();
+        let expected = r#"graph {
+  graph ["layout"="neato"];
+  node ["shape"="circle"];
+  edge ["color"="gray"];
+  "say \"hi\"" ["group"="1", "pos"="0,1.5!"];
+  "b" ["group"="2", "pos"="2,0!"];
+  "say \"hi\"" -- "b";
+}
+"#;
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    fn dot(graph: &Graph) -> String {
+        let mut out = Vec::new();
+        graph.write_dot(&Dot::new(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn dot_follows_direction() {
+        let undirected = dot(&Graph::from_edgelist(vec![(0, 1)], 2));
+        assert!(undirected.starts_with("graph {"));
+        assert!(undirected.contains(r#""0" -- "1";"#));
+
+        let directed = dot(&Graph::from_arclist(vec![(1, 0)], 2));
+        assert!(directed.starts_with("digraph {"));
+        assert!(directed.contains(r#""1" -> "0";"#));
+        assert!(!directed.contains("--"));
+    }
+}
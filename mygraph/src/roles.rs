@@ -0,0 +1,476 @@
+extern crate nalgebra as na;
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use na::DMatrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// CONCOR stops iterating once every correlation is this close to +-1
+const CONCOR_CONVERGENCE: f64 = 0.9999;
+const CONCOR_MAX_ITERS: usize = 100;
+
+// Ideal block types of generalised blockmodelling (Doreian, Batagelj & Ferligoj)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockType {
+    // No ties between the two positions
+    Null,
+    // Every possible tie between the two positions
+    Complete,
+    // Every vertex of either position has at least one tie to the other
+    Regular,
+    // Anything goes, never counts as an error
+    Free,
+}
+
+// Partition found by `fit_blockmodel` and its number of inconsistencies with the image
+#[derive(Clone, Debug)]
+pub struct Blockmodel {
+    pub membership: Vec<usize>,
+    pub error: usize,
+}
+
+// Pearson correlation of the columns of a matrix; constant columns correlate 0
+fn column_correlation(matrix: &DMatrix<f64>) -> DMatrix<f64> {
+    let (rows, cols) = matrix.shape();
+    let mut centered = matrix.clone();
+    for mut column in centered.column_iter_mut() {
+        let mean = column.sum() / rows as f64;
+        column.add_scalar_mut(-mean);
+        let norm = column.norm();
+        if norm > 0.0 {
+            column /= norm;
+        }
+    }
+    let mut correlation = centered.transpose() * &centered;
+    for i in 0..cols {
+        correlation[(i, i)] = 1.0;
+    }
+    correlation
+}
+
+// Inconsistencies of the partition with the image, from the number of ties every
+// vertex has into every position
+fn blockmodel_error(image: &[Vec<BlockType>], links: &[Vec<usize>], membership: &[usize]) -> usize {
+    let k = image.len();
+    let mut sizes = vec![0usize; k];
+    let mut ties = vec![vec![0; k]; k];
+    // Vertices of each position without any tie into each position
+    let mut isolated = vec![vec![0; k]; k];
+    for (v, &r) in membership.iter().enumerate() {
+        sizes[r] += 1;
+        for c in 0..k {
+            ties[r][c] += links[v][c];
+            if links[v][c] == 0 {
+                isolated[r][c] += 1;
+            }
+        }
+    }
+
+    let mut error = 0;
+    for r in 0..k {
+        for c in 0..k {
+            let possible = if r == c {
+                sizes[r] * sizes[r].saturating_sub(1)
+            } else {
+                sizes[r] * sizes[c]
+            };
+            error += match image[r][c] {
+                BlockType::Null => ties[r][c],
+                BlockType::Complete => possible - ties[r][c],
+                // A row or column without ties could be filled by one tie per cell
+                BlockType::Regular if sizes[r] > 0 && sizes[c] > 0 => {
+                    isolated[r][c] * sizes[c] + isolated[c][r] * sizes[r]
+                }
+                BlockType::Regular | BlockType::Free => 0,
+            };
+        }
+    }
+    error
+}
+
+impl Graph {
+    // Correlation between the adjacency rows of every pair of vertices; 1 for
+    // structurally equivalent vertices with the same neighbours
+    pub fn structural_correlation(&self) -> DMatrix<f64> {
+        let adjacency = simple_adjacency(self);
+        let matrix = DMatrix::from_fn(self.vertices, self.vertices, |i, j| {
+            adjacency[j].binary_search(&i).is_ok() as u8 as f64
+        });
+        column_correlation(&matrix)
+    }
+
+    // Euclidean distance between the adjacency rows of every pair of vertices,
+    // leaving out the entries of the pair itself (Burt); 0 for structurally
+    // equivalent vertices
+    pub fn structural_distance(&self) -> DMatrix<f64> {
+        let adjacency = simple_adjacency(self);
+        let n = self.vertices;
+        let mut distance = DMatrix::zeros(n, n);
+        let rows: Vec<(usize, usize, f64)> = (0..n)
+            .into_par_iter()
+            .flat_map_iter(|i| {
+                let adjacency = &adjacency;
+                (i + 1..n).map(move |j| {
+                    // Neighbours of exactly one of i and j, other than i and j
+                    let (a, b) = (&adjacency[i], &adjacency[j]);
+                    let differing = a
+                        .iter()
+                        .filter(|&&w| w != j && b.binary_search(&w).is_err())
+                        .count()
+                        + b.iter()
+                            .filter(|&&w| w != i && a.binary_search(&w).is_err())
+                            .count();
+                    // Rows and columns both differ in those entries
+                    (i, j, (2.0 * differing as f64).sqrt())
+                })
+            })
+            .collect();
+        for (i, j, d) in rows {
+            distance[(i, j)] = d;
+            distance[(j, i)] = d;
+        }
+        distance
+    }
+
+    // Regular equivalence similarity in [0, 1] (REGE-style): two vertices are
+    // similar when the successors of each can be matched with similar successors of
+    // the other, and likewise their predecessors. Starts from all ones and refines
+    // `iterations` times. Without direction every vertex that has a neighbour is
+    // regularly equivalent to every other, so this is meant for directed graphs.
+    pub fn regular_equivalence(&self, iterations: usize) -> DMatrix<f64> {
+        let n = self.vertices;
+        let simple = |neighbors: &[usize], v: usize| {
+            let mut neighbors = neighbors.to_vec();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            neighbors.retain(|&w| w != v);
+            neighbors
+        };
+        let out: Vec<Vec<usize>> = (0..n).map(|v| simple(self.successors(v), v)).collect();
+        let into: Vec<Vec<usize>> = (0..n).map(|v| simple(self.predecessors(v), v)).collect();
+
+        let mut similarity = DMatrix::from_element(n, n, 1.0);
+        for _ in 0..iterations {
+            let previous = &similarity;
+            let best_match = |from: &[usize], to: &[usize]| -> f64 {
+                from.iter()
+                    .map(|&k| to.iter().map(|&m| previous[(k, m)]).fold(0.0, f64::max))
+                    .sum()
+            };
+            let values: Vec<f64> = (0..n * n)
+                .into_par_iter()
+                .map(|cell| {
+                    let (i, j) = (cell % n, cell / n);
+                    let ties = out[i].len() + out[j].len() + into[i].len() + into[j].len();
+                    if ties == 0 {
+                        return 1.0;
+                    }
+                    let matched = best_match(&out[i], &out[j])
+                        + best_match(&out[j], &out[i])
+                        + best_match(&into[i], &into[j])
+                        + best_match(&into[j], &into[i]);
+                    matched / ties as f64
+                })
+                .collect();
+            similarity = DMatrix::from_vec(n, n, values);
+        }
+        similarity
+    }
+
+    // CONCOR (Breiger, Boorman & Arabie): correlate the adjacency columns, correlate
+    // the correlations until they are all +-1, and split the vertices by sign.
+    // Each position is split again, `depth` times, for up to 2^depth positions,
+    // numbered in order of their lowest vertex.
+    pub fn concor(&self, depth: usize) -> Vec<usize> {
+        let adjacency = simple_adjacency(self);
+        let n = self.vertices;
+        let mut positions: Vec<Vec<usize>> = vec![(0..n).collect()];
+
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for members in positions {
+                if members.len() < 2 {
+                    next.push(members);
+                    continue;
+                }
+                // Ties of the members with every vertex, one column per member
+                let matrix = DMatrix::from_fn(n, members.len(), |i, c| {
+                    adjacency[members[c]].binary_search(&i).is_ok() as u8 as f64
+                });
+                let mut correlation = column_correlation(&matrix);
+                for _ in 0..CONCOR_MAX_ITERS {
+                    if correlation.iter().all(|r| r.abs() >= CONCOR_CONVERGENCE) {
+                        break;
+                    }
+                    correlation = column_correlation(&correlation);
+                }
+
+                let (first, second): (Vec<usize>, Vec<usize>) =
+                    (0..members.len()).partition(|&c| correlation[(0, c)] >= 0.0);
+                let first: Vec<usize> = first.into_iter().map(|c| members[c]).collect();
+                let second: Vec<usize> = second.into_iter().map(|c| members[c]).collect();
+                next.push(first);
+                if !second.is_empty() {
+                    next.push(second);
+                }
+            }
+            positions = next;
+        }
+
+        positions.sort_by_key(|members| members.iter().min().copied());
+        let mut membership = vec![0; n];
+        for (p, members) in positions.iter().enumerate() {
+            for &v in members {
+                membership[v] = p;
+            }
+        }
+        membership
+    }
+
+    // Reduced image of a partition: the density of ties between every pair of
+    // positions, within a position among distinct vertices; NaN without pairs
+    pub fn block_image(&self, membership: &[usize]) -> DMatrix<f64> {
+        assert_eq!(membership.len(), self.vertices, "one position per vertex");
+        let adjacency = simple_adjacency(self);
+        let k = membership.iter().max().map_or(0, |&m| m + 1);
+        let mut sizes = vec![0.0; k];
+        let mut ties = DMatrix::<f64>::zeros(k, k);
+        for (v, neighbors) in adjacency.iter().enumerate() {
+            sizes[membership[v]] += 1.0;
+            for &w in neighbors {
+                ties[(membership[v], membership[w])] += 1.0;
+            }
+        }
+        DMatrix::from_fn(k, k, |r, c| {
+            let pairs = if r == c {
+                sizes[r] * (sizes[r] - 1.0)
+            } else {
+                sizes[r] * sizes[c]
+            };
+            if pairs > 0.0 {
+                ties[(r, c)] / pairs
+            } else {
+                f64::NAN
+            }
+        })
+    }
+
+    // Generalised blockmodelling: the partition into image.len() positions that
+    // best fits the ideal block types of the image, found by moving single vertices
+    // between positions while the error drops, from `restarts` random partitions.
+    // Self-loops, parallel edges and the direction of arcs are ignored.
+    pub fn fit_blockmodel(
+        &self,
+        image: &[Vec<BlockType>],
+        restarts: usize,
+        seed: u64,
+    ) -> Blockmodel {
+        let k = image.len();
+        assert!(k > 0, "the image needs at least one position");
+        assert!(
+            image.iter().all(|row| row.len() == k),
+            "the image must be square"
+        );
+        // `relocate` needs every tie in the lists of both of its ends
+        let adjacency = if self.is_directed() {
+            simple_adjacency(&Graph::from_edgelist(self.edges(), self.vertices))
+        } else {
+            simple_adjacency(self)
+        };
+        let n = self.vertices;
+
+        (0..restarts.max(1) as u64)
+            .into_par_iter()
+            .map(|restart| {
+                let mut rng = StdRng::seed_from_u64(seed.wrapping_add(restart));
+                let mut membership: Vec<usize> = (0..n).map(|_| rng.random_range(0..k)).collect();
+                let mut links = vec![vec![0; k]; n];
+                for (v, neighbors) in adjacency.iter().enumerate() {
+                    for &w in neighbors {
+                        links[v][membership[w]] += 1;
+                    }
+                }
+                let relocate = |links: &mut Vec<Vec<usize>>, v: usize, from: usize, to: usize| {
+                    for &w in &adjacency[v] {
+                        links[w][from] -= 1;
+                        links[w][to] += 1;
+                    }
+                };
+
+                let mut error = blockmodel_error(image, &links, &membership);
+                let mut improved = true;
+                while improved && error > 0 {
+                    improved = false;
+                    for v in 0..n {
+                        let from = membership[v];
+                        for to in (0..k).filter(|&to| to != from) {
+                            relocate(&mut links, v, from, to);
+                            membership[v] = to;
+                            let moved = blockmodel_error(image, &links, &membership);
+                            if moved < error {
+                                error = moved;
+                                improved = true;
+                                break;
+                            }
+                            relocate(&mut links, v, to, from);
+                            membership[v] = from;
+                        }
+                    }
+                }
+
+                Blockmodel { membership, error }
+            })
+            .min_by_key(|model| model.error)
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockType;
+    use crate::graph::Graph;
+    use crate::testutil::{self, random_graph};
+    use rand::Rng;
+
+    // Complete bipartite graph between {0, 1} and {2, 3, 4}
+    fn k23() -> Graph {
+        let mut edges = Vec::new();
+        for u in 0..2 {
+            for w in 2..5 {
+                edges.push((u, w));
+            }
+        }
+        Graph::from_edgelist(edges, 5)
+    }
+
+    // Blockmodel error of a partition computed cell by cell from the adjacency matrix
+    fn brute_force_error(graph: &Graph, image: &[Vec<BlockType>], membership: &[usize]) -> usize {
+        let n = graph.vertices;
+        let tie = |u: usize, w: usize| u != w && graph.has_edge(u, w);
+        let mut error = 0;
+        for (r, row) in image.iter().enumerate() {
+            for (c, &block) in row.iter().enumerate() {
+                let rows: Vec<usize> = (0..n).filter(|&v| membership[v] == r).collect();
+                let cols: Vec<usize> = (0..n).filter(|&v| membership[v] == c).collect();
+                let cells: Vec<(usize, usize)> = rows
+                    .iter()
+                    .flat_map(|&u| cols.iter().map(move |&w| (u, w)))
+                    .filter(|&(u, w)| u != w)
+                    .collect();
+                let ties = cells.iter().filter(|&&(u, w)| tie(u, w)).count();
+                error += match block {
+                    BlockType::Null => ties,
+                    BlockType::Complete => cells.len() - ties,
+                    BlockType::Regular if !rows.is_empty() && !cols.is_empty() => {
+                        let empty_rows = rows.iter().filter(|&&u| !cols.iter().any(|&w| tie(u, w)));
+                        let empty_cols = cols.iter().filter(|&&w| !rows.iter().any(|&u| tie(u, w)));
+                        empty_rows.count() * cols.len() + empty_cols.count() * rows.len()
+                    }
+                    _ => 0,
+                };
+            }
+        }
+        error
+    }
+
+    #[test]
+    fn structural_equivalence_of_a_complete_bipartite_graph() {
+        let graph = k23();
+        let correlation = graph.structural_correlation();
+        assert!((correlation[(0, 1)] - 1.0).abs() < 1e-12);
+        assert!((correlation[(2, 4)] - 1.0).abs() < 1e-12);
+        assert!((correlation[(0, 2)] + 1.0).abs() < 1e-12);
+
+        let distance = graph.structural_distance();
+        assert_eq!(distance[(0, 1)], 0.0);
+        assert_eq!(distance[(3, 4)], 0.0);
+        // 0 and 2 differ in their ties to 1, 3 and 4, in rows and columns
+        assert!((distance[(0, 2)] - 6f64.sqrt()).abs() < 1e-12);
+
+        assert_eq!(graph.concor(1), vec![0, 0, 1, 1, 1]);
+        let image = graph.block_image(&[0, 0, 1, 1, 1]);
+        assert_eq!(image[(0, 1)], 1.0);
+        assert_eq!(image[(1, 1)], 0.0);
+        let singletons = graph.block_image(&[0, 1, 1, 1, 1]);
+        assert!(singletons[(0, 0)].is_nan());
+    }
+
+    #[test]
+    fn structural_distance_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(2..12);
+            let m = rng.random_range(0..3 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            let distance = graph.structural_distance();
+            let tie = |u: usize, w: usize| (u != w && graph.has_edge(u, w)) as i32 as f64;
+            for i in 0..n {
+                for j in 0..n {
+                    let squares: f64 = (0..n)
+                        .filter(|&k| k != i && k != j)
+                        .map(|k| (tie(i, k) - tie(j, k)).powi(2) + (tie(k, i) - tie(k, j)).powi(2))
+                        .sum();
+                    assert!((distance[(i, j)] - squares.sqrt()).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn regular_equivalence_of_two_teams() {
+        // Bosses 0 and 3 with workers 1, 2 and 4, 5
+        let graph = Graph::from_arclist(vec![(0, 1), (0, 2), (3, 4), (3, 5)], 6);
+        let similarity = graph.regular_equivalence(5);
+        assert_eq!(similarity[(0, 3)], 1.0);
+        assert_eq!(similarity[(1, 5)], 1.0);
+        assert_eq!(similarity[(0, 1)], 0.0);
+        assert_eq!(similarity, similarity.transpose());
+    }
+
+    #[test]
+    fn blockmodel_fits_ideal_structures() {
+        let image = vec![
+            vec![BlockType::Null, BlockType::Complete],
+            vec![BlockType::Complete, BlockType::Null],
+        ];
+        let model = k23().fit_blockmodel(&image, 10, 1);
+        assert_eq!(model.error, 0);
+        assert_eq!(model.membership[0], model.membership[1]);
+        assert_ne!(model.membership[0], model.membership[2]);
+
+        let mut rng = testutil::rng();
+        let core_periphery = vec![
+            vec![BlockType::Complete, BlockType::Regular],
+            vec![BlockType::Regular, BlockType::Free],
+        ];
+        for _ in 0..30 {
+            let n = rng.random_range(2..9);
+            let m = rng.random_range(0..2 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            for image in [&image, &core_periphery] {
+                let model = graph.fit_blockmodel(image, 4, rng.random());
+                assert_eq!(
+                    model.error,
+                    brute_force_error(&graph, image, &model.membership)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn blockmodel_ignores_direction() {
+        let arcs = vec![(0, 1), (0, 2), (0, 3), (1, 2), (3, 4), (4, 5), (5, 0)];
+        let edges = arcs.clone();
+        let image = vec![
+            vec![BlockType::Complete, BlockType::Regular],
+            vec![BlockType::Regular, BlockType::Null],
+        ];
+        let directed = Graph::from_arclist(arcs, 6).fit_blockmodel(&image, 8, 7);
+        let undirected = Graph::from_edgelist(edges, 6).fit_blockmodel(&image, 8, 7);
+        assert_eq!(directed.membership, undirected.membership);
+        assert_eq!(directed.error, undirected.error);
+    }
+}
@@ -0,0 +1,523 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Comparisons of a hidden edge with a non-edge for the AUC estimate
+const AUC_SAMPLES: usize = 10_000;
+
+// Vertex similarity indices from the neighbourhoods of the two vertices
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Similarity {
+    // Common neighbours over all neighbours of either vertex
+    Jaccard,
+    // Common neighbours over the geometric mean of the degrees (Salton)
+    Cosine,
+    // Common neighbours weighted by 1 / ln(degree)
+    AdamicAdar,
+    // Common neighbours weighted by 1 / degree
+    ResourceAllocation,
+    // Product of the degrees
+    PreferentialAttachment,
+}
+
+impl Similarity {
+    pub const ALL: [Similarity; 5] = [
+        Similarity::Jaccard,
+        Similarity::Cosine,
+        Similarity::AdamicAdar,
+        Similarity::ResourceAllocation,
+        Similarity::PreferentialAttachment,
+    ];
+
+    // Only preferential attachment can score pairs without common neighbours
+    fn needs_common_neighbors(self) -> bool {
+        self != Similarity::PreferentialAttachment
+    }
+}
+
+// Quality of a similarity index at recovering hidden edges
+#[derive(Clone, Copy, Debug)]
+pub struct LinkPrediction {
+    // Probability that a hidden edge scores higher than a non-edge, ties count half
+    pub auc: f64,
+    // Share of hidden edges among the k best-scoring unconnected pairs
+    pub precision: f64,
+    pub hidden: usize,
+}
+
+// What a pair of vertices shares, enough to compute every index
+#[derive(Clone, Copy, Default)]
+struct Overlap {
+    common: usize,
+    adamic_adar: f64,
+    resource_allocation: f64,
+}
+
+impl Overlap {
+    fn add(&mut self, degree: usize) {
+        self.common += 1;
+        // A common neighbour has degree at least 2, so the logarithm is positive
+        self.adamic_adar += 1.0 / (degree as f64).ln();
+        self.resource_allocation += 1.0 / degree as f64;
+    }
+
+    fn score(&self, measure: Similarity, du: usize, dv: usize) -> f64 {
+        match measure {
+            Similarity::Jaccard => {
+                let union = du + dv - self.common;
+                if union > 0 {
+                    self.common as f64 / union as f64
+                } else {
+                    0.0
+                }
+            }
+            Similarity::Cosine => {
+                if du > 0 && dv > 0 {
+                    self.common as f64 / ((du * dv) as f64).sqrt()
+                } else {
+                    0.0
+                }
+            }
+            Similarity::AdamicAdar => self.adamic_adar,
+            Similarity::ResourceAllocation => self.resource_allocation,
+            Similarity::PreferentialAttachment => (du * dv) as f64,
+        }
+    }
+}
+
+fn overlap(adjacency: &[Vec<usize>], u: usize, v: usize) -> Overlap {
+    let (a, b) = (&adjacency[u], &adjacency[v]);
+    let mut overlap = Overlap::default();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                overlap.add(adjacency[a[i]].len());
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    overlap
+}
+
+// Overlap of u with every unconnected vertex v > u it shares a neighbour with
+fn two_hop_overlaps(adjacency: &[Vec<usize>], u: usize) -> HashMap<usize, Overlap> {
+    let mut overlaps: HashMap<usize, Overlap> = HashMap::new();
+    for &w in &adjacency[u] {
+        for &v in &adjacency[w] {
+            if v > u && adjacency[u].binary_search(&v).is_err() {
+                overlaps.entry(v).or_default().add(adjacency[w].len());
+            }
+        }
+    }
+    overlaps
+}
+
+// Unconnected pair ranked for link prediction; greater is better: higher score,
+// then lower pair
+#[derive(Clone, Copy)]
+struct Ranked {
+    score: f64,
+    u: usize,
+    v: usize,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then((other.u, other.v).cmp(&(self.u, self.v)))
+    }
+}
+
+// The `k` best of the ranked pairs, kept in a min-heap of the worst one
+struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<Ranked>>,
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        TopK {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    fn push(&mut self, ranked: Ranked) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(ranked));
+        } else if self.heap.peek().is_some_and(|worst| ranked > worst.0) {
+            self.heap.pop();
+            self.heap.push(Reverse(ranked));
+        }
+    }
+
+    fn merge(mut self, other: TopK) -> TopK {
+        for Reverse(ranked) in other.heap {
+            self.push(ranked);
+        }
+        self
+    }
+}
+
+// The `k` best-scoring unconnected pairs u < v that the index can rank above zero,
+// best first: the pairs with a common neighbour, or all of them for preferential
+// attachment. Every thread keeps only its own best k, so preferential attachment
+// takes time but not memory quadratic in the vertices.
+fn top_candidates(
+    adjacency: &[Vec<usize>],
+    measure: Similarity,
+    k: usize,
+) -> Vec<(usize, usize, f64)> {
+    let n = adjacency.len();
+    let degree = |v: usize| adjacency[v].len();
+    let top = (0..n)
+        .into_par_iter()
+        .fold(
+            || TopK::new(k),
+            |mut top, u| {
+                if !measure.needs_common_neighbors() {
+                    if degree(u) > 0 {
+                        for v in u + 1..n {
+                            if degree(v) > 0 && adjacency[u].binary_search(&v).is_err() {
+                                let score = (degree(u) * degree(v)) as f64;
+                                top.push(Ranked { score, u, v });
+                            }
+                        }
+                    }
+                    return top;
+                }
+                for (v, overlap) in two_hop_overlaps(adjacency, u) {
+                    let score = overlap.score(measure, degree(u), degree(v));
+                    top.push(Ranked { score, u, v });
+                }
+                top
+            },
+        )
+        .reduce(|| TopK::new(k), TopK::merge);
+    top.heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| (ranked.u, ranked.v, ranked.score))
+        .collect()
+}
+
+impl Graph {
+    // Similarity of each given pair of vertices. Self-loops and parallel edges are ignored.
+    pub fn similarity(&self, measure: Similarity, pairs: &[(usize, usize)]) -> Vec<f64> {
+        let adjacency = simple_adjacency(self);
+        pairs
+            .par_iter()
+            .map(|&(u, v)| {
+                overlap(&adjacency, u, v).score(measure, adjacency[u].len(), adjacency[v].len())
+            })
+            .collect()
+    }
+
+    // Similarity of every unconnected pair u < v with a common neighbour, the usual
+    // candidates for new links
+    pub fn two_hop_similarity(&self, measure: Similarity) -> Vec<(usize, usize, f64)> {
+        let adjacency = simple_adjacency(self);
+        let mut scores: Vec<(usize, usize, f64)> = (0..self.vertices)
+            .into_par_iter()
+            .flat_map_iter(|u| {
+                let adjacency = &adjacency;
+                two_hop_overlaps(adjacency, u)
+                    .into_iter()
+                    .map(move |(v, overlap)| {
+                        (
+                            u,
+                            v,
+                            overlap.score(measure, adjacency[u].len(), adjacency[v].len()),
+                        )
+                    })
+            })
+            .collect();
+        scores.par_sort_unstable_by_key(|&(u, v, _)| (u, v));
+        scores
+    }
+
+    // Hide a random `fraction` of the edges, score pairs on the remaining graph and
+    // report how well the hidden edges are recovered: the AUC against non-edges of
+    // the full graph, estimated from random comparisons, and precision among the
+    // `k` best-scoring unconnected pairs.
+    pub fn evaluate_link_prediction(
+        &self,
+        measure: Similarity,
+        fraction: f64,
+        k: usize,
+        seed: u64,
+    ) -> LinkPrediction {
+        let adjacency = simple_adjacency(self);
+        let n = self.vertices;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges: Vec<(usize, usize)> = adjacency
+            .iter()
+            .enumerate()
+            .flat_map(|(u, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |&&v| v > u)
+                    .map(move |&v| (u, v))
+            })
+            .collect();
+        edges.shuffle(&mut rng);
+        let hidden_count = ((edges.len() as f64 * fraction).round() as usize).min(edges.len());
+        let hidden: HashSet<(usize, usize)> = edges[..hidden_count].iter().copied().collect();
+        let training = simple_adjacency(&Graph::from_edgelist(edges[hidden_count..].to_vec(), n));
+
+        let score = |u: usize, v: usize| {
+            overlap(&training, u, v).score(measure, training[u].len(), training[v].len())
+        };
+        let hidden_list = &edges[..hidden_count];
+        let non_edges = n * n.saturating_sub(1) / 2 - edges.len();
+        let mut auc = f64::NAN;
+        if hidden_count > 0 && non_edges > 0 {
+            let mut total = 0.0;
+            for _ in 0..AUC_SAMPLES {
+                let (a, b) = hidden_list[rng.random_range(0..hidden_count)];
+                let (u, v) = loop {
+                    let (u, v) = (rng.random_range(0..n), rng.random_range(0..n));
+                    if u != v && adjacency[u].binary_search(&v).is_err() {
+                        break (u, v);
+                    }
+                };
+                let (positive, negative) = (score(a, b), score(u, v));
+                total += if positive > negative {
+                    1.0
+                } else if positive == negative {
+                    0.5
+                } else {
+                    0.0
+                };
+            }
+            auc = total / AUC_SAMPLES as f64;
+        }
+
+        // Best unconnected pairs of the training graph, ties by pair
+        let candidates = top_candidates(&training, measure, k);
+        let top = candidates.len();
+        let found = candidates
+            .iter()
+            .filter(|&&(u, v, _)| hidden.contains(&(u, v)))
+            .count();
+        let precision = if top > 0 {
+            found as f64 / top as f64
+        } else {
+            f64::NAN
+        };
+
+        LinkPrediction {
+            auc,
+            precision,
+            hidden: hidden_count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{top_candidates, Similarity};
+    use crate::graph::Graph;
+    use crate::structural::simple_adjacency;
+    use crate::testutil::{self, random_graph};
+    use rand::Rng;
+    use std::collections::HashSet;
+
+    // Similarity straight from the definitions on neighbour sets without loops
+    fn brute_force(graph: &Graph, measure: Similarity, u: usize, v: usize) -> f64 {
+        let neighbors = |x: usize| -> HashSet<usize> {
+            graph
+                .successors(x)
+                .iter()
+                .copied()
+                .filter(|&y| y != x)
+                .collect()
+        };
+        let (a, b) = (neighbors(u), neighbors(v));
+        let common: Vec<usize> = a.intersection(&b).copied().collect();
+        let (du, dv) = (a.len() as f64, b.len() as f64);
+        match measure {
+            Similarity::Jaccard => {
+                let union = a.union(&b).count();
+                if union > 0 {
+                    common.len() as f64 / union as f64
+                } else {
+                    0.0
+                }
+            }
+            Similarity::Cosine => {
+                if du > 0.0 && dv > 0.0 {
+                    common.len() as f64 / (du * dv).sqrt()
+                } else {
+                    0.0
+                }
+            }
+            Similarity::AdamicAdar => common
+                .iter()
+                .map(|&w| 1.0 / (neighbors(w).len() as f64).ln())
+                .sum(),
+            Similarity::ResourceAllocation => common
+                .iter()
+                .map(|&w| 1.0 / neighbors(w).len() as f64)
+                .sum(),
+            Similarity::PreferentialAttachment => du * dv,
+        }
+    }
+
+    #[test]
+    fn similarity_matches_brute_force() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(2..15);
+            let m = rng.random_range(0..4 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            let pairs: Vec<(usize, usize)> = (0..n)
+                .flat_map(|u| (0..n).map(move |v| (u, v)))
+                .filter(|&(u, v)| u != v)
+                .collect();
+            for measure in Similarity::ALL {
+                let scores = graph.similarity(measure, &pairs);
+                for (&(u, v), score) in pairs.iter().zip(scores) {
+                    let expected = brute_force(&graph, measure, u, v);
+                    assert!(
+                        (score - expected).abs() < 1e-12,
+                        "{:?} {} {}",
+                        measure,
+                        u,
+                        v
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_hop_pairs_share_a_neighbour() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(2..15);
+            let m = rng.random_range(0..3 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            let scores = graph.two_hop_similarity(Similarity::Jaccard);
+            let expected: Vec<(usize, usize)> = (0..n)
+                .flat_map(|u| (u + 1..n).map(move |v| (u, v)))
+                .filter(|&(u, v)| {
+                    !graph.has_edge(u, v)
+                        && (0..n).any(|w| {
+                            w != u && w != v && graph.has_edge(u, w) && graph.has_edge(v, w)
+                        })
+                })
+                .collect();
+            let pairs: Vec<(usize, usize)> = scores.iter().map(|&(u, v, _)| (u, v)).collect();
+            assert_eq!(pairs, expected);
+            for (u, v, score) in scores {
+                assert!((score - brute_force(&graph, Similarity::Jaccard, u, v)).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn top_candidates_match_a_full_sort() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(2..15);
+            let m = rng.random_range(0..3 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            let adjacency = simple_adjacency(&graph);
+            for measure in Similarity::ALL {
+                let mut all: Vec<(usize, usize, f64)> = (0..n)
+                    .flat_map(|u| (u + 1..n).map(move |v| (u, v)))
+                    .filter(|&(u, v)| !graph.has_edge(u, v))
+                    .map(|(u, v)| (u, v, graph.similarity(measure, &[(u, v)])[0]))
+                    .filter(|&(u, v, score)| {
+                        score > 0.0
+                            || measure.needs_common_neighbors()
+                                && (0..n).any(|w| {
+                                    w != u && w != v && graph.has_edge(u, w) && graph.has_edge(v, w)
+                                })
+                    })
+                    .collect();
+                all.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+                for k in [0, 1, 3, n * n] {
+                    let top = top_candidates(&adjacency, measure, k);
+                    assert_eq!(top, all[..k.min(all.len())]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn similarity_on_a_star() {
+        // Leaves 1..=4 around the centre 0
+        let graph = Graph::from_edgelist(vec![(0, 1), (0, 2), (0, 3), (0, 4)], 5);
+        let pairs = [(1, 2), (0, 1)];
+        assert_eq!(
+            graph.similarity(Similarity::Jaccard, &pairs),
+            vec![1.0, 0.0]
+        );
+        assert_eq!(graph.similarity(Similarity::Cosine, &pairs), vec![1.0, 0.0]);
+        let adamic_adar = graph.similarity(Similarity::AdamicAdar, &pairs);
+        assert!((adamic_adar[0] - 1.0 / 4f64.ln()).abs() < 1e-12);
+        assert_eq!(
+            graph.similarity(Similarity::ResourceAllocation, &pairs),
+            vec![0.25, 0.0]
+        );
+        assert_eq!(
+            graph.similarity(Similarity::PreferentialAttachment, &pairs),
+            vec![1.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn hidden_edges_of_separate_cliques_are_recovered() {
+        // Two disjoint K6: a hidden edge keeps common neighbours, a pair across has none
+        let mut edges = Vec::new();
+        for offset in [0, 6] {
+            for u in 0..6 {
+                for v in u + 1..6 {
+                    edges.push((offset + u, offset + v));
+                }
+            }
+        }
+        let graph = Graph::from_edgelist(edges, 12);
+        for measure in [
+            Similarity::Jaccard,
+            Similarity::AdamicAdar,
+            Similarity::ResourceAllocation,
+        ] {
+            let result = graph.evaluate_link_prediction(measure, 0.2, 6, 7);
+            assert_eq!(result.hidden, 6);
+            assert_eq!(result.auc, 1.0);
+            assert_eq!(result.precision, 1.0);
+        }
+        let result = graph.evaluate_link_prediction(Similarity::Jaccard, 0.0, 6, 7);
+        assert_eq!(result.hidden, 0);
+        assert!(result.auc.is_nan());
+    }
+}
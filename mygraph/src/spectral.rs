@@ -0,0 +1,292 @@
+extern crate nalgebra as na;
+extern crate nalgebra_sparse as na_sparse;
+use crate::graph::Graph;
+use na::{DMatrix, DVector, SymmetricEigen};
+use na_sparse::csr::CsrMatrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Krylov subspace size of a single Lanczos run
+const LANCZOS_STEPS: usize = 64;
+const MAX_RESTARTS: usize = 200;
+
+// Upper bound on the largest eigenvalue magnitude (Gershgorin)
+fn spectral_bound(matrix: &CsrMatrix<f64>) -> f64 {
+    matrix
+        .row_iter()
+        .map(|row| row.values().iter().map(|v| v.abs()).sum::<f64>())
+        .fold(0.0, f64::max)
+}
+
+fn orthogonalize(w: &mut DVector<f64>, basis: &[DVector<f64>]) {
+    // Twice is enough to keep the basis orthogonal in floating point
+    for _ in 0..2 {
+        for q in basis {
+            let projection = w.dot(q);
+            w.axpy(-projection, q, 1.0);
+        }
+    }
+}
+
+// Lanczos with full reorthogonalisation for the largest eigenpair of
+// M = shift * I - matrix, restricted to the complement of `locked`.
+// Returns the Ritz value, the Ritz vector and the residual norm.
+fn lanczos_largest(
+    matrix: &CsrMatrix<f64>,
+    shift: f64,
+    locked: &[DVector<f64>],
+    mut start: DVector<f64>,
+) -> Option<(f64, DVector<f64>, f64)> {
+    orthogonalize(&mut start, locked);
+    let norm = start.norm();
+    if norm < 1e-12 {
+        return None;
+    }
+
+    let steps = LANCZOS_STEPS.min(matrix.nrows() - locked.len());
+    let mut basis = vec![start / norm];
+    let mut alphas = Vec::with_capacity(steps);
+    let mut betas: Vec<f64> = Vec::with_capacity(steps);
+    let mut last_beta = 0.0;
+
+    for j in 0..steps {
+        let q = &basis[j];
+        let mut w = q * shift - matrix * q;
+        let alpha = w.dot(q);
+        alphas.push(alpha);
+        // Locked directions last: they are the dominant ones of M and any
+        // component left in them would grow in every step
+        orthogonalize(&mut w, &basis);
+        orthogonalize(&mut w, locked);
+        let beta = w.norm();
+        // An invariant subspace has been found, the Ritz pairs are exact
+        if beta < 1e-10 * shift.max(1.0) || j + 1 == steps {
+            last_beta = beta;
+            break;
+        }
+        betas.push(beta);
+        basis.push(w / beta);
+    }
+
+    let m = alphas.len();
+    let mut tridiagonal = DMatrix::zeros(m, m);
+    for i in 0..m {
+        tridiagonal[(i, i)] = alphas[i];
+        if i + 1 < m {
+            tridiagonal[(i, i + 1)] = betas[i];
+            tridiagonal[(i + 1, i)] = betas[i];
+        }
+    }
+    let eigen = SymmetricEigen::new(tridiagonal);
+    let top = eigen.eigenvalues.imax();
+    let s = eigen.eigenvectors.column(top);
+
+    let mut ritz = DVector::zeros(matrix.nrows());
+    for (i, q) in basis.iter().take(m).enumerate() {
+        ritz.axpy(s[i], q, 1.0);
+    }
+    let residual = (last_beta * s[m - 1]).abs();
+    Some((eigen.eigenvalues[top], ritz, residual))
+}
+
+// Flip the sign so that the largest component is positive, for reproducible output
+fn fix_sign(vector: &mut DVector<f64>) {
+    let largest = vector.iamax();
+    if vector[largest] < 0.0 {
+        *vector *= -1.0;
+    }
+}
+
+// The k smallest eigenvalues (ascending) and eigenvectors (as columns) of a sparse
+// symmetric matrix. Eigenpairs are found one at a time with restarted Lanczos runs
+// and locked, so repeated eigenvalues, e.g. of disconnected graphs, are all found.
+pub fn smallest_eigenpairs(matrix: &CsrMatrix<f64>, k: usize) -> (DVector<f64>, DMatrix<f64>) {
+    let n = matrix.nrows();
+    let k = k.min(n);
+    let shift = spectral_bound(matrix);
+    let tolerance = 1e-9 * shift.max(1.0);
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let mut values = Vec::with_capacity(k);
+    let mut locked: Vec<DVector<f64>> = Vec::with_capacity(k);
+    while locked.len() < k {
+        let mut start = DVector::from_fn(n, |_, _| rng.random::<f64>() - 0.5);
+        let mut best = None;
+        for _ in 0..MAX_RESTARTS {
+            let Some((theta, ritz, residual)) = lanczos_largest(matrix, shift, &locked, start)
+            else {
+                break;
+            };
+            let converged = residual < tolerance;
+            start = ritz.clone();
+            best = Some((theta, ritz));
+            if converged {
+                break;
+            }
+        }
+        let Some((theta, mut vector)) = best else {
+            break;
+        };
+        vector.normalize_mut();
+        fix_sign(&mut vector);
+        values.push(shift - theta);
+        locked.push(vector);
+    }
+
+    // Locking order follows the eigenvalues up to rounding; sort to be sure
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let eigenvalues = DVector::from_iterator(order.len(), order.iter().map(|&i| values[i]));
+    let mut eigenvectors = DMatrix::zeros(n, order.len());
+    for (column, &i) in order.iter().enumerate() {
+        eigenvectors.set_column(column, &locked[i]);
+    }
+
+    (eigenvalues, eigenvectors)
+}
+
+impl Graph {
+    // Second smallest eigenvalue of the Laplacian, zero iff the graph is disconnected
+    pub fn algebraic_connectivity(&self) -> f64 {
+        let (eigenvalues, _) = smallest_eigenpairs(&self.laplacian(), 2);
+        eigenvalues.get(1).copied().unwrap_or(0.0).max(0.0)
+    }
+
+    // Eigenvector of the Laplacian belonging to the algebraic connectivity
+    pub fn fiedler_vector(&self) -> DVector<f64> {
+        let (_, eigenvectors) = smallest_eigenpairs(&self.laplacian(), 2);
+        if eigenvectors.ncols() < 2 {
+            return DVector::zeros(self.vertices);
+        }
+        eigenvectors.column(1).into_owned()
+    }
+
+    // Split the vertices into two parts by the sign of the Fiedler vector
+    pub fn spectral_bisection(&self) -> Vec<usize> {
+        self.fiedler_vector()
+            .iter()
+            .map(|&x| if x < 0.0 { 1 } else { 0 })
+            .collect()
+    }
+
+    // Laplacian eigenmap: one row per vertex with the coordinates from the
+    // eigenvectors of the 2nd to (k+1)-th smallest eigenvalues of the normalised
+    // Laplacian, rescaled by D^-1/2 to those of the random-walk Laplacian.
+    pub fn spectral_embedding(&self, k: usize) -> DMatrix<f64> {
+        let (_, eigenvectors) = smallest_eigenpairs(&self.normalized_laplacian(), k + 1);
+        let k = eigenvectors.ncols().saturating_sub(1);
+        let mut embedding = eigenvectors.columns(1, k).into_owned();
+        for (node, mut row) in embedding.row_iter_mut().enumerate() {
+            let degree = self.adj_list[node].iter().filter(|&&w| w != node).count();
+            if degree > 0 {
+                row /= (degree as f64).sqrt();
+            }
+        }
+        embedding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::na::{DMatrix, SymmetricEigen};
+    use super::smallest_eigenpairs;
+    use super::CsrMatrix;
+    use crate::graph::Graph;
+    use crate::testutil::{self, random_graph};
+    use rand::Rng;
+    use std::f64::consts::PI;
+
+    fn dense(matrix: &CsrMatrix<f64>) -> DMatrix<f64> {
+        let mut dense = DMatrix::zeros(matrix.nrows(), matrix.ncols());
+        for (i, j, &v) in matrix.triplet_iter() {
+            dense[(i, j)] += v;
+        }
+        dense
+    }
+
+    #[test]
+    fn eigenpairs_match_dense_solver() {
+        let mut rng = testutil::rng();
+        for _ in 0..30 {
+            let n = rng.random_range(2..40);
+            let m = rng.random_range(0..3 * n);
+            let graph = random_graph(&mut rng, n, m, false);
+            for matrix in [graph.laplacian(), graph.normalized_laplacian()] {
+                let full = dense(&matrix);
+                let mut expected: Vec<f64> = SymmetricEigen::new(full.clone())
+                    .eigenvalues
+                    .iter()
+                    .copied()
+                    .collect();
+                expected.sort_by(f64::total_cmp);
+
+                let k = n.min(5);
+                let (values, vectors) = smallest_eigenpairs(&matrix, k);
+                assert_eq!(values.len(), k);
+                for i in 0..k {
+                    assert!((values[i] - expected[i]).abs() < 1e-6);
+                    let v = vectors.column(i);
+                    assert!((v.norm() - 1.0).abs() < 1e-9);
+                    assert!((&full * v - v * values[i]).norm() < 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn laplacian_rows_sum_to_zero() {
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 0), (2, 3)], 4);
+        let laplacian = dense(&graph.laplacian());
+        for row in laplacian.row_iter() {
+            assert_eq!(row.sum(), 0.0);
+        }
+        assert_eq!(laplacian[(2, 2)], 3.0);
+        let random_walk = dense(&graph.random_walk_laplacian());
+        assert!((random_walk[(2, 3)] + 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn path_and_cycle_spectra() {
+        let n = 12;
+        let path = Graph::from_edgelist((0..n - 1).map(|i| (i, i + 1)).collect(), n);
+        let expected = 2.0 * (1.0 - (PI / n as f64).cos());
+        assert!((path.algebraic_connectivity() - expected).abs() < 1e-9);
+        // The Fiedler vector of a path is monotone, splitting it in the middle
+        let halves = path.spectral_bisection();
+        assert!(halves[..n / 2].iter().all(|&h| h == halves[0]));
+        assert!(halves[n / 2..].iter().all(|&h| h != halves[0]));
+
+        let cycle = Graph::from_edgelist((0..n).map(|i| (i, (i + 1) % n)).collect(), n);
+        let (values, _) = smallest_eigenpairs(&cycle.laplacian(), 5);
+        let second = 2.0 - 2.0 * (2.0 * PI / n as f64).cos();
+        let third = 2.0 - 2.0 * (4.0 * PI / n as f64).cos();
+        let expected = [0.0, second, second, third, third];
+        for (value, expected) in values.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn disconnected_graph_has_zero_connectivity() {
+        let graph = Graph::from_edgelist(vec![(0, 1), (2, 3)], 4);
+        assert!(graph.algebraic_connectivity().abs() < 1e-9);
+        let (values, _) = smallest_eigenpairs(&graph.laplacian(), 2);
+        assert!(values.iter().all(|v| v.abs() < 1e-9));
+    }
+
+    #[test]
+    fn embedding_solves_the_random_walk_eigenproblem() {
+        let graph = Graph::from_edgelist(
+            vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3)],
+            6,
+        );
+        let (values, _) = smallest_eigenpairs(&graph.normalized_laplacian(), 3);
+        let embedding = graph.spectral_embedding(2);
+        assert_eq!(embedding.shape(), (6, 2));
+        let random_walk = dense(&graph.random_walk_laplacian());
+        for c in 0..2 {
+            let y = embedding.column(c);
+            assert!((&random_walk * y - y * values[c + 1]).norm() < 1e-8);
+        }
+    }
+}
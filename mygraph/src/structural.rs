@@ -1,19 +1,49 @@
 use crate::graph::Graph;
 extern crate rayon;
 use rayon::prelude::*;
+use std::collections::VecDeque;
+
+// Ensure each adjacency list is sorted to allow binary search
+pub(crate) fn sorted_adjacency(graph: &Graph) -> Vec<Vec<usize>> {
+    graph
+        .adj_list
+        .par_iter()
+        .map(|neighbors| {
+            let mut sorted_neighbors = neighbors.clone();
+            sorted_neighbors.sort_unstable();
+            sorted_neighbors
+        })
+        .collect()
+}
+
+// Sorted neighbour sets without self-loops and parallel edges
+pub(crate) fn simple_adjacency(graph: &Graph) -> Vec<Vec<usize>> {
+    graph
+        .adj_list
+        .par_iter()
+        .enumerate()
+        .map(|(node, neighbors)| {
+            let mut sorted_neighbors = neighbors.clone();
+            sorted_neighbors.sort_unstable();
+            sorted_neighbors.dedup();
+            sorted_neighbors.retain(|&w| w != node);
+            sorted_neighbors
+        })
+        .collect()
+}
+
+// Union-find root with path halving
+pub(crate) fn find_root(parent: &mut [usize], mut v: usize) -> usize {
+    while parent[v] != v {
+        parent[v] = parent[parent[v]];
+        v = parent[v];
+    }
+    v
+}
 
 impl Graph {
     pub fn count_triangles(&self) -> usize {
-        // Ensure each adjacency list is sorted to allow binary search
-        let sorted_adj_list: Vec<Vec<usize>> = self
-            .adj_list
-            .par_iter()
-            .map(|neighbors| {
-                let mut sorted_neighbors = neighbors.clone();
-                sorted_neighbors.sort_unstable();
-                sorted_neighbors
-            })
-            .collect();
+        let sorted_adj_list = sorted_adjacency(self);
 
         // Use a parallel iterator to iterate over vertices
         let triangle_counts: Vec<usize> = (0..self.adj_list.len())
@@ -34,7 +64,177 @@ impl Graph {
             })
             .collect();
 
-        // Sum up the local counts and divide by 3, as each triangle is counted three times
-        triangle_counts.into_iter().sum::<usize>() / 3
+        // Each triangle is only counted from its lowest vertex, so the counts just add up
+        triangle_counts.into_iter().sum::<usize>()
+    }
+
+    // Number of triangles each vertex is part of
+    pub fn local_triangles(&self) -> Vec<usize> {
+        let sorted_adj_list = sorted_adjacency(self);
+
+        (0..self.adj_list.len())
+            .into_par_iter()
+            .map(|u| {
+                let mut local_count = 0;
+                for &v in &sorted_adj_list[u] {
+                    if v == u {
+                        continue;
+                    }
+                    // Every pair v < w of neighbours of u that are adjacent closes a triangle
+                    for &w in &sorted_adj_list[u] {
+                        if w > v && w != u && sorted_adj_list[v].binary_search(&w).is_ok() {
+                            local_count += 1;
+                        }
+                    }
+                }
+                local_count
+            })
+            .collect()
+    }
+
+    // Component id of every vertex, numbered in order of the lowest vertex they contain.
+    // Arcs are followed both ways, so a directed graph gets its weak components.
+    pub fn connected_components(&self) -> Vec<usize> {
+        let mut membership = vec![usize::MAX; self.vertices];
+        let mut queue = VecDeque::new();
+        let mut component = 0;
+
+        for start in 0..self.vertices {
+            if membership[start] != usize::MAX {
+                continue;
+            }
+            membership[start] = component;
+            queue.push_back(start);
+            while let Some(node) = queue.pop_front() {
+                let predecessors = match &self.in_list {
+                    Some(in_list) => &in_list[node][..],
+                    None => &[],
+                };
+                for &neighbor in self.adj_list[node].iter().chain(predecessors) {
+                    if membership[neighbor] == usize::MAX {
+                        membership[neighbor] = component;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            component += 1;
+        }
+
+        membership
+    }
+
+    // Number of vertices in the largest connected component
+    pub fn largest_component_size(&self) -> usize {
+        let membership = self.connected_components();
+        let mut sizes = vec![0; self.vertices];
+        for c in membership {
+            sizes[c] += 1;
+        }
+        sizes.into_iter().max().unwrap_or(0)
+    }
+
+    // Largest component size after removing the first i vertices of `order`, for
+    // i = 0..=order.len(). Vertices are added back in reverse with a union-find,
+    // so the whole curve costs about as much as one component search. Components
+    // are weak for a directed graph, as in `connected_components`.
+    pub fn robustness(&self, order: &[usize]) -> Vec<usize> {
+        let mut removed = vec![false; self.vertices];
+        for &v in order {
+            removed[v] = true;
+        }
+
+        let mut parent: Vec<usize> = (0..self.vertices).collect();
+        let mut size = vec![1; self.vertices];
+        let mut largest = 0;
+        let mut activate = |v: usize, removed: &mut [bool], largest: &mut usize| {
+            removed[v] = false;
+            *largest = (*largest).max(1);
+            let predecessors = match &self.in_list {
+                Some(in_list) => &in_list[v][..],
+                None => &[],
+            };
+            for &w in self.adj_list[v].iter().chain(predecessors) {
+                if removed[w] {
+                    continue;
+                }
+                let (a, b) = (find_root(&mut parent, v), find_root(&mut parent, w));
+                if a != b {
+                    let (a, b) = if size[a] < size[b] { (b, a) } else { (a, b) };
+                    parent[b] = a;
+                    size[a] += size[b];
+                    *largest = (*largest).max(size[a]);
+                }
+            }
+        };
+
+        for v in 0..self.vertices {
+            if !removed[v] {
+                activate(v, &mut removed, &mut largest);
+            }
+        }
+        let mut curve = vec![largest];
+        for &v in order.iter().rev() {
+            activate(v, &mut removed, &mut largest);
+            curve.push(largest);
+        }
+        curve.reverse();
+        curve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::testutil::{self, random_edges};
+    use rand::seq::SliceRandom;
+    use rand::Rng;
+
+    #[test]
+    fn robustness_matches_removing_vertices_one_by_one() {
+        let mut rng = testutil::rng();
+        for round in 0..100 {
+            let directed = round % 2 == 1;
+            let n = rng.random_range(1..12);
+            let m = rng.random_range(0..2 * n);
+            let edges = random_edges(&mut rng, n, m);
+            let build = |edges: Vec<(usize, usize)>| {
+                if directed {
+                    Graph::from_arclist(edges, n)
+                } else {
+                    Graph::from_edgelist(edges, n)
+                }
+            };
+            let graph = build(edges.clone());
+            let mut order: Vec<usize> = (0..n).collect();
+            order.shuffle(&mut rng);
+            order.truncate(rng.random_range(0..=n));
+
+            let curve = graph.robustness(&order);
+            assert_eq!(curve.len(), order.len() + 1);
+            for (i, &largest) in curve.iter().enumerate() {
+                // Keep the removed vertices but drop their edges
+                let kept: Vec<(usize, usize)> = edges
+                    .iter()
+                    .copied()
+                    .filter(|&(u, w)| !order[..i].contains(&u) && !order[..i].contains(&w))
+                    .collect();
+                let expected = if i == n {
+                    0
+                } else {
+                    build(kept).largest_component_size()
+                };
+                assert_eq!(largest, expected);
+            }
+        }
+        // The arc into vertex 0 joins it on its return
+        let arc = Graph::from_arclist(vec![(1, 0)], 2);
+        assert_eq!(arc.robustness(&[0]), vec![2, 1]);
+    }
+
+    #[test]
+    fn directed_components_are_weak() {
+        let graph = Graph::from_arclist(vec![(1, 0), (3, 2), (2, 4)], 6);
+        assert_eq!(graph.connected_components(), vec![0, 0, 1, 1, 1, 2]);
+        assert_eq!(graph.largest_component_size(), 3);
     }
 }
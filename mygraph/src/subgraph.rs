@@ -0,0 +1,219 @@
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use std::collections::VecDeque;
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// A graph cut out of a larger one; `mapping[v]` is the original index of vertex `v`
+pub struct Subgraph {
+    pub graph: Graph,
+    pub mapping: Vec<usize>,
+}
+
+// Burt's structural hole measures of an order-1 ego network.
+// Values that are undefined for the ego (e.g. density with fewer than two alters) are NaN.
+#[derive(Clone, Copy, Debug)]
+pub struct EgoMetrics {
+    // Number of alters
+    pub size: usize,
+    // Share of alter pairs that are tied
+    pub density: f64,
+    pub effective_size: f64,
+    pub constraint: f64,
+}
+
+// Ego metrics from the ego's alters and the simple adjacency of the whole graph
+fn ego_metrics(adj: &[Vec<usize>], ego: usize) -> EgoMetrics {
+    let alters = &adj[ego];
+    let n = alters.len();
+
+    let mut ties = 0;
+    for (i, &j) in alters.iter().enumerate() {
+        for &q in &alters[i + 1..] {
+            if adj[j].binary_search(&q).is_ok() {
+                ties += 1;
+            }
+        }
+    }
+    let density = if n > 1 {
+        2.0 * ties as f64 / (n * (n - 1)) as f64
+    } else {
+        f64::NAN
+    };
+    let effective_size = if n > 0 {
+        n as f64 - 2.0 * ties as f64 / n as f64
+    } else {
+        f64::NAN
+    };
+
+    // Proportional tie strength p_ij = a_ij / sum_k a_ik, which for an unweighted
+    // graph is 1 / degree(i)
+    let p = |i: usize| 1.0 / adj[i].len() as f64;
+    let constraint = if n > 0 {
+        alters
+            .iter()
+            .map(|&j| {
+                let indirect: f64 = alters
+                    .iter()
+                    .filter(|&&q| q != j && adj[q].binary_search(&j).is_ok())
+                    .map(|&q| p(ego) * p(q))
+                    .sum();
+                (p(ego) + indirect).powi(2)
+            })
+            .sum()
+    } else {
+        f64::NAN
+    };
+
+    EgoMetrics {
+        size: n,
+        density,
+        effective_size,
+        constraint,
+    }
+}
+
+impl Graph {
+    // Subgraph induced by a set of vertices, in the order given (duplicates are ignored).
+    // Keeps labels, weights, the policy, the direction of arcs, and any self-loops and
+    // parallel edges among the vertices.
+    pub fn induced_subgraph(&self, vertices: &[usize]) -> Subgraph {
+        let mut new_index = vec![usize::MAX; self.vertices];
+        let mut mapping = Vec::with_capacity(vertices.len());
+        for &v in vertices {
+            if new_index[v] == usize::MAX {
+                new_index[v] = mapping.len();
+                mapping.push(v);
+            }
+        }
+
+        let restrict = |lists: &[Vec<usize>]| -> Vec<Vec<usize>> {
+            mapping
+                .iter()
+                .map(|&v| {
+                    lists[v]
+                        .iter()
+                        .filter(|&&w| new_index[w] != usize::MAX)
+                        .map(|&w| new_index[w])
+                        .collect()
+                })
+                .collect()
+        };
+        let mut graph = Graph::with_policy(mapping.len(), self.policy);
+        graph.adj_list = restrict(&self.adj_list);
+        graph.in_list = self.in_list.as_deref().map(restrict);
+        graph.weights = self.weights.as_ref().map(|weights| {
+            mapping
+                .iter()
+                .map(|&v| {
+                    self.adj_list[v]
+                        .iter()
+                        .zip(&weights[v])
+                        .filter(|&(&w, _)| new_index[w] != usize::MAX)
+                        .map(|(_, &weight)| weight)
+                        .collect()
+                })
+                .collect()
+        });
+        if let Some(labels) = &self.labels {
+            graph.labels = Some(mapping.iter().map(|&v| labels[v].clone()).collect());
+        }
+
+        Subgraph { graph, mapping }
+    }
+
+    // Subgraph induced by all vertices within `order` steps of `ego`, ego first
+    pub fn ego_network(&self, ego: usize, order: usize) -> Subgraph {
+        let mut distances = vec![usize::MAX; self.vertices];
+        let mut members = vec![ego];
+        let mut queue = VecDeque::new();
+
+        distances[ego] = 0;
+        queue.push_back(ego);
+
+        while let Some(node) = queue.pop_front() {
+            if distances[node] == order {
+                continue;
+            }
+            for &neighbor in &self.adj_list[node] {
+                if distances[neighbor] == usize::MAX {
+                    distances[neighbor] = distances[node] + 1;
+                    members.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        self.induced_subgraph(&members)
+    }
+
+    // Size, density, effective size and constraint of every vertex's order-1 ego network
+    pub fn ego_metrics(&self) -> Vec<EgoMetrics> {
+        let adj = simple_adjacency(self);
+        (0..self.vertices)
+            .into_par_iter()
+            .map(|ego| ego_metrics(&adj, ego))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+
+    #[test]
+    fn induced_subgraph_keeps_weights_labels_and_order() {
+        let graph = Graph::from_weighted_edgelist(
+            vec![(0, 1, 2.0), (1, 2, 3.0), (2, 3, 4.0), (3, 0, 5.0)],
+            4,
+        )
+        .with_labels(["a", "b", "c", "d"].iter().map(|s| s.to_string()).collect());
+        let sub = graph.induced_subgraph(&[2, 1, 3, 2]);
+        assert_eq!(sub.mapping, vec![2, 1, 3]);
+        assert_eq!(sub.graph.label(0), "c");
+        let mut edges = sub.graph.weighted_edges();
+        edges.sort_by_key(|&(u, w, _)| (u, w));
+        assert_eq!(edges, vec![(0, 1, 3.0), (0, 2, 4.0)]);
+    }
+
+    #[test]
+    fn induced_subgraph_keeps_direction() {
+        let graph = Graph::from_arclist(vec![(0, 1), (2, 1), (1, 3)], 4);
+        let sub = graph.induced_subgraph(&[1, 2]);
+        assert!(sub.graph.is_directed());
+        assert_eq!(sub.graph.edges(), vec![(1, 0)]);
+        assert_eq!(sub.graph.predecessors(0), &[1]);
+    }
+
+    #[test]
+    fn ego_network_stops_at_its_order() {
+        let path = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 3), (3, 4)], 5);
+        assert_eq!(path.ego_network(2, 0).mapping, vec![2]);
+        assert_eq!(path.ego_network(2, 1).mapping, vec![2, 1, 3]);
+        let ego = path.ego_network(0, 2);
+        assert_eq!(ego.mapping, vec![0, 1, 2]);
+        assert_eq!(ego.graph.edge_count(), 2);
+    }
+
+    #[test]
+    fn burt_measures_of_a_star_and_a_triangle() {
+        let star = Graph::from_edgelist(vec![(0, 1), (0, 2), (0, 3)], 4).ego_metrics();
+        assert_eq!(star[0].size, 3);
+        assert_eq!(star[0].density, 0.0);
+        assert_eq!(star[0].effective_size, 3.0);
+        assert!((star[0].constraint - 1.0 / 3.0).abs() < 1e-12);
+        assert!(star[1].density.is_nan());
+        assert_eq!(star[1].constraint, 1.0);
+
+        let triangle = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 0)], 3).ego_metrics();
+        assert_eq!(triangle[0].density, 1.0);
+        assert_eq!(triangle[0].effective_size, 1.0);
+        // (1/2 + 1/2 * 1/2)^2 for each of the two alters
+        assert!((triangle[0].constraint - 1.125).abs() < 1e-12);
+
+        let isolated = Graph::new(1).ego_metrics();
+        assert_eq!(isolated[0].size, 0);
+        assert!(isolated[0].effective_size.is_nan());
+    }
+}
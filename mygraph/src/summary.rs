@@ -0,0 +1,224 @@
+use crate::graph::{Graph, GraphPolicy};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Summary {
+    pub vertices: usize,
+    pub edges: usize,
+    pub density: f64,
+    pub self_loops: usize,
+    pub multi_edges: usize,
+    pub min_degree: usize,
+    pub mean_degree: f64,
+    pub max_degree: usize,
+    pub components: usize,
+    pub largest_component: usize,
+    pub triangles: usize,
+    pub transitivity: f64,
+    // Number of vertices with each degree, indexed by degree
+    pub degree_histogram: Vec<usize>,
+}
+
+// Self-loops and surplus parallel edges (each multi-edge beyond the first).
+// In a directed graph parallel arcs run the same way.
+fn count_loops_and_multi_edges(graph: &Graph) -> (usize, usize) {
+    // An undirected self-loop is stored twice in its own adjacency list, and every
+    // other undirected edge is seen from both ends
+    let copies = if graph.is_directed() { 1 } else { 2 };
+    let mut self_loops = 0;
+    let mut surplus_loops = 0;
+    let mut surplus_edges = 0;
+
+    for (node, neighbors) in graph.adj_list.iter().enumerate() {
+        let mut sorted_neighbors = neighbors.clone();
+        sorted_neighbors.sort_unstable();
+        for run in sorted_neighbors.chunk_by(|a, b| a == b) {
+            if run[0] == node {
+                let loops = run.len() / copies;
+                self_loops += loops;
+                surplus_loops += loops.saturating_sub(1);
+            } else {
+                surplus_edges += run.len() - 1;
+            }
+        }
+    }
+
+    (self_loops, surplus_loops + surplus_edges / copies)
+}
+
+impl Graph {
+    pub fn summary(&self) -> Summary {
+        let degree = self.degree();
+        let edges = self.edge_count();
+        let (self_loops, multi_edges) = count_loops_and_multi_edges(self);
+
+        // Ordered pairs of vertices if directed
+        let mut pairs = self.vertices * self.vertices.saturating_sub(1);
+        if !self.is_directed() {
+            pairs /= 2;
+        }
+        let density = if pairs > 0 {
+            edges as f64 / pairs as f64
+        } else {
+            0.0
+        };
+
+        let min_degree = degree.iter().copied().min().unwrap_or(0);
+        let max_degree = degree.iter().copied().max().unwrap_or(0);
+        let mean_degree = if self.vertices > 0 {
+            degree.iter().sum::<usize>() as f64 / self.vertices as f64
+        } else {
+            0.0
+        };
+        let mut degree_histogram = vec![0; max_degree + 1];
+        for &d in &degree {
+            degree_histogram[d] += 1;
+        }
+
+        let components = self
+            .connected_components()
+            .into_iter()
+            .map(|c| c + 1)
+            .max()
+            .unwrap_or(0);
+        let largest_component = self.largest_component_size();
+
+        // Global clustering: closed triplets over connected triplets, of the underlying
+        // simple undirected graph
+        let underlying;
+        let simple = if self.is_directed() || self.policy != GraphPolicy::Simple {
+            underlying = Graph::from_edgelist(self.edges(), self.vertices);
+            &underlying
+        } else {
+            self
+        };
+        let triangles = simple.count_triangles();
+        let triplets: usize = simple
+            .degree()
+            .iter()
+            .map(|&d| d * d.saturating_sub(1) / 2)
+            .sum();
+        let transitivity = if triplets > 0 {
+            3.0 * triangles as f64 / triplets as f64
+        } else {
+            0.0
+        };
+
+        Summary {
+            vertices: self.vertices,
+            edges,
+            density,
+            self_loops,
+            multi_edges,
+            min_degree,
+            mean_degree,
+            max_degree,
+            components,
+            largest_component,
+            triangles,
+            transitivity,
+            degree_histogram,
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "vertices:          {}", self.vertices)?;
+        writeln!(f, "edges:             {}", self.edges)?;
+        writeln!(f, "density:           {:.6}", self.density)?;
+        writeln!(f, "self-loops:        {}", self.self_loops)?;
+        writeln!(f, "multi-edges:       {}", self.multi_edges)?;
+        writeln!(
+            f,
+            "degree:            min {} / mean {:.3} / max {}",
+            self.min_degree, self.mean_degree, self.max_degree
+        )?;
+        writeln!(f, "components:        {}", self.components)?;
+        writeln!(f, "largest component: {}", self.largest_component)?;
+        writeln!(f, "triangles:         {}", self.triangles)?;
+        writeln!(f, "transitivity:      {:.6}", self.transitivity)?;
+        writeln!(f, "degree histogram:")?;
+        for (degree, &count) in self.degree_histogram.iter().enumerate() {
+            if count > 0 {
+                writeln!(f, "  {:>6} {:>8}", degree, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{Graph, GraphPolicy};
+
+    #[test]
+    fn summary_of_two_triangles_and_an_isolated_vertex() {
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 0)], 5);
+        let summary = graph.summary();
+        assert_eq!(summary.vertices, 5);
+        assert_eq!(summary.edges, 5);
+        assert!((summary.density - 0.5).abs() < 1e-12);
+        assert_eq!((summary.self_loops, summary.multi_edges), (0, 0));
+        assert_eq!((summary.min_degree, summary.max_degree), (0, 3));
+        assert!((summary.mean_degree - 2.0).abs() < 1e-12);
+        assert_eq!(summary.components, 2);
+        assert_eq!(summary.largest_component, 4);
+        assert_eq!(summary.triangles, 2);
+        // Six closed of eight connected triplets
+        assert!((summary.transitivity - 0.75).abs() < 1e-12);
+        assert_eq!(summary.degree_histogram, vec![1, 0, 2, 2]);
+    }
+
+    #[test]
+    fn summary_counts_loops_and_parallel_edges() {
+        let edges = vec![(0, 1), (1, 2), (1, 2), (0, 0), (0, 0)];
+        let graph = Graph::from_edgelist_with_policy(edges, 3, GraphPolicy::Multigraph);
+        let summary = graph.summary();
+        assert_eq!(summary.edges, 5);
+        assert_eq!(summary.self_loops, 2);
+        // The second loop and the second edge between 1 and 2
+        assert_eq!(summary.multi_edges, 2);
+    }
+
+    #[test]
+    fn transitivity_ignores_loops_and_parallel_edges() {
+        let triangle = vec![(0, 1), (1, 2), (2, 0)];
+        let mut looped = triangle.clone();
+        looped.extend([(0, 0), (1, 1), (2, 2)]);
+        let looped = Graph::from_edgelist_with_policy(looped, 3, GraphPolicy::Loops);
+        let mut doubled = triangle.clone();
+        doubled.push((0, 1));
+        let doubled = Graph::from_edgelist_with_policy(doubled, 3, GraphPolicy::Multigraph);
+        for graph in [looped, doubled] {
+            let summary = graph.summary();
+            assert_eq!(summary.triangles, 1);
+            assert_eq!(summary.transitivity, 1.0);
+        }
+    }
+
+    #[test]
+    fn empty_graph_summary() {
+        let summary = Graph::new(0).summary();
+        assert_eq!(summary.edges, 0);
+        assert_eq!(summary.density, 0.0);
+        assert_eq!(summary.components, 0);
+        assert_eq!(summary.transitivity, 0.0);
+    }
+
+    #[test]
+    fn directed_summary_counts_arcs_once() {
+        let graph = Graph::from_arclist(vec![(0, 1), (1, 2), (2, 0), (2, 3)], 4);
+        let summary = graph.summary();
+        assert_eq!(summary.edges, 4);
+        assert!((summary.density - 4.0 / 12.0).abs() < 1e-12);
+        assert_eq!(summary.min_degree, 1);
+        assert_eq!(summary.max_degree, 3);
+        assert!((summary.mean_degree - 2.0).abs() < 1e-12);
+        assert_eq!(summary.components, 1);
+        assert_eq!(summary.triangles, 1);
+        // Underlying degrees 2, 2, 3, 1 give 5 connected triplets
+        assert!((summary.transitivity - 0.6).abs() < 1e-12);
+    }
+}
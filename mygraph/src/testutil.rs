@@ -0,0 +1,46 @@
+// Fixtures shared by the unit tests
+use crate::graph::Graph;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Random stream of a test, the same on every run
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(0x5eed)
+}
+
+// `m` edges whose ends are drawn uniformly, so self-loops and repeats occur
+pub fn random_edges(rng: &mut StdRng, n: usize, m: usize) -> Vec<(usize, usize)> {
+    (0..m)
+        .map(|_| (rng.random_range(0..n), rng.random_range(0..n)))
+        .collect()
+}
+
+// Simple graph, or digraph, from `m` random edges; the simple policy drops the
+// self-loops and repeats
+pub fn random_graph(rng: &mut StdRng, n: usize, m: usize, directed: bool) -> Graph {
+    let edges = random_edges(rng, n, m);
+    if directed {
+        Graph::from_arclist(edges, n)
+    } else {
+        Graph::from_edgelist(edges, n)
+    }
+}
+
+// Erdős–Rényi G(n, p): every pair of distinct vertices joined with probability
+// `p`, or every ordered pair if `directed`
+pub fn gnp(rng: &mut StdRng, n: usize, p: f64, directed: bool) -> Graph {
+    let mut edges = Vec::new();
+    for u in 0..n {
+        let first = if directed { 0 } else { u + 1 };
+        for w in first..n {
+            if u != w && rng.random::<f64>() < p {
+                edges.push((u, w));
+            }
+        }
+    }
+    if directed {
+        Graph::from_arclist(edges, n)
+    } else {
+        Graph::from_edgelist(edges, n)
+    }
+}
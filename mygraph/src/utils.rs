@@ -1,24 +1,333 @@
-use csv::Reader;
+use csv::ReaderBuilder;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
+use std::io::{BufRead, BufReader};
 
-use crate::graph::Graph;
+use crate::bipartite::BipartiteGraph;
+use crate::graph::{Graph, GraphPolicy};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    // Comma separated edge list, one `src,dest` pair per row
+    Csv,
+    // Whitespace separated edge list, one `src dest` pair per line
+    Edgelist,
+    // Adjacency list, one `vertex neighbor neighbor ...` line per vertex
+    Adjlist,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReadOptions {
+    pub format: InputFormat,
+    // Skip the first line of the file
+    pub header: bool,
+    // Treat vertex ids as arbitrary labels instead of 0-based indices
+    pub labelled: bool,
+    // Which self-loops and parallel edges to keep
+    pub policy: GraphPolicy,
+    // Read every row as an arc from the first vertex to the second
+    pub directed: bool,
+    // Read the third column of an edge list as the edge weight
+    pub weighted: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            format: InputFormat::Csv,
+            header: false,
+            labelled: false,
+            policy: GraphPolicy::Simple,
+            directed: false,
+            weighted: false,
+        }
+    }
+}
+
+// Maps vertex ids found in a file to contiguous indices
+struct VertexIds {
+    labelled: bool,
+    index: HashMap<String, usize>,
+    labels: Vec<String>,
+    max_node_index: Option<usize>,
+}
+
+impl VertexIds {
+    fn new(labelled: bool) -> Self {
+        VertexIds {
+            labelled,
+            index: HashMap::new(),
+            labels: Vec::new(),
+            max_node_index: None,
+        }
+    }
+
+    fn get(&mut self, id: &str) -> Result<usize, Box<dyn Error>> {
+        if !self.labelled {
+            let node: usize = id
+                .parse()
+                .map_err(|e| format!("invalid vertex index {:?}: {}", id, e))?;
+            self.max_node_index = Some(self.max_node_index.map_or(node, |m| m.max(node)));
+            return Ok(node);
+        }
+        if let Some(&node) = self.index.get(id) {
+            return Ok(node);
+        }
+        let node = self.labels.len();
+        self.index.insert(id.to_string(), node);
+        self.labels.push(id.to_string());
+        Ok(node)
+    }
+
+    fn vertices(&self) -> usize {
+        if self.labelled {
+            self.labels.len()
+        } else {
+            // Assuming node indices start from 0 and are continuous
+            self.max_node_index.map_or(0, |m| m + 1)
+        }
+    }
+
+    // Labels of all vertices; their indices if ids are not labels
+    fn into_labels(self) -> Vec<String> {
+        if self.labelled {
+            self.labels
+        } else {
+            (0..self.vertices()).map(|v| v.to_string()).collect()
+        }
+    }
+
+    fn finish(self, graph: Graph) -> Graph {
+        if self.labelled {
+            graph.with_labels(self.labels)
+        } else {
+            graph
+        }
+    }
+}
 
 pub fn read_edgelist(file_path: &str) -> Result<Graph, Box<dyn Error>> {
-    let mut reader = Reader::from_reader(File::open(file_path)?);
+    read_graph(file_path, &ReadOptions::default())
+}
+
+pub fn read_graph(file_path: &str, options: &ReadOptions) -> Result<Graph, Box<dyn Error>> {
+    match options.format {
+        InputFormat::Csv | InputFormat::Edgelist => read_edge_list(file_path, options),
+        InputFormat::Adjlist => read_adjlist(file_path, options),
+    }
+}
+
+fn read_edge_list(file_path: &str, options: &ReadOptions) -> Result<Graph, Box<dyn Error>> {
+    let mut ids = VertexIds::new(options.labelled);
+    let (edgelist, weights) = read_rows(file_path, options, |_, id| ids.get(id))?;
+    let graph = edge_list_graph(edgelist, weights, ids.vertices(), options);
+    Ok(ids.finish(graph))
+}
+
+// Two-mode edge list: the first column holds vertices of side false, the second of
+// side true, and each side has its own ids. Vertices of side true come after those of
+// side false, so every vertex is labelled with its id. Edges are read as undirected.
+pub fn read_bipartite(
+    file_path: &str,
+    options: &ReadOptions,
+) -> Result<BipartiteGraph, Box<dyn Error>> {
+    let mut ids = [
+        VertexIds::new(options.labelled),
+        VertexIds::new(options.labelled),
+    ];
+    let (edgelist, weights) = read_rows(file_path, options, |column, id| ids[column].get(id))?;
+    let [first, second] = ids;
+    let offset = first.vertices();
+    let vertices = offset + second.vertices();
+    let edgelist = edgelist
+        .into_iter()
+        .map(|(src, dest)| (src, offset + dest))
+        .collect();
+    let options = ReadOptions {
+        directed: false,
+        ..options.clone()
+    };
+    let graph = edge_list_graph(edgelist, weights, vertices, &options);
+
+    let mut labels = first.into_labels();
+    labels.extend(second.into_labels());
+    let types = (0..vertices).map(|v| v >= offset).collect();
+    Ok(BipartiteGraph::new(graph.with_labels(labels), types))
+}
+
+// Edges read from a file and their weights, empty if unweighted
+type Rows = (Vec<(usize, usize)>, Vec<f64>);
+
+// Rows of a CSV or whitespace separated edge list, and a weight per row if
+// `options.weighted`. `index` maps the vertex id in column 0 or 1 to its index.
+fn read_rows(
+    file_path: &str,
+    options: &ReadOptions,
+    mut index: impl FnMut(usize, &str) -> Result<usize, Box<dyn Error>>,
+) -> Result<Rows, Box<dyn Error>> {
     let mut edgelist: Vec<(usize, usize)> = Vec::new();
-    let mut max_node_index = 0;
-    for result in reader.records() {
-        let record = result?;
-        let src: usize = record[0].parse()?;
-        let dest: usize = record[1].parse()?;
-        max_node_index = max_node_index.max(src).max(dest);
-        edgelist.push((src, dest));
+    let mut weights: Vec<f64> = Vec::new();
+    let mut push = |src: &str, dest: &str, weight: Option<&str>, row: &str| {
+        edgelist.push((index(0, src)?, index(1, dest)?));
+        if options.weighted {
+            weights.push(parse_weight(weight, row)?);
+        }
+        Ok::<(), Box<dyn Error>>(())
+    };
+
+    match options.format {
+        InputFormat::Csv => {
+            let mut reader = ReaderBuilder::new()
+                .has_headers(options.header)
+                .trim(csv::Trim::All)
+                .from_reader(File::open(file_path)?);
+            for result in reader.records() {
+                let record = result?;
+                if record.len() < 2 {
+                    return Err(format!("expected two columns in {:?}", record).into());
+                }
+                let row = format!("{:?}", record);
+                push(&record[0], &record[1], record.get(2), &row)?;
+            }
+        }
+        InputFormat::Edgelist => {
+            for line in data_lines(file_path, options.header)? {
+                let line = line?;
+                let mut fields = line.split_whitespace();
+                match (fields.next(), fields.next()) {
+                    (Some(src), Some(dest)) => {
+                        push(src, dest, fields.next(), &format!("{:?}", line))?
+                    }
+                    _ => return Err(format!("expected two columns in {:?}", line).into()),
+                }
+            }
+        }
+        InputFormat::Adjlist => return Err("expected an edge list format".into()),
+    }
+    Ok((edgelist, weights))
+}
+
+fn parse_weight(field: Option<&str>, row: &str) -> Result<f64, Box<dyn Error>> {
+    let field = field.ok_or_else(|| format!("expected a weight column in {}", row))?;
+    field
+        .parse()
+        .map_err(|e| format!("invalid weight {:?}: {}", field, e).into())
+}
+
+// Graph from the rows of an edge list, weighted if a weight was read for every row
+fn edge_list_graph(
+    edgelist: Vec<(usize, usize)>,
+    weights: Vec<f64>,
+    vertices: usize,
+    options: &ReadOptions,
+) -> Graph {
+    if options.weighted {
+        let edges = edgelist
+            .into_iter()
+            .zip(weights)
+            .map(|((src, dest), weight)| (src, dest, weight))
+            .collect();
+        if options.directed {
+            Graph::from_weighted_arclist_with_policy(edges, vertices, options.policy)
+        } else {
+            Graph::from_weighted_edgelist_with_policy(edges, vertices, options.policy)
+        }
+    } else if options.directed {
+        Graph::from_arclist_with_policy(edgelist, vertices, options.policy)
+    } else {
+        Graph::from_edgelist_with_policy(edgelist, vertices, options.policy)
+    }
+}
+
+// Non-empty lines that are not comments, split on whitespace
+fn data_lines(
+    file_path: &str,
+    header: bool,
+) -> Result<impl Iterator<Item = std::io::Result<String>>, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(file_path)?);
+    Ok(reader
+        .lines()
+        .skip(header as usize)
+        .filter(|line| match line {
+            Ok(line) => {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#') && !line.starts_with('%')
+            }
+            Err(_) => true,
+        }))
+}
+
+fn read_adjlist(file_path: &str, options: &ReadOptions) -> Result<Graph, Box<dyn Error>> {
+    if options.weighted {
+        return Err("adjacency lists carry no weights, use an edge list".into());
+    }
+    let mut ids = VertexIds::new(options.labelled);
+    let mut rows: Vec<(usize, Vec<usize>)> = Vec::new();
+    for line in data_lines(file_path, options.header)? {
+        let line = line?;
+        let mut fields = line.split_whitespace();
+        let node = ids.get(fields.next().unwrap())?;
+        let neighbors = fields.map(|id| ids.get(id)).collect::<Result<_, _>>()?;
+        rows.push((node, neighbors));
     }
 
-    // Assuming node indices start from 0 and are continuous
-    let vertices = max_node_index + 1;
-    let graph = Graph::from_edgelist(edgelist, vertices);
+    if options.directed {
+        let arcs = rows
+            .into_iter()
+            .flat_map(|(node, neighbors)| neighbors.into_iter().map(move |w| (node, w)))
+            .collect();
+        let graph = Graph::from_arclist_with_policy(arcs, ids.vertices(), options.policy);
+        return Ok(ids.finish(graph));
+    }
+
+    let mut adj_list = vec![Vec::new(); ids.vertices()];
+    for (node, neighbors) in rows {
+        adj_list[node].extend(neighbors);
+    }
+    let graph = Graph::from_adjlist_with_policy(adj_list, options.policy);
+    Ok(ids.finish(graph))
+}
 
-    Ok(graph)
+#[cfg(test)]
+mod tests {
+    use super::{read_graph, InputFormat, ReadOptions};
+
+    fn read(contents: &str, options: &ReadOptions) -> crate::graph::Graph {
+        let path = std::env::temp_dir().join(format!(
+            "mygraph-utils-{}-{:?}.txt",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        std::fs::write(&path, contents).unwrap();
+        let graph = read_graph(path.to_str().unwrap(), options);
+        std::fs::remove_file(&path).unwrap();
+        graph.unwrap()
+    }
+
+    #[test]
+    fn labelled_csv_keeps_labels_in_order_of_appearance() {
+        let options = ReadOptions {
+            header: true,
+            labelled: true,
+            ..ReadOptions::default()
+        };
+        let graph = read("src,dest\nx,y\ny,z\n", &options);
+        assert_eq!(graph.vertices, 3);
+        assert_eq!(graph.label(0), "x");
+        assert_eq!(graph.label(2), "z");
+        assert!(graph.has_edge(2, 1));
+    }
+
+    #[test]
+    fn indices_up_to_the_largest_are_vertices() {
+        let options = ReadOptions {
+            format: InputFormat::Edgelist,
+            ..ReadOptions::default()
+        };
+        let graph = read("0 3\n3  1\n", &options);
+        assert_eq!(graph.vertices, 4);
+        assert_eq!(graph.degree(), vec![1, 1, 0, 2]);
+        assert_eq!(graph.label(2), "2");
+    }
 }
@@ -0,0 +1,501 @@
+extern crate nalgebra as na;
+use crate::graph::Graph;
+use crate::structural::simple_adjacency;
+use na::DMatrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{self, Write};
+
+extern crate rayon;
+use rayon::prelude::*;
+
+// Walker's alias method (Vose's variant): draws from a discrete distribution in
+// constant time after linear-time preprocessing
+#[derive(Clone, Debug)]
+pub struct AliasTable {
+    probability: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    // Table for drawing index i with probability proportional to weights[i]
+    pub fn new(weights: &[f64]) -> Self {
+        let n = weights.len();
+        let total: f64 = weights.iter().sum();
+        let mut probability: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut alias = vec![0; n];
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| probability[i] < 1.0);
+
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            alias[s] = l;
+            probability[l] -= 1.0 - probability[s];
+            if probability[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Whatever is left over is 1 up to rounding
+        for i in small.into_iter().chain(large) {
+            probability[i] = 1.0;
+        }
+
+        AliasTable { probability, alias }
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let i = rng.random_range(0..self.probability.len());
+        if rng.random::<f64>() < self.probability[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+// Settings of node2vec walks (Grover & Leskovec, 2016)
+#[derive(Clone, Debug)]
+pub struct Node2Vec {
+    // Vertices per walk, the start included
+    pub walk_length: usize,
+    pub walks_per_vertex: usize,
+    // Return parameter: high values make stepping back to the previous vertex unlikely
+    pub p: f64,
+    // In-out parameter: high values keep walks local (BFS-like), low values let them
+    // move outward (DFS-like)
+    pub q: f64,
+    pub seed: u64,
+}
+
+impl Default for Node2Vec {
+    fn default() -> Self {
+        Node2Vec {
+            walk_length: 80,
+            walks_per_vertex: 10,
+            p: 1.0,
+            q: 1.0,
+            seed: 42,
+        }
+    }
+}
+
+// Settings of the skip-gram with negative sampling trainer (Mikolov et al., 2013)
+#[derive(Clone, Debug)]
+pub struct SkipGram {
+    pub dimensions: usize,
+    // Largest distance between a vertex and its context within a walk
+    pub window: usize,
+    // Noise vertices drawn per context vertex
+    pub negative: usize,
+    pub epochs: usize,
+    // Initial learning rate, decays linearly to nearly zero
+    pub learning_rate: f64,
+    pub seed: u64,
+}
+
+impl Default for SkipGram {
+    fn default() -> Self {
+        SkipGram {
+            dimensions: 128,
+            window: 5,
+            negative: 5,
+            epochs: 1,
+            learning_rate: 0.025,
+            seed: 42,
+        }
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+// Walks of every round, one per vertex in vertex order, each with its own random
+// stream so that the result does not depend on the number of threads
+fn generate_walks<F>(
+    vertices: usize,
+    walks_per_vertex: usize,
+    seed: u64,
+    walk: F,
+) -> Vec<Vec<usize>>
+where
+    F: Fn(usize, &mut StdRng) -> Vec<usize> + Sync,
+{
+    (0..walks_per_vertex * vertices)
+        .into_par_iter()
+        .map(|i| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(i as u64));
+            walk(i % vertices, &mut rng)
+        })
+        .collect()
+}
+
+// Vertex embeddings from walks with skip-gram and negative sampling, one row per
+// vertex. Noise vertices are drawn by frequency to the power 0.75, as in word2vec.
+pub fn train_skipgram(walks: &[Vec<usize>], vertices: usize, settings: &SkipGram) -> DMatrix<f64> {
+    let dim = settings.dimensions;
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let mut input: Vec<f64> = (0..vertices * dim)
+        .map(|_| (rng.random::<f64>() - 0.5) / dim as f64)
+        .collect();
+    let mut output = vec![0.0; vertices * dim];
+
+    let mut frequency = vec![0.0; vertices];
+    for &v in walks.iter().flatten() {
+        frequency[v] += 1.0;
+    }
+    if frequency.iter().all(|&f| f == 0.0) {
+        return DMatrix::from_row_slice(vertices, dim, &input);
+    }
+    let noise = AliasTable::new(
+        &frequency
+            .iter()
+            .map(|f: &f64| f.powf(0.75))
+            .collect::<Vec<_>>(),
+    );
+
+    let total = (settings.epochs * walks.iter().map(Vec::len).sum::<usize>()).max(1) as f64;
+    let mut processed = 0.0;
+    let mut gradient = vec![0.0; dim];
+    for _ in 0..settings.epochs {
+        for walk in walks {
+            for (i, &center) in walk.iter().enumerate() {
+                let learning_rate = settings.learning_rate * (1.0 - processed / total).max(1e-4);
+                processed += 1.0;
+                // Nearer context counts more, by shrinking the window at random
+                let window = rng.random_range(1..=settings.window.max(1));
+                let start = i.saturating_sub(window);
+                let end = (i + window + 1).min(walk.len());
+                for (j, &context) in walk.iter().enumerate().take(end).skip(start) {
+                    if j == i {
+                        continue;
+                    }
+                    gradient.iter_mut().for_each(|g| *g = 0.0);
+                    let center_row = center * dim..(center + 1) * dim;
+                    for sample in 0..=settings.negative {
+                        let (target, label) = if sample == 0 {
+                            (context, 1.0)
+                        } else {
+                            let target = noise.sample(&mut rng);
+                            if target == context {
+                                continue;
+                            }
+                            (target, 0.0)
+                        };
+                        let target_row = target * dim..(target + 1) * dim;
+                        let dot: f64 = input[center_row.clone()]
+                            .iter()
+                            .zip(&output[target_row.clone()])
+                            .map(|(a, b)| a * b)
+                            .sum();
+                        let g = (label - sigmoid(dot)) * learning_rate;
+                        for ((grad, out), inp) in gradient
+                            .iter_mut()
+                            .zip(&mut output[target_row])
+                            .zip(&input[center_row.clone()])
+                        {
+                            *grad += g * *out;
+                            *out += g * inp;
+                        }
+                    }
+                    for (inp, grad) in input[center_row].iter_mut().zip(&gradient) {
+                        *inp += grad;
+                    }
+                }
+            }
+        }
+    }
+
+    DMatrix::from_row_slice(vertices, dim, &input)
+}
+
+impl Graph {
+    // Uniform random walks of `walk_length` vertices, `walks_per_vertex` from every
+    // vertex. A walk stops early at a vertex without (out-)neighbours.
+    pub fn random_walks(
+        &self,
+        walk_length: usize,
+        walks_per_vertex: usize,
+        seed: u64,
+    ) -> Vec<Vec<usize>> {
+        generate_walks(self.vertices, walks_per_vertex, seed, |start, rng| {
+            let mut walk = vec![start];
+            while walk.len() < walk_length {
+                let neighbors = &self.adj_list[*walk.last().unwrap()];
+                if neighbors.is_empty() {
+                    break;
+                }
+                walk.push(neighbors[rng.random_range(0..neighbors.len())]);
+            }
+            walk
+        })
+    }
+
+    // Second-order node2vec walks. The step from v after arriving from t is drawn
+    // from an alias table per arc (t, v), weighting the next vertex x by 1/p if it
+    // is t, 1 if it is next to t and 1/q otherwise. The tables take memory in the
+    // order of the sum of squared degrees. Self-loops and parallel edges are ignored.
+    pub fn node2vec_walks(&self, settings: &Node2Vec) -> Vec<Vec<usize>> {
+        assert!(
+            settings.p > 0.0 && settings.p.is_finite(),
+            "p must be positive and finite"
+        );
+        assert!(
+            settings.q > 0.0 && settings.q.is_finite(),
+            "q must be positive and finite"
+        );
+        let adjacency = simple_adjacency(self);
+        // Vertices a walk can arrive at v from, sorted
+        let arriving: Vec<Vec<usize>> = match &self.in_list {
+            Some(_) => (0..self.vertices)
+                .into_par_iter()
+                .map(|v| {
+                    let mut predecessors = self.predecessors(v).to_vec();
+                    predecessors.sort_unstable();
+                    predecessors.dedup();
+                    predecessors.retain(|&t| t != v);
+                    predecessors
+                })
+                .collect(),
+            None => adjacency.clone(),
+        };
+        // tables[v][i]: the step from v after arriving from arriving[v][i]
+        let tables: Vec<Vec<AliasTable>> = adjacency
+            .par_iter()
+            .zip(&arriving)
+            .map(|(neighbors, from)| {
+                from.iter()
+                    .map(|&t| {
+                        let weights: Vec<f64> = neighbors
+                            .iter()
+                            .map(|&x| {
+                                if x == t {
+                                    1.0 / settings.p
+                                } else if adjacency[t].binary_search(&x).is_ok() {
+                                    1.0
+                                } else {
+                                    1.0 / settings.q
+                                }
+                            })
+                            .collect();
+                        AliasTable::new(&weights)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        generate_walks(
+            self.vertices,
+            settings.walks_per_vertex,
+            settings.seed,
+            |start, rng| {
+                let mut walk = vec![start];
+                if adjacency[start].is_empty() {
+                    return walk;
+                }
+                if walk.len() < settings.walk_length {
+                    walk.push(adjacency[start][rng.random_range(0..adjacency[start].len())]);
+                }
+                while walk.len() < settings.walk_length {
+                    let (t, v) = (walk[walk.len() - 2], walk[walk.len() - 1]);
+                    if adjacency[v].is_empty() {
+                        break;
+                    }
+                    let i = arriving[v].binary_search(&t).expect("arc into v");
+                    walk.push(adjacency[v][tables[v][i].sample(rng)]);
+                }
+                walk
+            },
+        )
+    }
+
+    // node2vec embedding: node2vec walks fed to the skip-gram trainer
+    pub fn node2vec(&self, walks: &Node2Vec, training: &SkipGram) -> DMatrix<f64> {
+        train_skipgram(&self.node2vec_walks(walks), self.vertices, training)
+    }
+
+    // Write walks as a corpus for word2vec-style trainers: one walk per line,
+    // vertex labels separated by spaces
+    pub fn write_walks<W: Write>(&self, walks: &[Vec<usize>], mut writer: W) -> io::Result<()> {
+        for walk in walks {
+            let line: Vec<String> = walk.iter().map(|&v| self.label(v)).collect();
+            writeln!(writer, "{}", line.join(" "))?;
+        }
+        Ok(())
+    }
+
+    // Write an embedding in the word2vec text format: a "vertices dimensions"
+    // header, then one line per vertex with its label and coordinates
+    pub fn write_embedding<W: Write>(
+        &self,
+        embedding: &DMatrix<f64>,
+        mut writer: W,
+    ) -> io::Result<()> {
+        writeln!(writer, "{} {}", embedding.nrows(), embedding.ncols())?;
+        for (v, row) in embedding.row_iter().enumerate() {
+            let values: Vec<String> = row.iter().map(|x| format!("{:.6}", x)).collect();
+            writeln!(writer, "{} {}", self.label(v), values.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{train_skipgram, AliasTable, DMatrix, Node2Vec, SkipGram};
+    use crate::graph::Graph;
+    use crate::testutil;
+
+    // Every step of every walk follows an edge or arc of the graph
+    fn assert_walks_follow_edges(graph: &Graph, walks: &[Vec<usize>], length: usize) {
+        for walk in walks {
+            assert!(!walk.is_empty() && walk.len() <= length);
+            for pair in walk.windows(2) {
+                assert!(graph.successors(pair[0]).contains(&pair[1]));
+            }
+            if walk.len() < length {
+                assert!(graph.successors(*walk.last().unwrap()).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn alias_table_draws_in_proportion() {
+        let weights = [1.0, 0.0, 3.0, 0.5, 5.5];
+        let table = AliasTable::new(&weights);
+        let mut rng = testutil::rng();
+        let draws = 200_000;
+        let mut counts = [0usize; 5];
+        for _ in 0..draws {
+            counts[table.sample(&mut rng)] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        for (count, weight) in counts.iter().zip(weights) {
+            assert!((*count as f64 / draws as f64 - weight / 10.0).abs() < 0.005);
+        }
+    }
+
+    #[test]
+    fn walks_follow_edges() {
+        let graph = Graph::from_edgelist(vec![(0, 1), (1, 2), (2, 0), (2, 3), (4, 4)], 6);
+        let walks = graph.random_walks(10, 3, 7);
+        assert_eq!(walks.len(), 18);
+        assert_eq!(walks[7][0], 1);
+        assert_walks_follow_edges(&graph, &walks, 10);
+        assert_eq!(walks, graph.random_walks(10, 3, 7));
+
+        // Walks along arcs stop at the sink 3
+        let digraph = Graph::from_arclist(vec![(0, 1), (1, 2), (2, 0), (2, 3)], 4);
+        assert_walks_follow_edges(&digraph, &digraph.random_walks(10, 5, 7), 10);
+        let settings = Node2Vec {
+            walk_length: 10,
+            walks_per_vertex: 5,
+            ..Node2Vec::default()
+        };
+        assert_walks_follow_edges(&digraph, &digraph.node2vec_walks(&settings), 10);
+        // Loops are dropped by node2vec, so 4 stays put
+        let walks = graph.node2vec_walks(&settings);
+        assert_walks_follow_edges(&graph, &walks[..4], 10);
+        assert_eq!(walks[4], vec![4]);
+    }
+
+    #[test]
+    fn node2vec_steps_are_biased_by_p_and_q() {
+        // After 0 -> 1 the walk returns to 0 (weight 1/p), stays next to 0 at 2
+        // (weight 1) or moves away to 3 (weight 1/q)
+        let graph = Graph::from_edgelist(vec![(0, 1), (0, 2), (1, 2), (1, 3)], 4);
+        let settings = Node2Vec {
+            walk_length: 3,
+            walks_per_vertex: 40_000,
+            p: 0.5,
+            q: 2.0,
+            seed: 7,
+        };
+        let mut counts = [0usize; 4];
+        for walk in graph.node2vec_walks(&settings) {
+            if walk[..2] == [0, 1] {
+                counts[walk[2]] += 1;
+            }
+        }
+        let total: usize = counts.iter().sum();
+        let expected = [2.0 / 3.5, 0.0, 1.0 / 3.5, 0.5 / 3.5];
+        for (count, expected) in counts.iter().zip(expected) {
+            assert!((*count as f64 / total as f64 - expected).abs() < 0.015);
+        }
+    }
+
+    #[test]
+    fn skipgram_separates_disconnected_cliques() {
+        let mut edges = Vec::new();
+        for offset in [0, 5] {
+            for u in 0..5 {
+                for v in u + 1..5 {
+                    edges.push((offset + u, offset + v));
+                }
+            }
+        }
+        let graph = Graph::from_edgelist(edges, 10);
+        let walks = graph.random_walks(20, 20, 7);
+        let settings = SkipGram {
+            dimensions: 8,
+            epochs: 5,
+            ..SkipGram::default()
+        };
+        let embedding = train_skipgram(&walks, 10, &settings);
+        assert_eq!(embedding.shape(), (10, 8));
+        assert!(embedding.iter().all(|x| x.is_finite()));
+        let cosine = |u: usize, v: usize| {
+            let (a, b) = (embedding.row(u), embedding.row(v));
+            a.dot(&b) / (a.norm() * b.norm())
+        };
+        for u in 0..10 {
+            for v in 0..10 {
+                for w in 0..10 {
+                    if u != v && u / 5 == v / 5 && u / 5 != w / 5 {
+                        assert!(cosine(u, v) > cosine(u, w));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn corpus_and_embedding_formats() {
+        let graph = Graph::from_edgelist(vec![(0, 1)], 2)
+            .with_labels(vec!["a".to_string(), "b".to_string()]);
+        let mut corpus = Vec::new();
+        graph
+            .write_walks(&[vec![0, 1, 0], vec![1]], &mut corpus)
+            .unwrap();
+        assert_eq!(String::from_utf8(corpus).unwrap(), "a b a\nb\n");
+
+        let embedding = DMatrix::from_row_slice(2, 2, &[0.5, -1.0, 0.0, 0.25]);
+        let mut text = Vec::new();
+        graph.write_embedding(&embedding, &mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "2 2\na 0.500000 -1.000000\nb 0.000000 0.250000\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "q must be positive")]
+    fn node2vec_rejects_zero_q() {
+        let settings = Node2Vec {
+            q: 0.0,
+            ..Node2Vec::default()
+        };
+        Graph::from_edgelist(vec![(0, 1)], 2).node2vec_walks(&settings);
+    }
+
+    #[test]
+    #[should_panic(expected = "p must be positive")]
+    fn node2vec_rejects_infinite_p() {
+        let settings = Node2Vec {
+            p: f64::INFINITY,
+            ..Node2Vec::default()
+        };
+        Graph::from_edgelist(vec![(0, 1)], 2).node2vec_walks(&settings);
+    }
+}